@@ -0,0 +1,12 @@
+use std::env;
+
+fn main() {
+    println!("cargo::rustc-check-cfg=cfg(alloc_backend)");
+
+    // `alloc` takes precedence when both backends are enabled, unless `prefer-heapless` is.
+    let alloc = env::var_os("CARGO_FEATURE_ALLOC").is_some();
+    let prefer_heapless = env::var_os("CARGO_FEATURE_PREFER_HEAPLESS").is_some();
+    if alloc && !prefer_heapless {
+        println!("cargo::rustc-cfg=alloc_backend");
+    }
+}
@@ -0,0 +1,187 @@
+//! Bit-level reading and writing, for packed formats whose fields are not byte-aligned.
+//!
+//! Fields of any width (up to 64 bits) are written to a [`Vec<u8, N>`] through a [`BitWriter`]
+//! and read back from any byte slice through a [`BitReader`], in the [`BitOrder`] of the format.
+//!
+//! # Example
+//!
+//! ```
+//! use mayheap::{
+//!     bits::{BitOrder, BitReader, BitWriter},
+//!     Vec,
+//! };
+//!
+//! let mut buf = Vec::<u8, 4>::new();
+//! let mut writer = BitWriter::new(&mut buf, BitOrder::MsbFirst);
+//! writer.write_bits(0b101, 3).unwrap();
+//! writer.write_bool(true).unwrap();
+//! writer.write_bits(0x3ff, 10).unwrap();
+//! assert_eq!(buf, [0b1011_1111, 0b1111_1100]);
+//!
+//! let mut reader = BitReader::new(&buf, BitOrder::MsbFirst);
+//! assert_eq!(reader.read_bits(3), Some(0b101));
+//! assert_eq!(reader.read_bool(), Some(true));
+//! assert_eq!(reader.read_bits(10), Some(0x3ff));
+//! // Only the padding is left.
+//! assert_eq!(reader.remaining(), 2);
+//! assert_eq!(reader.read_bits(3), None);
+//! ```
+
+use crate::Vec;
+
+/// The order in which the bits of fields are laid out in the bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BitOrder {
+    /// The most significant bit of a field comes first, filling each byte from its most
+    /// significant bit (as in most network protocols).
+    MsbFirst,
+    /// The least significant bit of a field comes first, filling each byte from its least
+    /// significant bit.
+    LsbFirst,
+}
+
+impl BitOrder {
+    // The shift of the bit at `index` (0-7) within a byte.
+    #[inline]
+    fn shift(self, index: u32) -> u32 {
+        match self {
+            BitOrder::MsbFirst => 7 - index,
+            BitOrder::LsbFirst => index,
+        }
+    }
+}
+
+/// Writes fields of arbitrary bit widths to the end of a [`Vec<u8, N>`].
+///
+/// The first field starts at the byte boundary after the existing contents. The unused bits of
+/// the last byte are zero.
+#[derive(Debug)]
+pub struct BitWriter<'a, const N: usize> {
+    buf: &'a mut Vec<u8, N>,
+    order: BitOrder,
+    // The number of bits used in the last byte, `0` meaning none or all of them.
+    used: u32,
+}
+
+impl<'a, const N: usize> BitWriter<'a, N> {
+    /// Creates a new writer appending to `buf`, with the bits laid out in `order`.
+    #[inline]
+    pub fn new(buf: &'a mut Vec<u8, N>, order: BitOrder) -> Self {
+        Self {
+            buf,
+            order,
+            used: 0,
+        }
+    }
+
+    /// Writes the lower `width` bits of `value`.
+    ///
+    /// If `width` is larger than 64, `value` is zero-extended. Nothing is written on failure.
+    #[inline]
+    pub fn write_bits(&mut self, value: u64, width: u32) -> crate::Result<()> {
+        let partial = usize::from(self.used > 0);
+        let needed = (self.used as usize + width as usize).div_ceil(8) - partial;
+        self.buf.try_reserve(needed)?;
+
+        for i in 0..width {
+            let index = match self.order {
+                BitOrder::MsbFirst => width - 1 - i,
+                BitOrder::LsbFirst => i,
+            };
+            let bit = value.checked_shr(index).unwrap_or(0) & 1;
+            if self.used == 0 {
+                // We just made sure it fits.
+                let _ = self.buf.push(0);
+            }
+            if let Some(byte) = self.buf.last_mut() {
+                *byte |= (bit as u8) << self.order.shift(self.used);
+            }
+            self.used = (self.used + 1) % 8;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a single bit.
+    #[inline]
+    pub fn write_bool(&mut self, value: bool) -> crate::Result<()> {
+        self.write_bits(value.into(), 1)
+    }
+
+    /// Returns `true` if the next field starts at a byte boundary.
+    #[inline]
+    pub fn is_aligned(&self) -> bool {
+        self.used == 0
+    }
+
+    /// Skips the unused bits of the last byte, so that the next field starts at a byte boundary.
+    #[inline]
+    pub fn align(&mut self) {
+        self.used = 0;
+    }
+}
+
+/// Reads fields of arbitrary bit widths from a byte slice.
+#[derive(Debug, Clone)]
+pub struct BitReader<'a> {
+    buf: &'a [u8],
+    order: BitOrder,
+    // The position in bits.
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// Creates a new reader from the start of `buf`, with the bits laid out in `order`.
+    #[inline]
+    pub fn new(buf: &'a [u8], order: BitOrder) -> Self {
+        Self { buf, order, pos: 0 }
+    }
+
+    /// Reads a field of `width` bits.
+    ///
+    /// Returns `None`, without advancing, if there are less than `width` bits left or if `width`
+    /// is larger than 64.
+    #[inline]
+    pub fn read_bits(&mut self, width: u32) -> Option<u64> {
+        if width > u64::BITS || width as usize > self.remaining() {
+            return None;
+        }
+
+        let mut value = 0;
+        for i in 0..width {
+            let byte = self.buf[self.pos / 8];
+            let bit = u64::from(byte >> self.order.shift((self.pos % 8) as u32) & 1);
+            match self.order {
+                BitOrder::MsbFirst => value = value << 1 | bit,
+                BitOrder::LsbFirst => value |= bit << i,
+            }
+            self.pos += 1;
+        }
+
+        Some(value)
+    }
+
+    /// Reads a single bit.
+    #[inline]
+    pub fn read_bool(&mut self) -> Option<bool> {
+        self.read_bits(1).map(|bit| bit == 1)
+    }
+
+    /// Returns the position in bits.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Returns the number of bits left.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.buf.len() * 8 - self.pos
+    }
+
+    /// Skips the rest of the current byte, so that the next field starts at a byte boundary.
+    #[inline]
+    pub fn align(&mut self) {
+        self.pos = self.pos.next_multiple_of(8);
+    }
+}
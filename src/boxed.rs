@@ -4,7 +4,7 @@
 //! when either:
 //!
 //! - `alloc` feature is enabled, or
-//! - `heapless` and `portable-atomic` features are enabled.
+//! - `heapless` and either `portable-atomic` or `critical-section` features are enabled.
 //!
 //! # Usage
 //!
@@ -14,6 +14,9 @@
 //! // Create a pool for u32 type with a capacity of 2.
 //! box_pool!(MyBoxPool: u32, 2);
 //!
+//! // Optionally, initialize the pool upfront so it's not done lazily on first allocation.
+//! MyBoxPool.init();
+//!
 //! // Allocate a new boxed value from the pool.
 //! let mut boxed = MyBoxPool.alloc(42).unwrap();
 //! assert_eq!(*boxed, 42);
@@ -36,10 +39,165 @@
 //!     // This will not.
 //!     let res = MyBoxPool.alloc(45);
 //!     assert_eq!(res, Err(45));
+//!     // Or if you don't need the value back.
+//!     let res = MyBoxPool.try_alloc(45);
+//!     assert_eq!(res.unwrap_err(), mayheap::Error::PoolExhausted);
 //! }
+//!
+//! // Deep copies are allocated from the same pool.
+//! drop(boxed);
+//! let boxed = _boxed.try_clone().unwrap();
+//! assert_eq!(*boxed, 43);
+//! drop(boxed);
+//!
+//! // Values can also be constructed directly in the allocated memory.
+//! let boxed = MyBoxPool.alloc_init(|slot| slot.write(46)).unwrap();
+//! assert_eq!(*boxed, 46);
+//! drop(boxed);
+//!
+//! // Values can also be default-initialized, and later replaced or taken out of the box.
+//! let mut boxed = MyBoxPool.alloc_default().unwrap();
+//! assert_eq!(*boxed, 0);
+//! assert_eq!(Box::replace(&mut boxed, 48), 0);
+//! assert_eq!(Box::take(&mut boxed), 48);
+//! assert_eq!(*boxed, 0);
+//! drop(boxed);
+//!
+//! // Boxes can be converted to raw pointers, e.g to pass them through FFI, and back.
+//! let ptr = Box::into_raw(MyBoxPool.alloc(49).unwrap());
+//! let boxed = unsafe { Box::<MyBoxPool>::from_raw(ptr) };
+//! assert_eq!(*boxed, 49);
+//! drop(boxed);
+//!
+//! // Multiple values can be allocated at once, which either succeeds for all or none of them.
+//! let res = MyBoxPool.alloc_iter::<_, 3>([50, 51, 52]);
+//! #[cfg(feature = "heapless")]
+//! assert_eq!(res.unwrap_err(), mayheap::Error::PoolExhausted);
+//! #[cfg(feature = "alloc")]
+//! assert_eq!(res.unwrap().len(), 3);
+//!
+//! // Pinned allocations are also possible.
+//! let pinned = MyBoxPool.alloc_pinned(47).unwrap();
+//! assert_eq!(*pinned, 47);
 //! ```
+//!
+//! # Attributes
+//!
+//! Attributes, including doc comments and derives, can be given for the pool type. `cfg`
+//! attributes apply to all the generated items:
+//!
+//! ```
+//! use mayheap::{box_pool, boxed::BoxPool};
+//!
+//! box_pool!(
+//!     /// The pool for sensor readings.
+//!     #[cfg(not(feature = "no-sensors"))]
+//!     #[derive(Default, Hash)]
+//!     pub ReadingPool: u16,
+//!     4
+//! );
+//!
+//! let reading = ReadingPool::default().alloc(512).unwrap();
+//! assert_eq!(*reading, 512);
+//! ```
+//!
+//! Attributes wrapped in `#[storage(...)]` apply to the `static` holding the blocks of the pool
+//! instead, e.g. for placing them in a DMA-capable or otherwise non-default memory region through
+//! `link_section`. They're ignored with `alloc`, since the blocks are then allocated on the heap.
+//!
+//! ```
+//! use mayheap::{box_pool, boxed::BoxPool};
+//!
+//! box_pool!(
+//!     #[storage(cfg_attr(target_os = "none", link_section = ".sram2"))]
+//!     DmaPool: [u8; 64],
+//!     2
+//! );
+//!
+//! let buf = DmaPool.alloc([0; 64]).unwrap();
+//! assert_eq!(buf.len(), 64);
+//! ```
+//!
+//! # Generic data types
+//!
+//! The data type can be any `'static` type, including instantiations of generic types. However,
+//! pools themselves can't be generic since each pool needs its own `static` memory when `heapless`
+//! feature is enabled. Create a pool for each concrete type instead:
+//!
+//! ```
+//! use mayheap::{box_pool, boxed::BoxPool, Vec};
+//!
+//! box_pool!(FramePool: Vec<u8, 16>, 2);
+//! box_pool!(EntryPool: (u8, &'static str), 2);
+//!
+//! let frame = FramePool.alloc(Vec::from_slice(&[1, 2, 3]).unwrap()).unwrap();
+//! assert_eq!(*frame, [1, 2, 3]);
+//! let entry = EntryPool.alloc((1, "one")).unwrap();
+//! assert_eq!(*entry, (1, "one"));
+//! ```
+//!
+//! # Strings and byte buffers
+//!
+//! Variable-length payloads can be allocated from pools created with [`crate::str_pool`] and
+//! [`crate::bytes_pool`], which copy the given slices into the allocated memory. For payloads of
+//! widely varying lengths, [`crate::size_class_pool`] combines several byte buffer pools of
+//! different block sizes:
+//!
+//! ```
+//! use mayheap::{bytes_pool, str_pool};
+//!
+//! str_pool!(NamePool: 16, 2);
+//! bytes_pool!(FramePool: 64, 2);
+//!
+//! let name = NamePool.alloc_str("sensor-1").unwrap();
+//! assert_eq!(name.as_str(), "sensor-1");
+//! let frame = FramePool.alloc_bytes(&[0x7e, 0x01, 0x7e]).unwrap();
+//! assert_eq!(&frame[..], [0x7e, 0x01, 0x7e]);
+//!
+//! // Doesn't fit in a block so should fail with `heapless` but not with `alloc`.
+//! let res = NamePool.alloc_str("a-much-longer-sensor-name");
+//! #[cfg(feature = "heapless")]
+//! assert_eq!(res.unwrap_err().kind(), mayheap::ErrorKind::BufferOverflow);
+//! #[cfg(feature = "alloc")]
+//! assert_eq!(res.unwrap().len(), 25);
+//! ```
+
+use core::{
+    cmp::Ordering,
+    fmt,
+    future::Future,
+    hash::{Hash, Hasher},
+    mem::{ManuallyDrop, MaybeUninit},
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
 
-use core::ops::{Deref, DerefMut};
+#[cfg(any(
+    feature = "portable-atomic",
+    feature = "critical-section",
+    target_has_atomic = "ptr"
+))]
+mod slot;
+#[cfg(any(
+    feature = "portable-atomic",
+    feature = "critical-section",
+    target_has_atomic = "ptr"
+))]
+pub use slot::AtomicSlot;
+mod static_pool;
+pub use static_pool::{StaticBox, StaticBoxPool};
+mod sync;
+#[cfg(any(
+    feature = "portable-atomic",
+    feature = "critical-section",
+    target_has_atomic = "ptr"
+))]
+#[doc(hidden)]
+pub use sync::Counter as __Counter;
+#[cfg(not(alloc_backend))]
+#[doc(hidden)]
+pub use sync::{Once as __Once, Waiters as __Waiters};
 
 /// A singleton that manages `pool::boxed::Box`-es.
 ///
@@ -48,25 +206,279 @@ pub trait BoxPool {
     /// The data type managed by the memory pool.
     type Data;
     /// The implementation-specific type of the boxed value.
+    ///
+    /// This must be a pointer type that never moves the data it points to.
     type BoxedValue: DerefMut<Target = Self::Data>;
 
     /// Allocates a new boxed value from the pool.
     fn alloc(&self, value: Self::Data) -> Result<Box<Self>, Self::Data>
     where
         Self: Sized;
+
+    /// Allocates a new boxed value from the pool.
+    ///
+    /// Unlike [`BoxPool::alloc`], this fails with [`crate::Error::PoolExhausted`] if there are no
+    /// free blocks left in the pool, so it can be propagated with `?` alongside other errors. The
+    /// value is dropped in that case.
+    fn try_alloc(&self, value: Self::Data) -> Result<Box<Self>, crate::Error>
+    where
+        Self: Sized,
+    {
+        self.alloc(value).map_err(|_| crate::Error::PoolExhausted)
+    }
+
+    /// Allocates a new boxed value from the pool, initializing it in place through `init`.
+    ///
+    /// `init` must initialize the given slot and return a reference to the initialized value (e.g
+    /// through [`MaybeUninit::write`]). This allows large values to be constructed directly in the
+    /// allocated memory, rather than on the stack first.
+    ///
+    /// Fails with [`crate::Error::PoolExhausted`] if there are no free blocks left in the pool, in
+    /// which case `init` is not called.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `init` returns a reference to anything other than the given slot.
+    fn alloc_init<F>(&self, init: F) -> Result<Box<Self>, crate::Error>
+    where
+        F: FnOnce(&mut MaybeUninit<Self::Data>) -> &mut Self::Data,
+        Self: Sized;
+
+    /// Allocates a new uninitialized block from the pool.
+    ///
+    /// The block can then be filled in place (e.g by a DMA engine or a decoder) and converted into
+    /// a [`Box`] through [`UninitBox::write`] or [`UninitBox::assume_init`], without
+    /// constructing a value first.
+    ///
+    /// Fails with [`crate::Error::PoolExhausted`] if there are no free blocks left in the pool.
+    fn alloc_uninit(&self) -> Result<UninitBox<Self>, crate::Error>
+    where
+        Self: Sized;
+
+    /// Allocates a new boxed value from the pool, holding the value returned by `f`.
+    ///
+    /// Unlike [`BoxPool::alloc`], `f` is only called once the memory has been allocated, which
+    /// allows the compiler to construct the value directly in it. See [`BoxPool::alloc_init`] for
+    /// a way to guarantee that.
+    ///
+    /// Fails with [`crate::Error::PoolExhausted`] if there are no free blocks left in the pool, in
+    /// which case `f` is not called.
+    fn alloc_with<F>(&self, f: F) -> Result<Box<Self>, crate::Error>
+    where
+        F: FnOnce() -> Self::Data,
+        Self: Sized,
+    {
+        self.alloc_init(|slot| slot.write(f()))
+    }
+
+    /// Allocates a new boxed value from the pool, holding the default value of the data type.
+    ///
+    /// Fails with [`crate::Error::PoolExhausted`] if there are no free blocks left in the pool.
+    fn alloc_default(&self) -> Result<Box<Self>, crate::Error>
+    where
+        Self::Data: Default,
+        Self: Sized,
+    {
+        self.alloc_with(Default::default)
+    }
+
+    /// Allocates a new boxed value from the pool for each of the values in `iter`.
+    ///
+    /// This is all or nothing: if any of the allocations fail, all the boxes allocated so far are
+    /// dropped and freed again. Fails with [`crate::Error::PoolExhausted`] if there are no free
+    /// blocks left in the pool, or [`crate::Error::BufferOverflow`] if there are more than `N`
+    /// values.
+    fn alloc_iter<I, const N: usize>(&self, iter: I) -> crate::Result<crate::Vec<Box<Self>, N>>
+    where
+        I: IntoIterator<Item = Self::Data>,
+        Self: Sized,
+    {
+        let mut boxes = crate::Vec::new();
+        for value in iter {
+            boxes
+                .push(self.try_alloc(value)?)
+                .map_err(|_| crate::Error::overflow::<crate::Vec<Box<Self>, N>>(N, 1, N))?;
+        }
+
+        Ok(boxes)
+    }
+
+    /// Allocates a new pinned boxed value from the pool.
+    ///
+    /// See [`Box::into_pin`] for details.
+    fn alloc_pinned(&self, value: Self::Data) -> Result<Pin<Box<Self>>, Self::Data>
+    where
+        Self: Sized,
+    {
+        self.alloc(value).map(Box::into_pin)
+    }
+
+    /// Allocates a new boxed value from the pool, waiting for a free block if needed.
+    ///
+    /// With `alloc`, this resolves immediately. With `heapless`, the waiting tasks are all woken up
+    /// when a box allocated from the pool is dropped, and the first one to be polled gets the
+    /// block. Up to 8 tasks can wait on the same pool at the same time. Any further ones are polled
+    /// again right away, busy-waiting until a waiting task is done.
+    fn alloc_async(&self, value: Self::Data) -> AllocFuture<'_, Self>
+    where
+        Self: Sized,
+    {
+        AllocFuture {
+            pool: self,
+            value: Some(value),
+            waiter: None,
+        }
+    }
+
+    /// Returns the singleton instance of the pool.
+    fn instance() -> &'static Self
+    where
+        Self: Sized + 'static;
+
+    /// `AllocFuture` implementation detail.
+    ///
+    /// Registers `waker` in the waiter slot `slot`, claiming a free one first if it's `None`.
+    /// Returns `false` if all the slots are claimed.
+    #[doc(hidden)]
+    fn __register_waker(slot: &mut Option<usize>, waker: &Waker) -> bool;
+
+    /// `AllocFuture` implementation detail.
+    ///
+    /// Frees the waiter slot `slot`.
+    #[doc(hidden)]
+    fn __unregister_waker(_slot: usize) {}
+
+    /// `PoolObserver` implementation detail.
+    #[doc(hidden)]
+    fn __observe(_allocated: bool) {}
+
+    /// `Box::into_raw` implementation detail.
+    #[doc(hidden)]
+    fn __into_raw(boxed: Self::BoxedValue) -> *mut Self::Data;
+
+    /// `Box::from_raw` implementation detail.
+    #[doc(hidden)]
+    unsafe fn __from_raw(ptr: *mut Self::Data) -> Self::BoxedValue;
+
+    /// `UninitBox` implementation detail.
+    ///
+    /// Frees the block of `ptr`, without dropping its value.
+    #[doc(hidden)]
+    unsafe fn __free_uninit(ptr: *mut Self::Data);
 }
 
 /// A boxed value managed by a [`BoxPool`].
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub struct Box<P: BoxPool>(P::BoxedValue);
+pub struct Box<P: BoxPool>(ManuallyDrop<P::BoxedValue>);
 
 impl<P: BoxPool> Box<P> {
     /// Allocates a new boxed value from the pool.
     pub fn new(value: P::BoxedValue) -> Self {
-        Self(value)
+        P::__observe(true);
+
+        Self(ManuallyDrop::new(value))
+    }
+
+    /// Converts a `Box<P>` into a `Pin<Box<P>>`.
+    ///
+    /// The boxed value is never moved, neither by the global allocator nor by a memory pool, so
+    /// this is always safe. This doesn't allocate.
+    pub fn into_pin(boxed: Self) -> Pin<Self> {
+        // SAFETY: The data is never moved by any of the backends (see `BoxPool::BoxedValue`) and
+        // `Box` doesn't provide any API to move the data out without consuming it.
+        unsafe { Pin::new_unchecked(boxed) }
+    }
+
+    /// Consumes the box, returning a raw pointer to the boxed value.
+    ///
+    /// Neither is the value dropped, nor the memory freed. Use [`Box::from_raw`] to convert the
+    /// pointer back into a box, which takes care of both.
+    pub fn into_raw(boxed: Self) -> *mut P::Data {
+        let mut boxed = ManuallyDrop::new(boxed);
+        // SAFETY: `boxed` is never used again, and its `Drop` is never run.
+        P::__into_raw(unsafe { ManuallyDrop::take(&mut boxed.0) })
+    }
+
+    /// Constructs a box from a raw pointer.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by [`Box::into_raw`] for a box of the same pool `P` and must
+    /// not have been converted back into a box already. With `alloc`, pointers returned by
+    /// `alloc::boxed::Box::into_raw` for the same data type are also fine, while with `heapless`
+    /// the pointer must point into the memory of the pool `P`.
+    pub unsafe fn from_raw(ptr: *mut P::Data) -> Self {
+        // SAFETY: The caller guarantees that the pointer came from this pool.
+        Self(ManuallyDrop::new(unsafe { P::__from_raw(ptr) }))
+    }
+
+    /// Takes the boxed value, leaving the default value of the data type in its place.
+    ///
+    /// This is an associated function so it doesn't shadow any `take` method of the data type.
+    pub fn take(boxed: &mut Self) -> P::Data
+    where
+        P::Data: Default,
+    {
+        core::mem::take(boxed)
+    }
+
+    /// Replaces the boxed value with `value`, returning the old value.
+    ///
+    /// This is an associated function so it doesn't shadow any `replace` method of the data type.
+    pub fn replace(boxed: &mut Self, value: P::Data) -> P::Data {
+        core::mem::replace(boxed, value)
+    }
+
+    /// Allocates a new boxed value from the pool, holding a clone of this one's value.
+    ///
+    /// Fails with [`crate::Error::PoolExhausted`] if there are no free blocks left in the pool.
+    pub fn try_clone(&self) -> Result<Self, crate::Error>
+    where
+        P: 'static,
+        P::Data: Clone,
+    {
+        P::instance().try_alloc(self.deref().clone())
+    }
+}
+
+impl<P: BoxPool> Drop for Box<P> {
+    fn drop(&mut self) {
+        // SAFETY: The value is never used again.
+        unsafe { ManuallyDrop::drop(&mut self.0) };
+
+        P::__observe(false);
     }
 }
 
+/// Panics if there are no free blocks left in the pool. See [`Box::try_clone`] for the fallible
+/// alternative.
+#[cfg(not(feature = "panic-free"))]
+impl<P: BoxPool> Clone for Box<P>
+where
+    P::BoxedValue: Clone,
+{
+    fn clone(&self) -> Self {
+        Self::new(self.0.deref().clone())
+    }
+}
+
+impl<P: BoxPool> fmt::Debug for Box<P>
+where
+    P::BoxedValue: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Box").field(self.0.deref()).finish()
+    }
+}
+
+impl<P: BoxPool> From<Box<P>> for Pin<Box<P>> {
+    fn from(boxed: Box<P>) -> Self {
+        Box::into_pin(boxed)
+    }
+}
+
+// Like `alloc::boxed::Box`, the box itself can be moved around freely even if the data can't be.
+impl<P: BoxPool> Unpin for Box<P> {}
+
 impl<P: BoxPool> Deref for Box<P> {
     type Target = P::Data;
 
@@ -81,14 +493,649 @@ impl<P: BoxPool> DerefMut for Box<P> {
     }
 }
 
+impl<P: BoxPool> AsRef<P::Data> for Box<P> {
+    fn as_ref(&self) -> &P::Data {
+        self
+    }
+}
+
+impl<P: BoxPool> AsMut<P::Data> for Box<P> {
+    fn as_mut(&mut self) -> &mut P::Data {
+        self
+    }
+}
+
+impl<P: BoxPool> fmt::Display for Box<P>
+where
+    P::Data: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl<P: BoxPool> fmt::Pointer for Box<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ptr: *const P::Data = self.deref();
+
+        fmt::Pointer::fmt(&ptr, f)
+    }
+}
+
+impl<P: BoxPool> Hash for Box<P>
+where
+    P::Data: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.deref().hash(state)
+    }
+}
+
+impl<P: BoxPool> PartialEq for Box<P>
+where
+    P::Data: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.deref().eq(other.deref())
+    }
+}
+
+impl<P: BoxPool> Eq for Box<P> where P::Data: Eq {}
+
+impl<P: BoxPool> PartialOrd for Box<P>
+where
+    P::Data: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.deref().partial_cmp(other.deref())
+    }
+}
+
+impl<P: BoxPool> Ord for Box<P>
+where
+    P::Data: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.deref().cmp(other.deref())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<P: BoxPool> serde_core::Serialize for Box<P>
+where
+    P::Data: serde_core::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde_core::Serializer,
+    {
+        self.deref().serialize(serializer)
+    }
+}
+
+/// Deserializes the value and allocates a box for it from the pool `P`.
+///
+/// Fails if there are no free blocks left in the pool.
+#[cfg(feature = "serde")]
+impl<'de, P: BoxPool + 'static> serde_core::Deserialize<'de> for Box<P>
+where
+    P::Data: serde_core::Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde_core::Deserializer<'de>,
+    {
+        let value = P::Data::deserialize(deserializer)?;
+
+        P::instance()
+            .try_alloc(value)
+            .map_err(serde_core::de::Error::custom)
+    }
+}
+
+/// An uninitialized block allocated from a [`BoxPool`].
+///
+/// This struct is created by [`BoxPool::alloc_uninit`]. It dereferences to a
+/// [`MaybeUninit<P::Data>`] for filling the block in place. Dropping it frees the block, without
+/// dropping any value in it.
+///
+/// # Example
+///
+/// ```
+/// use mayheap::{box_pool, boxed::BoxPool};
+///
+/// box_pool!(FramePool: [u8; 64], 2);
+///
+/// let mut block = FramePool.alloc_uninit().unwrap();
+/// // E.g. let the DMA engine fill the block.
+/// let ptr = block.as_mut_ptr().cast::<u8>();
+/// unsafe { ptr.write_bytes(0x7e, 64) };
+/// // SAFETY: All the bytes have been written.
+/// let frame = unsafe { block.assume_init() };
+/// assert_eq!(frame[63], 0x7e);
+///
+/// let frame = FramePool.alloc_uninit().unwrap().write([1; 64]);
+/// assert_eq!(frame[0], 1);
+/// ```
+pub struct UninitBox<P: BoxPool> {
+    ptr: core::ptr::NonNull<P::Data>,
+}
+
+// SAFETY: The block is owned, just like the value of a `Box`.
+unsafe impl<P: BoxPool> Send for UninitBox<P> where Box<P>: Send {}
+
+// SAFETY: Same as above.
+unsafe impl<P: BoxPool> Sync for UninitBox<P> where Box<P>: Sync {}
+
+impl<P: BoxPool> UninitBox<P> {
+    /// `box_pool!` implementation detail.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a free block of the pool `P`, taken out of the pool.
+    #[doc(hidden)]
+    pub unsafe fn __new(ptr: *mut P::Data) -> Self {
+        P::__observe(true);
+
+        Self {
+            // SAFETY: The caller guarantees that `ptr` points to a block.
+            ptr: unsafe { core::ptr::NonNull::new_unchecked(ptr) },
+        }
+    }
+
+    /// Returns a raw pointer to the block.
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut P::Data {
+        self.ptr.as_ptr()
+    }
+
+    /// Writes `value` to the block, converting it into a [`Box`].
+    ///
+    /// Use `MaybeUninit::write(&mut *block, value)` to write through the `Deref` implementation
+    /// instead, keeping the block uninitialized as far as the type system is concerned.
+    #[inline]
+    pub fn write(mut self, value: P::Data) -> Box<P> {
+        MaybeUninit::write(&mut *self, value);
+
+        // SAFETY: We just initialized the value.
+        unsafe { self.assume_init() }
+    }
+
+    /// Converts the block into a [`Box`], assuming it's been initialized.
+    ///
+    /// # Safety
+    ///
+    /// The block must hold a valid value of `P::Data`. See [`MaybeUninit::assume_init`].
+    #[inline]
+    pub unsafe fn assume_init(self) -> Box<P> {
+        let this = ManuallyDrop::new(self);
+
+        // SAFETY: The block belongs to `P` and the caller guarantees that it's initialized. The
+        // block is already accounted for, so we don't go through `Box::new`.
+        Box(ManuallyDrop::new(unsafe {
+            P::__from_raw(this.ptr.as_ptr())
+        }))
+    }
+}
+
+impl<P: BoxPool> Deref for UninitBox<P> {
+    type Target = MaybeUninit<P::Data>;
+
+    fn deref(&self) -> &MaybeUninit<P::Data> {
+        // SAFETY: `MaybeUninit<T>` has the same layout as `T` and the block is owned by us.
+        unsafe { self.ptr.cast().as_ref() }
+    }
+}
+
+impl<P: BoxPool> DerefMut for UninitBox<P> {
+    fn deref_mut(&mut self) -> &mut MaybeUninit<P::Data> {
+        // SAFETY: `MaybeUninit<T>` has the same layout as `T` and the block is owned by us.
+        unsafe { self.ptr.cast().as_mut() }
+    }
+}
+
+impl<P: BoxPool> Drop for UninitBox<P> {
+    fn drop(&mut self) {
+        // SAFETY: The block belongs to `P` and is never used again.
+        unsafe { P::__free_uninit(self.ptr.as_ptr()) };
+
+        P::__observe(false);
+    }
+}
+
+impl<P: BoxPool> fmt::Debug for UninitBox<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UninitBox").finish_non_exhaustive()
+    }
+}
+
+/// `str_pool!` implementation detail.
+#[doc(hidden)]
+pub fn __alloc_str<P, const N: usize>(pool: &P, s: &str) -> Result<Box<P>, crate::Error>
+where
+    P: BoxPool<Data = crate::String<N>>,
+{
+    // Only allocate what's needed with `alloc`.
+    #[cfg(alloc_backend)]
+    let string = crate::String::from(crate::reexports::alloc::string::String::from(s));
+    #[cfg(not(alloc_backend))]
+    let string = crate::String::try_from(s)?;
+
+    pool.try_alloc(string)
+}
+
+/// `bytes_pool!` implementation detail.
+#[doc(hidden)]
+pub fn __alloc_bytes<P, const N: usize>(pool: &P, bytes: &[u8]) -> Result<Box<P>, crate::Error>
+where
+    P: BoxPool<Data = crate::Vec<u8, N>>,
+{
+    // Only allocate what's needed with `alloc`.
+    #[cfg(alloc_backend)]
+    let vec = crate::Vec::from(crate::reexports::alloc::vec::Vec::from(bytes));
+    #[cfg(not(alloc_backend))]
+    let vec = crate::Vec::from_slice(bytes)?;
+
+    pool.try_alloc(vec)
+}
+
+/// Observes the allocations from a pool, e.g for telemetry.
+///
+/// Set for a pool through the `observer` argument of [`crate::box_pool`]. Since the boxes in use
+/// are counted atomically, this is only available on targets with atomic compare-and-swap
+/// operations, unless the `portable-atomic` or `critical-section` feature is enabled.
+///
+/// ```
+/// use mayheap::{box_pool, boxed::{BoxPool, PoolObserver}};
+///
+/// struct Telemetry;
+///
+/// impl PoolObserver for Telemetry {
+///     fn on_alloc(pool: &'static str, in_use: usize) {
+///         println!("{pool}: allocated, {in_use} in use");
+///     }
+///
+///     fn on_free(pool: &'static str, in_use: usize) {
+///         println!("{pool}: freed, {in_use} in use");
+///     }
+/// }
+///
+/// box_pool!(FramePool: [u8; 64], 4, observer = Telemetry);
+///
+/// let frame = FramePool.alloc([0; 64]).unwrap();
+/// drop(frame);
+/// ```
+#[cfg(any(
+    feature = "portable-atomic",
+    feature = "critical-section",
+    target_has_atomic = "ptr"
+))]
+pub trait PoolObserver {
+    /// Called after a box has been allocated from the pool named `pool`.
+    ///
+    /// `in_use` is the number of boxes from the pool that are currently in use.
+    fn on_alloc(pool: &'static str, in_use: usize);
+
+    /// Called after a box allocated from the pool named `pool` has been dropped.
+    ///
+    /// `in_use` is the number of boxes from the pool that are currently in use.
+    fn on_free(pool: &'static str, in_use: usize);
+}
+
+/// `box_pool!` implementation detail.
+#[cfg(any(
+    feature = "portable-atomic",
+    feature = "critical-section",
+    target_has_atomic = "ptr"
+))]
+#[doc(hidden)]
+pub fn __observe<O: PoolObserver>(pool: &'static str, in_use: &__Counter, allocated: bool) {
+    if allocated {
+        O::on_alloc(pool, in_use.increment());
+    } else {
+        O::on_free(pool, in_use.decrement());
+    }
+}
+
+/// Future returned by [`BoxPool::alloc_async`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct AllocFuture<'a, P: BoxPool> {
+    pool: &'a P,
+    value: Option<P::Data>,
+    // The waiter slot, once claimed.
+    waiter: Option<usize>,
+}
+
+// The value is never pinned.
+impl<P: BoxPool> Unpin for AllocFuture<'_, P> {}
+
+impl<P: BoxPool> Future for AllocFuture<'_, P> {
+    type Output = Box<P>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Box<P>> {
+        let this = self.get_mut();
+        let value = this
+            .value
+            .take()
+            .expect("`AllocFuture` polled after completion");
+
+        // Register first so a block freed right after a failed attempt isn't missed.
+        let registered = P::__register_waker(&mut this.waiter, cx.waker());
+        match this.pool.alloc(value) {
+            Ok(boxed) => {
+                if let Some(slot) = this.waiter.take() {
+                    P::__unregister_waker(slot);
+                }
+
+                Poll::Ready(boxed)
+            }
+            Err(value) => {
+                this.value = Some(value);
+                if !registered {
+                    // No slot to wait in, so try again later.
+                    cx.waker().wake_by_ref();
+                }
+
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<P: BoxPool> Drop for AllocFuture<'_, P> {
+    fn drop(&mut self) {
+        if let Some(slot) = self.waiter.take() {
+            P::__unregister_waker(slot);
+        }
+    }
+}
+
+impl<P: BoxPool> fmt::Debug for AllocFuture<'_, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AllocFuture").finish_non_exhaustive()
+    }
+}
+
+// Initializes `slot` through `init`, ensuring that it actually did.
+fn init_slot<T, F>(slot: &mut MaybeUninit<T>, init: F)
+where
+    F: FnOnce(&mut MaybeUninit<T>) -> &mut T,
+{
+    let ptr = slot.as_ptr();
+    let init_ptr: *const T = init(slot);
+    assert!(
+        core::ptr::eq(ptr, init_ptr),
+        "`init` must initialize the given slot"
+    );
+}
+
+/// `box_pool!` implementation detail.
+#[cfg(alloc_backend)]
+#[doc(hidden)]
+pub fn __alloc_init<T, F>(init: F) -> crate::reexports::alloc::boxed::Box<T>
+where
+    F: FnOnce(&mut MaybeUninit<T>) -> &mut T,
+{
+    let mut boxed = crate::reexports::alloc::boxed::Box::new_uninit();
+    init_slot(&mut boxed, init);
+
+    // SAFETY: `init_slot` ensures that the value is initialized.
+    unsafe { boxed.assume_init() }
+}
+
+/// `box_pool!` implementation detail.
+///
+/// A `heapless` memory pool of `MaybeUninit<T>`, along with the tasks waiting for its blocks.
+#[cfg(not(alloc_backend))]
+#[doc(hidden)]
+pub trait HeaplessBoxPool<T>: heapless::pool::boxed::BoxPool<Data = MaybeUninit<T>> {
+    fn waiters() -> &'static __Waiters;
+}
+
+/// `box_pool!` implementation detail.
+///
+/// A `heapless::pool::boxed::Box` holding an always initialized `MaybeUninit<T>`. Managing
+/// uninitialized blocks allows us to initialize the values in place.
+#[cfg(not(alloc_backend))]
+#[doc(hidden)]
+pub struct PoolBox<P, T>(
+    core::mem::ManuallyDrop<heapless::pool::boxed::Box<P>>,
+    core::marker::PhantomData<T>,
+)
+where
+    P: HeaplessBoxPool<T>;
+
+/// `box_pool!` implementation detail.
+#[cfg(not(alloc_backend))]
+#[doc(hidden)]
+pub fn __pool_alloc<P, T>(value: T) -> Result<PoolBox<P, T>, T>
+where
+    P: HeaplessBoxPool<T>,
+{
+    match P::alloc(MaybeUninit::uninit()) {
+        Ok(mut boxed) => {
+            boxed.write(value);
+
+            Ok(PoolBox(
+                core::mem::ManuallyDrop::new(boxed),
+                core::marker::PhantomData,
+            ))
+        }
+        Err(_) => Err(value),
+    }
+}
+
+/// `box_pool!` implementation detail.
+#[cfg(not(alloc_backend))]
+#[doc(hidden)]
+pub fn __pool_alloc_init<P, T, F>(init: F) -> Option<PoolBox<P, T>>
+where
+    P: HeaplessBoxPool<T>,
+    F: FnOnce(&mut MaybeUninit<T>) -> &mut T,
+{
+    let mut boxed = P::alloc(MaybeUninit::uninit()).ok()?;
+    init_slot(&mut boxed, init);
+
+    Some(PoolBox(
+        core::mem::ManuallyDrop::new(boxed),
+        core::marker::PhantomData,
+    ))
+}
+
+/// `box_pool!` implementation detail.
+#[cfg(not(alloc_backend))]
+#[doc(hidden)]
+pub fn __pool_alloc_uninit<P, T>() -> Option<*mut T>
+where
+    P: HeaplessBoxPool<T>,
+{
+    let boxed = P::alloc(MaybeUninit::uninit()).ok()?;
+
+    Some(heapless::pool::boxed::Box::into_raw(boxed).cast())
+}
+
+/// `box_pool!` implementation detail.
+#[cfg(not(alloc_backend))]
+#[doc(hidden)]
+pub unsafe fn __pool_free_uninit<P, T>(ptr: *mut T)
+where
+    P: HeaplessBoxPool<T>,
+{
+    // SAFETY: The caller guarantees that the block belongs to `P`. Dropping a `MaybeUninit` is a
+    // no-op.
+    drop(unsafe { heapless::pool::boxed::Box::<P>::from_raw(ptr.cast()) });
+
+    P::waiters().wake();
+}
+
+/// `box_pool!` implementation detail.
+#[cfg(not(alloc_backend))]
+#[doc(hidden)]
+pub unsafe fn __pool_reset<P, T: 'static, const N: usize>(
+    blocks: *mut [heapless::pool::boxed::BoxBlock<MaybeUninit<T>>; N],
+) where
+    P: heapless::pool::boxed::BoxPool<Data = MaybeUninit<T>>,
+{
+    // Take out all the free blocks, so that the remaining ones are the leaked ones.
+    let mut free = heapless::Vec::<heapless::pool::boxed::Box<P>, N>::new();
+    while let Ok(boxed) = P::alloc(MaybeUninit::uninit()) {
+        // The pool can't have more than `N` blocks.
+        let _ = free.push(boxed);
+    }
+    let block_size = core::mem::size_of::<heapless::pool::boxed::BoxBlock<MaybeUninit<T>>>();
+    let mut is_free = [false; N];
+    for boxed in &free {
+        let offset = (&**boxed as *const MaybeUninit<T> as usize) - (blocks as usize);
+        is_free[offset / block_size] = true;
+    }
+
+    for (i, is_free) in is_free.into_iter().enumerate() {
+        if !is_free {
+            // SAFETY: The caller guarantees that the leaked box isn't used anymore.
+            P::manage(unsafe { &mut (*blocks)[i] });
+        }
+    }
+    // Dropping `free` returns the free blocks back to the pool.
+}
+
+#[cfg(not(alloc_backend))]
+impl<P, T> PoolBox<P, T>
+where
+    P: HeaplessBoxPool<T>,
+{
+    /// `box_pool!` implementation detail.
+    #[doc(hidden)]
+    pub fn __into_raw(self) -> *mut T {
+        let mut this = core::mem::ManuallyDrop::new(self);
+        // SAFETY: `this` is never used again, and its `Drop` is never run.
+        let boxed = unsafe { core::mem::ManuallyDrop::take(&mut this.0) };
+
+        heapless::pool::boxed::Box::into_raw(boxed).cast()
+    }
+
+    /// `box_pool!` implementation detail.
+    #[doc(hidden)]
+    pub unsafe fn __from_raw(ptr: *mut T) -> Self {
+        // SAFETY: `MaybeUninit<T>` has the same layout as `T` and the caller guarantees the rest.
+        let boxed = unsafe { heapless::pool::boxed::Box::from_raw(ptr.cast()) };
+
+        Self(
+            core::mem::ManuallyDrop::new(boxed),
+            core::marker::PhantomData,
+        )
+    }
+}
+
+#[cfg(not(alloc_backend))]
+impl<P, T> Deref for PoolBox<P, T>
+where
+    P: HeaplessBoxPool<T>,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: The value is always initialized.
+        unsafe { self.0.assume_init_ref() }
+    }
+}
+
+#[cfg(not(alloc_backend))]
+impl<P, T> DerefMut for PoolBox<P, T>
+where
+    P: HeaplessBoxPool<T>,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: The value is always initialized.
+        unsafe { self.0.assume_init_mut() }
+    }
+}
+
+#[cfg(not(alloc_backend))]
+impl<P, T> Drop for PoolBox<P, T>
+where
+    P: HeaplessBoxPool<T>,
+{
+    fn drop(&mut self) {
+        // SAFETY: The value is always initialized and neither it nor the box are used again.
+        unsafe {
+            self.0.assume_init_drop();
+            core::mem::ManuallyDrop::drop(&mut self.0);
+        }
+
+        // Only wake up the waiting task once the block is actually back in the pool.
+        P::waiters().wake();
+    }
+}
+
+#[cfg(all(not(alloc_backend), not(feature = "panic-free")))]
+impl<P, T> Clone for PoolBox<P, T>
+where
+    P: HeaplessBoxPool<T>,
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        __pool_alloc(self.deref().clone())
+            .unwrap_or_else(|_| panic!("{}", crate::Error::PoolExhausted))
+    }
+}
+
+#[cfg(not(alloc_backend))]
+impl<P, T> core::fmt::Debug for PoolBox<P, T>
+where
+    P: HeaplessBoxPool<T>,
+    T: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+#[cfg(not(alloc_backend))]
+impl<P, T> PartialEq for PoolBox<P, T>
+where
+    P: HeaplessBoxPool<T>,
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.deref().eq(other.deref())
+    }
+}
+
+#[cfg(not(alloc_backend))]
+impl<P, T> Eq for PoolBox<P, T>
+where
+    P: HeaplessBoxPool<T>,
+    T: Eq,
+{
+}
+
 /// Creates a new BoxPool singleton with the given $name that manages the specified $data_type
-#[cfg(feature = "alloc")]
+#[cfg(alloc_backend)]
 #[macro_export]
 macro_rules! box_pool {
-    ($visibility:vis $name:ident: $ty:ty, $capacity:expr) => {
+    // Split the attributes into `cfg` ones, which apply to all generated items, `storage` ones,
+    // which apply to the memory of the blocks, and the rest, which only apply to the pool type.
+    (@split [$($cfg:tt)*] [$($attr:tt)*] [$($storage:tt)*] #[cfg $($c:tt)*] $($rest:tt)*) => {
+        $crate::box_pool!(
+            @split [$($cfg)* #[cfg $($c)*]] [$($attr)*] [$($storage)*] $($rest)*
+        );
+    };
+    (@split [$($cfg:tt)*] [$($attr:tt)*] [$($storage:tt)*] #[storage($($s:tt)*)] $($rest:tt)*) => {
+        $crate::box_pool!(@split [$($cfg)*] [$($attr)*] [$($storage)* #[$($s)*]] $($rest)*);
+    };
+    (@split [$($cfg:tt)*] [$($attr:tt)*] [$($storage:tt)*] #[$($a:tt)*] $($rest:tt)*) => {
+        $crate::box_pool!(@split [$($cfg)*] [$($attr)* #[$($a)*]] [$($storage)*] $($rest)*);
+    };
+    (
+        @split [$(#[$cfg:meta])*] [$(#[$attr:meta])*] [$(#[$storage:meta])*]
+        ; $visibility:vis $name:ident: $ty:ty, $capacity:expr $(, observer = $observer:ty)?
+    ) => {
+        $(#[$cfg])*
+        $(#[$attr])*
         #[derive(Debug, Clone, PartialEq, Eq)]
         $visibility struct $name;
 
+        $(#[$cfg])*
         impl $crate::boxed::BoxPool for $name {
             type Data = $ty;
             type BoxedValue = $crate::reexports::alloc::boxed::Box<$ty>;
@@ -98,101 +1145,570 @@ macro_rules! box_pool {
                     $crate::reexports::alloc::boxed::Box::new(value),
                 ))
             }
+
+            fn alloc_init<F>(&self, init: F) -> Result<$crate::boxed::Box<Self>, $crate::Error>
+            where
+                F: FnOnce(&mut core::mem::MaybeUninit<Self::Data>) -> &mut Self::Data,
+            {
+                Ok($crate::boxed::Box::new($crate::boxed::__alloc_init(init)))
+            }
+
+            fn alloc_uninit(&self) -> Result<$crate::boxed::UninitBox<Self>, $crate::Error> {
+                let boxed = $crate::reexports::alloc::boxed::Box::<$ty>::new_uninit();
+                let ptr = $crate::reexports::alloc::boxed::Box::into_raw(boxed).cast();
+
+                // SAFETY: The block was just allocated.
+                Ok(unsafe { $crate::boxed::UninitBox::__new(ptr) })
+            }
+
+            fn instance() -> &'static Self {
+                &$name
+            }
+
+            $(
+                fn __observe(allocated: bool) {
+                    static IN_USE: $crate::boxed::__Counter = $crate::boxed::__Counter::new();
+
+                    $crate::boxed::__observe::<$observer>(stringify!($name), &IN_USE, allocated);
+                }
+            )?
+
+            fn __register_waker(
+                _slot: &mut Option<usize>,
+                _waker: &core::task::Waker,
+            ) -> bool {
+                // Allocations never wait.
+                true
+            }
+
+            fn __into_raw(boxed: Self::BoxedValue) -> *mut Self::Data {
+                $crate::reexports::alloc::boxed::Box::into_raw(boxed)
+            }
+
+            unsafe fn __from_raw(ptr: *mut Self::Data) -> Self::BoxedValue {
+                unsafe { $crate::reexports::alloc::boxed::Box::from_raw(ptr) }
+            }
+
+            unsafe fn __free_uninit(ptr: *mut Self::Data) {
+                drop(unsafe {
+                    $crate::reexports::alloc::boxed::Box::from_raw(
+                        ptr.cast::<core::mem::MaybeUninit<$ty>>(),
+                    )
+                });
+            }
+        }
+
+        $(#[$cfg])*
+        impl $name {
+            /// Initializes the memory pool.
+            ///
+            /// The pool is initialized lazily on first allocation but calling this early (e.g at
+            /// startup) ensures that the (synchronized) initialization never happens later, e.g
+            /// racing inside an interrupt handler. Calling this more than once is harmless.
+            ///
+            /// This is a no-op with `alloc`.
+            #[allow(dead_code)]
+            $visibility fn init(&self) {}
+        }
+
+        $crate::__test_util! {
+            $(#[$cfg])*
+            impl $name {
+                /// Reclaims all the blocks of the pool, including the ones of leaked boxes.
+                ///
+                /// This is meant for tests that exhaust or leak from a pool, which would
+                /// otherwise affect all the subsequent tests in the same process. The leaked values
+                /// are not dropped and observers are not notified. This is a no-op with `alloc`.
+                ///
+                /// Only available in tests or with the `test-util` feature enabled.
+                ///
+                /// # Safety
+                ///
+                /// Any boxes allocated from the pool that are still alive must never be used or
+                /// dropped again (e.g because they've been leaked), since their memory will be
+                /// reused.
+                #[allow(dead_code)]
+                $visibility unsafe fn reset(&self) {}
+            }
         }
 
         $crate::reexports::paste::paste! {
             // Let's use the $capacity variable so callers don't get "unused const" warnings.
+            $(#[$cfg])*
             #[allow(non_upper_case_globals, dead_code)]
             const [<__dummy__ $name>]: () = {
                 let _ = $capacity;
             };
         }
     };
+    (
+        $(#[$($attr:tt)*])*
+        $visibility:vis $name:ident: $ty:ty, $capacity:expr $(, observer = $observer:ty)?
+    ) => {
+        $crate::box_pool!(
+            @split [] [] [] $(#[$($attr)*])*
+            ; $visibility $name: $ty, $capacity $(, observer = $observer)?
+        );
+    };
 }
 
 /// Creates a new BoxPool singleton with the given $name that manages the specified $data_type
-#[cfg(not(feature = "alloc"))]
+#[cfg(not(alloc_backend))]
 #[macro_export]
 macro_rules! box_pool {
-    ($visibility:vis $name:ident: $ty:ty, $capacity:expr) => {
+    // Split the attributes into `cfg` ones, which apply to all generated items, `storage` ones,
+    // which apply to the memory of the blocks, and the rest, which only apply to the pool type.
+    (@split [$($cfg:tt)*] [$($attr:tt)*] [$($storage:tt)*] #[cfg $($c:tt)*] $($rest:tt)*) => {
+        $crate::box_pool!(
+            @split [$($cfg)* #[cfg $($c)*]] [$($attr)*] [$($storage)*] $($rest)*
+        );
+    };
+    (@split [$($cfg:tt)*] [$($attr:tt)*] [$($storage:tt)*] #[storage($($s:tt)*)] $($rest:tt)*) => {
+        $crate::box_pool!(@split [$($cfg)*] [$($attr)*] [$($storage)* #[$($s)*]] $($rest)*);
+    };
+    (@split [$($cfg:tt)*] [$($attr:tt)*] [$($storage:tt)*] #[$($a:tt)*] $($rest:tt)*) => {
+        $crate::box_pool!(@split [$($cfg)*] [$($attr)* #[$($a)*]] [$($storage)*] $($rest)*);
+    };
+    (
+        @split [$(#[$cfg:meta])*] [$(#[$attr:meta])*] [$(#[$storage:meta])*]
+        ; $visibility:vis $name:ident: $ty:ty, $capacity:expr $(, observer = $observer:ty)?
+    ) => {
         $crate::reexports::paste::paste! {
-            heapless::box_pool!([<$name Pool>]: $ty);
+            $(#[$cfg])*
+            heapless::box_pool!([<$name Pool>]: core::mem::MaybeUninit<$ty>);
+
+            $(#[$cfg])*
+            impl $crate::boxed::HeaplessBoxPool<$ty> for [<$name Pool>] {
+                fn waiters() -> &'static $crate::boxed::__Waiters {
+                    static WAITERS: $crate::boxed::__Waiters = $crate::boxed::__Waiters::new();
+
+                    &WAITERS
+                }
+            }
 
+            $(#[$cfg])*
+            $(#[$attr])*
             #[derive(Debug, Clone, PartialEq, Eq)]
             $visibility struct $name;
 
+            $(#[$cfg])*
             impl $crate::boxed::BoxPool for $name {
                 type Data = $ty;
-                type BoxedValue = heapless::pool::boxed::Box<[<$name Pool>]>;
+                type BoxedValue = $crate::boxed::PoolBox<[<$name Pool>], $ty>;
 
                 fn alloc(&self, value: Self::Data) -> Result<$crate::boxed::Box<Self>, $ty> {
                     $name.init();
 
-                    [<$name Pool>].alloc(value).map($crate::boxed::Box::new)
+                    $crate::boxed::__pool_alloc(value).map($crate::boxed::Box::new)
+                }
+
+                fn alloc_init<F>(&self, init: F) -> Result<$crate::boxed::Box<Self>, $crate::Error>
+                where
+                    F: FnOnce(&mut core::mem::MaybeUninit<Self::Data>) -> &mut Self::Data,
+                {
+                    $name.init();
+
+                    $crate::boxed::__pool_alloc_init(init)
+                        .map($crate::boxed::Box::new)
+                        .ok_or($crate::Error::PoolExhausted)
+                }
+
+                fn alloc_uninit(&self) -> Result<$crate::boxed::UninitBox<Self>, $crate::Error> {
+                    $name.init();
+
+                    let ptr = $crate::boxed::__pool_alloc_uninit::<[<$name Pool>], $ty>()
+                        .ok_or($crate::Error::PoolExhausted)?;
+
+                    // SAFETY: The block was just allocated.
+                    Ok(unsafe { $crate::boxed::UninitBox::__new(ptr) })
+                }
+
+                fn instance() -> &'static Self {
+                    &$name
+                }
+
+                $(
+                    fn __observe(allocated: bool) {
+                        static IN_USE: $crate::boxed::__Counter = $crate::boxed::__Counter::new();
+
+                        $crate::boxed::__observe::<$observer>(
+                            stringify!($name),
+                            &IN_USE,
+                            allocated,
+                        );
+                    }
+                )?
+
+                fn __register_waker(slot: &mut Option<usize>, waker: &core::task::Waker) -> bool {
+                    <[<$name Pool>] as $crate::boxed::HeaplessBoxPool<$ty>>::waiters()
+                        .register(slot, waker)
+                }
+
+                fn __unregister_waker(slot: usize) {
+                    <[<$name Pool>] as $crate::boxed::HeaplessBoxPool<$ty>>::waiters()
+                        .unregister(slot);
+                }
+
+                fn __into_raw(boxed: Self::BoxedValue) -> *mut Self::Data {
+                    boxed.__into_raw()
+                }
+
+                unsafe fn __from_raw(ptr: *mut Self::Data) -> Self::BoxedValue {
+                    unsafe { $crate::boxed::PoolBox::__from_raw(ptr) }
+                }
+
+                unsafe fn __free_uninit(ptr: *mut Self::Data) {
+                    unsafe { $crate::boxed::__pool_free_uninit::<[<$name Pool>], $ty>(ptr) }
                 }
             }
 
+            $(#[$cfg])*
             impl $name {
-                fn init(&self) {
-                    use portable_atomic::{AtomicU8, Ordering};
-                    use heapless::pool::boxed::BoxBlock;
-
-                    static STATE: AtomicU8 = AtomicU8::new(InitState::Uninitialized as u8);
-
-                    match STATE
-                        .compare_exchange(
-                            InitState::Uninitialized as u8,
-                            InitState::Initializing as u8,
-                            Ordering::AcqRel,
-                            Ordering::Acquire,
-                        )
-                        .map(|state| state.into())
-                        .map_err(|state| state.into())
-                    {
-                        Ok(InitState::Uninitialized) => {
-                            // We won the race, initialize.
-                            let blocks: &'static mut [BoxBlock<$ty>] = {
-                                static mut BLOCKS: [BoxBlock<$ty>; $capacity] = [const { BoxBlock::new() }; $capacity];
-                                unsafe { &mut BLOCKS }
-                            };
-                            for block in blocks {
-                               [<$name Pool>].manage(block);
-                            }
-                            STATE.store(InitState::Initialized as u8, Ordering::Release);
-                        }
-                        Err(InitState::Initializing) => {
-                            // Someone else is initializing, wait.
-                            while STATE.load(Ordering::Acquire) == InitState::Initializing as u8 {
-                                core::hint::spin_loop();
-                            }
+                /// Initializes the memory pool.
+                ///
+                /// The pool is initialized lazily on first allocation but calling this early (e.g
+                /// at startup) ensures that the (synchronized) initialization never happens later,
+                /// e.g racing inside an interrupt handler. Calling this more than once is harmless.
+                #[allow(dead_code)]
+                $visibility fn init(&self) {
+                    static ONCE: $crate::boxed::__Once = $crate::boxed::__Once::new();
+
+                    ONCE.call_once(|| {
+                        // SAFETY: This is the only time the blocks are handed out.
+                        for block in unsafe { &mut *Self::__blocks() } {
+                            [<$name Pool>].manage(block);
                         }
-                        Err(InitState::Initialized) => {
-                            // Already initialized.
+                    });
+                }
+
+                #[doc(hidden)]
+                fn __blocks() -> *mut [
+                    heapless::pool::boxed::BoxBlock<core::mem::MaybeUninit<$ty>>;
+                    $capacity
+                ] {
+                    $(#[$storage])*
+                    static mut BLOCKS: [
+                        heapless::pool::boxed::BoxBlock<core::mem::MaybeUninit<$ty>>;
+                        $capacity
+                    ] = [const { heapless::pool::boxed::BoxBlock::new() }; $capacity];
+
+                    core::ptr::addr_of_mut!(BLOCKS)
+                }
+            }
+
+            $crate::__test_util! {
+                $(#[$cfg])*
+                impl $name {
+                    /// Reclaims all the blocks of the pool, including the ones of leaked boxes.
+                    ///
+                    /// This is meant for tests that exhaust or leak from a pool, which would
+                    /// otherwise affect all the subsequent tests in the same process. The leaked
+                    /// values are not dropped and observers are not notified. This is a no-op with
+                    /// `alloc`.
+                    ///
+                    /// Only available in tests or with the `test-util` feature enabled.
+                    ///
+                    /// # Safety
+                    ///
+                    /// Any boxes allocated from the pool that are still alive must never be used or
+                    /// dropped again (e.g because they've been leaked), since their memory will be
+                    /// reused.
+                    #[allow(dead_code)]
+                    $visibility unsafe fn reset(&self) {
+                        $name.init();
+
+                        // SAFETY: The caller guarantees that no boxes are used anymore.
+                        unsafe {
+                            $crate::boxed::__pool_reset::<[<$name Pool>], $ty, $capacity>(
+                                Self::__blocks(),
+                            )
                         }
-                        // All other states should never happen.
-                        _ => unreachable!(),
                     }
+                }
+            }
+        }
+    };
+    (
+        $(#[$($attr:tt)*])*
+        $visibility:vis $name:ident: $ty:ty, $capacity:expr $(, observer = $observer:ty)?
+    ) => {
+        $crate::box_pool!(
+            @split [] [] [] $(#[$($attr)*])*
+            ; $visibility $name: $ty, $capacity $(, observer = $observer)?
+        );
+    };
+}
 
-                    #[repr(u8)]
-                    #[derive(PartialEq)]
-                    enum InitState {
-                        Uninitialized = 0,
-                        Initializing = 1,
-                        Initialized = 2,
+/// Creates a new BoxPool singleton with the given $name that manages strings of up to $max_len
+/// bytes.
+///
+/// The pool manages [`crate::String`]s and additionally provides an `alloc_str` method that
+/// allocates a copy of the given string slice. With `heapless`, each block can hold strings of up
+/// to `$max_len` bytes and longer strings result in [`crate::Error::BufferOverflow`]. With `alloc`,
+/// only the memory needed for the string itself is allocated.
+///
+/// Attributes, including `#[storage(...)]` ones, are handled the same way as by
+/// [`crate::box_pool`].
+#[macro_export]
+macro_rules! str_pool {
+    // Split off the `cfg` attributes, which also apply to the `impl` block. The rest are forwarded
+    // to `box_pool!` as is.
+    (@split [$($cfg:tt)*] [$($attr:tt)*] #[cfg $($c:tt)*] $($rest:tt)*) => {
+        $crate::str_pool!(@split [$($cfg)* #[cfg $($c)*]] [$($attr)*] $($rest)*);
+    };
+    (@split [$($cfg:tt)*] [$($attr:tt)*] #[$($a:tt)*] $($rest:tt)*) => {
+        $crate::str_pool!(@split [$($cfg)*] [$($attr)* #[$($a)*]] $($rest)*);
+    };
+    (
+        @split [$($cfg:tt)*] [$($attr:tt)*]
+        ; $visibility:vis $name:ident: $max_len:expr, $capacity:expr
+    ) => {
+        $crate::box_pool!(
+            $($cfg)* $($attr)*
+            $visibility $name: $crate::String<{ $max_len }>,
+            $capacity
+        );
+
+        $($cfg)*
+        impl $name {
+            /// Allocates a copy of `s` from the pool.
+            #[allow(dead_code)]
+            $visibility fn alloc_str(
+                &self,
+                s: &str,
+            ) -> Result<$crate::boxed::Box<Self>, $crate::Error> {
+                $crate::boxed::__alloc_str(self, s)
+            }
+        }
+    };
+    (
+        $(#[$($attr:tt)*])*
+        $visibility:vis $name:ident: $max_len:expr, $capacity:expr
+    ) => {
+        $crate::str_pool!(
+            @split [] [] $(#[$($attr)*])*
+            ; $visibility $name: $max_len, $capacity
+        );
+    };
+}
+
+/// Creates a new BoxPool singleton with the given $name that manages byte buffers of up to
+/// $max_len bytes.
+///
+/// The pool manages [`crate::Vec<u8, N>`]s and additionally provides an `alloc_bytes` method that
+/// allocates a copy of the given byte slice. With `heapless`, each block can hold up to `$max_len`
+/// bytes and longer slices result in [`crate::Error::BufferOverflow`]. With `alloc`, only the
+/// memory needed for the bytes themselves is allocated.
+///
+/// Attributes, including `#[storage(...)]` ones, are handled the same way as by
+/// [`crate::box_pool`].
+#[macro_export]
+macro_rules! bytes_pool {
+    // Split off the `cfg` attributes, which also apply to the `impl` block. The rest are forwarded
+    // to `box_pool!` as is.
+    (@split [$($cfg:tt)*] [$($attr:tt)*] #[cfg $($c:tt)*] $($rest:tt)*) => {
+        $crate::bytes_pool!(@split [$($cfg)* #[cfg $($c)*]] [$($attr)*] $($rest)*);
+    };
+    (@split [$($cfg:tt)*] [$($attr:tt)*] #[$($a:tt)*] $($rest:tt)*) => {
+        $crate::bytes_pool!(@split [$($cfg)*] [$($attr)* #[$($a)*]] $($rest)*);
+    };
+    (
+        @split [$($cfg:tt)*] [$($attr:tt)*]
+        ; $visibility:vis $name:ident: $max_len:expr, $capacity:expr
+    ) => {
+        $crate::box_pool!(
+            $($cfg)* $($attr)*
+            $visibility $name: $crate::Vec<u8, { $max_len }>,
+            $capacity
+        );
+
+        $($cfg)*
+        impl $name {
+            /// Allocates a copy of `bytes` from the pool.
+            #[allow(dead_code)]
+            $visibility fn alloc_bytes(
+                &self,
+                bytes: &[u8],
+            ) -> Result<$crate::boxed::Box<Self>, $crate::Error> {
+                $crate::boxed::__alloc_bytes(self, bytes)
+            }
+        }
+    };
+    (
+        $(#[$($attr:tt)*])*
+        $visibility:vis $name:ident: $max_len:expr, $capacity:expr
+    ) => {
+        $crate::bytes_pool!(
+            @split [] [] $(#[$($attr)*])*
+            ; $visibility $name: $max_len, $capacity
+        );
+    };
+}
+
+/// Creates a new pool with the given $name that manages byte buffers of several size classes.
+///
+/// Each class is a [`crate::bytes_pool`] of `$capacity` blocks of up to `$max_len` bytes, given in
+/// ascending order of `$max_len`. The `alloc_bytes` method of the pool allocates a copy of the
+/// given byte slice from the smallest class that fits it and still has free blocks, returning a
+/// `${name}Box` enum with a variant per class (e.g `Class64`) that dereferences to `[u8]`. This
+/// avoids reserving the worst-case size for every variable-length payload.
+///
+/// With `heapless`, slices longer than the largest class result in
+/// [`crate::Error::BufferOverflow`] and [`crate::Error::PoolExhausted`] is returned if none of the
+/// classes that fit have free blocks left. With `alloc`, only the memory needed for the bytes
+/// themselves is allocated, so the variant only tells which class was picked and not how much the
+/// buffer holds: slices longer than the largest class end up in its variant as well.
+///
+/// Attributes given before a class are forwarded to its [`crate::bytes_pool`], e.g.
+/// `#[storage(...)]` to place the blocks of only some of the classes in a specific memory region.
+/// `cfg` attributes aren't supported there since every class is needed by the pool.
+///
+/// # Example
+///
+/// ```
+/// use mayheap::size_class_pool;
+///
+/// size_class_pool!(PayloadPool: {
+///     16 => 2,
+///     #[storage(cfg_attr(target_os = "none", link_section = ".sram2"))]
+///     64 => 1,
+/// });
+///
+/// let small = PayloadPool.alloc_bytes(b"ping").unwrap();
+/// assert!(matches!(small, PayloadPoolBox::Class16(_)));
+/// assert_eq!(&*small, b"ping");
+/// let large = PayloadPool.alloc_bytes(&[0; 40]).unwrap();
+/// assert!(matches!(large, PayloadPoolBox::Class64(_)));
+///
+/// // With `alloc`, payloads longer than the largest class end up in it.
+/// #[cfg(feature = "alloc")]
+/// assert!(matches!(PayloadPool.alloc_bytes(&[0; 100]).unwrap(), PayloadPoolBox::Class64(_)));
+///
+/// // With `heapless`, small payloads use larger classes once theirs is exhausted.
+/// let _small = PayloadPool.alloc_bytes(b"pong").unwrap();
+/// #[cfg(feature = "heapless")]
+/// {
+///     let res = PayloadPool.alloc_bytes(b"ping-pong");
+///     assert_eq!(res.unwrap_err(), mayheap::Error::PoolExhausted);
+///     drop(large);
+///     let res = PayloadPool.alloc_bytes(b"ping-pong");
+///     assert!(matches!(res.unwrap(), PayloadPoolBox::Class64(_)));
+/// }
+/// ```
+#[macro_export]
+macro_rules! size_class_pool {
+    (
+        $visibility:vis $name:ident: {
+            $($(#[$($attr:tt)*])* $max_len:literal => $capacity:expr),+ $(,)?
+        }
+    ) => {
+        $crate::reexports::paste::paste! {
+            $(
+                $crate::bytes_pool!(
+                    $(#[$($attr)*])*
+                    $visibility [<$name $max_len>]: $max_len, $capacity
+                );
+            )+
+
+            /// A byte buffer allocated from one of the size classes of the pool.
+            #[derive(Debug)]
+            #[allow(dead_code)]
+            $visibility enum [<$name Box>] {
+                $(
+                    #[allow(missing_docs)]
+                    [<Class $max_len>]($crate::boxed::Box<[<$name $max_len>]>),
+                )+
+            }
+
+            impl core::ops::Deref for [<$name Box>] {
+                type Target = [u8];
+
+                fn deref(&self) -> &[u8] {
+                    match self {
+                        $(Self::[<Class $max_len>](boxed) => boxed,)+
                     }
+                }
+            }
+
+            impl core::ops::DerefMut for [<$name Box>] {
+                fn deref_mut(&mut self) -> &mut [u8] {
+                    match self {
+                        $(Self::[<Class $max_len>](boxed) => boxed,)+
+                    }
+                }
+            }
+
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            $visibility struct $name;
+
+            impl $name {
+                /// The block sizes of the classes, in ascending order.
+                #[allow(dead_code)]
+                $visibility const CLASSES: &'static [usize] = &[$($max_len),+];
+
+                /// Initializes the memory pools of all the classes.
+                ///
+                /// See the `init` method of [`mayheap::box_pool`] for details.
+                #[allow(dead_code)]
+                $visibility fn init(&self) {
+                    $([<$name $max_len>].init();)+
+                }
+
+                /// Allocates a copy of `bytes` from the smallest class that fits it and has free
+                /// blocks left.
+                #[allow(dead_code)]
+                $visibility fn alloc_bytes(
+                    &self,
+                    bytes: &[u8],
+                ) -> Result<[<$name Box>], $crate::Error> {
+                    const {
+                        let classes = $name::CLASSES;
+                        let mut i = 1;
+                        while i < classes.len() {
+                            assert!(
+                                classes[i - 1] < classes[i],
+                                "size classes must be in ascending order",
+                            );
+                            i += 1;
+                        }
+                    };
+                    let largest = Self::CLASSES[Self::CLASSES.len() - 1];
 
-                    impl From<u8> for InitState {
-                        fn from(value: u8) -> Self {
-                            match value {
-                                0 => InitState::Uninitialized,
-                                1 => InitState::Initializing,
-                                2 => InitState::Initialized,
-                                _ => unreachable!(),
+                    let mut res = Err($crate::Error::PoolExhausted);
+                    $(
+                        // The largest class is always tried, so that it reports the overflow with
+                        // `heapless`, and allocates whatever is needed with `alloc`.
+                        if bytes.len() <= $max_len || $max_len == largest {
+                            match [<$name $max_len>].alloc_bytes(bytes) {
+                                Ok(boxed) => return Ok([<$name Box>]::[<Class $max_len>](boxed)),
+                                Err(e) => res = Err(e),
                             }
                         }
-                    }
+                    )+
+
+                    res
                 }
             }
         }
     };
 }
+
+/// `box_pool!` implementation detail.
+#[cfg(feature = "test-util")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __test_util {
+    ($($item:item)*) => {
+        $($item)*
+    };
+}
+
+/// `box_pool!` implementation detail.
+#[cfg(not(feature = "test-util"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __test_util {
+    ($($item:item)*) => {
+        $(#[cfg(test)] $item)*
+    };
+}
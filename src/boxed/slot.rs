@@ -0,0 +1,291 @@
+//! An atomically swappable slot for a boxed value.
+
+#[cfg(all(not(feature = "critical-section"), not(feature = "portable-atomic")))]
+use core::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+use core::{fmt, ptr};
+#[cfg(all(not(feature = "critical-section"), feature = "portable-atomic"))]
+use portable_atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+#[cfg(feature = "critical-section")]
+use {core::cell::Cell, critical_section::Mutex};
+
+use super::{Box, BoxPool};
+
+/// A slot holding an optional [`Box`], which can be replaced while others are reading it.
+///
+/// This is meant for sharing the latest version of a value (e.g. a configuration) that is updated
+/// as a whole: writers allocate the new version from the pool and swap it in, and readers always
+/// see either the old or the new version, never a mix of both.
+///
+/// Unless the `critical-section` feature is enabled, reads are lock-free: they only increment a
+/// counter of in-flight reads of the current generation of the slot for their duration. In return,
+/// writers start a new generation and wait for the reads of the previous one (i.e. the ones that
+/// started before the swap) to finish before giving back the old value. Reads that start after the
+/// swap don't hold writers up. Still, keep reads short and don't replace the value from an
+/// interrupt handler that can preempt a reader on the same core. With `critical-section`, reads
+/// and writes are done inside a critical section instead, so they can be freely mixed with
+/// interrupt handlers.
+///
+/// The value must not be replaced from within a [`AtomicSlot::read`] closure of the same slot,
+/// since the replaced value would still be in use. This deadlocks, or panics with
+/// `critical-section`.
+///
+/// This is only available on targets with atomic compare-and-swap operations, unless the
+/// `portable-atomic` or `critical-section` feature is enabled.
+///
+/// # Example
+///
+/// ```
+/// use mayheap::{box_pool, boxed::{AtomicSlot, BoxPool}};
+///
+/// #[derive(Debug, PartialEq)]
+/// pub struct Config {
+///     pub rate: u32,
+/// }
+///
+/// box_pool!(ConfigPool: Config, 2);
+///
+/// static CONFIG: AtomicSlot<ConfigPool> = AtomicSlot::empty();
+///
+/// assert_eq!(CONFIG.read(|config| config.map(|c| c.rate)), None);
+///
+/// CONFIG.store(ConfigPool.alloc(Config { rate: 100 }).unwrap());
+/// assert_eq!(CONFIG.read(|config| config.map(|c| c.rate)), Some(100));
+///
+/// let old = CONFIG.swap(Some(ConfigPool.alloc(Config { rate: 200 }).unwrap()));
+/// assert_eq!(old.as_deref(), Some(&Config { rate: 100 }));
+/// assert_eq!(CONFIG.read(|config| config.map(|c| c.rate)), Some(200));
+/// ```
+pub struct AtomicSlot<P: BoxPool> {
+    #[cfg(feature = "critical-section")]
+    ptr: Mutex<Cell<*mut P::Data>>,
+    // The number of in-flight reads, which can only be nested ones.
+    #[cfg(feature = "critical-section")]
+    readers: Mutex<Cell<usize>>,
+    #[cfg(not(feature = "critical-section"))]
+    ptr: AtomicPtr<P::Data>,
+    // The generation of the value, incremented by every swap.
+    #[cfg(not(feature = "critical-section"))]
+    epoch: AtomicUsize,
+    // The number of in-flight reads of the current and the previous generation, indexed by the
+    // parity of the generation.
+    #[cfg(not(feature = "critical-section"))]
+    readers: [AtomicUsize; 2],
+    // Serializes the writers.
+    #[cfg(not(feature = "critical-section"))]
+    writing: AtomicBool,
+}
+
+// SAFETY: The slot owns the boxed value, just like a `Box`.
+unsafe impl<P: BoxPool> Send for AtomicSlot<P> where Box<P>: Send {}
+
+// SAFETY: The boxed value is moved in and out of the slot from any thread (hence `Send`) and
+// shared with all readers (hence `Sync`). It's never freed while being read.
+unsafe impl<P: BoxPool> Sync for AtomicSlot<P>
+where
+    Box<P>: Send,
+    P::Data: Sync,
+{
+}
+
+impl<P: BoxPool> AtomicSlot<P> {
+    /// Creates a new, empty slot.
+    #[inline]
+    pub const fn empty() -> Self {
+        Self {
+            #[cfg(feature = "critical-section")]
+            ptr: Mutex::new(Cell::new(ptr::null_mut())),
+            #[cfg(feature = "critical-section")]
+            readers: Mutex::new(Cell::new(0)),
+            #[cfg(not(feature = "critical-section"))]
+            ptr: AtomicPtr::new(ptr::null_mut()),
+            #[cfg(not(feature = "critical-section"))]
+            epoch: AtomicUsize::new(0),
+            #[cfg(not(feature = "critical-section"))]
+            readers: [const { AtomicUsize::new(0) }; 2],
+            #[cfg(not(feature = "critical-section"))]
+            writing: AtomicBool::new(false),
+        }
+    }
+
+    /// Creates a new slot holding `boxed`.
+    #[inline]
+    pub fn new(boxed: Box<P>) -> Self {
+        let slot = Self::empty();
+        slot.store(boxed);
+
+        slot
+    }
+
+    /// Runs `f` with a reference to the current value, if any, and returns its result.
+    ///
+    /// The value is guaranteed not to be replaced under `f`'s feet. `f` must not replace the value
+    /// itself, see the [type documentation](Self) for details.
+    #[inline]
+    pub fn read<F, U>(&self, f: F) -> U
+    where
+        F: FnOnce(Option<&P::Data>) -> U,
+    {
+        #[cfg(feature = "critical-section")]
+        {
+            critical_section::with(|cs| {
+                let readers = self.readers.borrow(cs);
+                readers.set(readers.get() + 1);
+                // Leave the read even if `f` panics.
+                struct Leave<'a>(&'a Cell<usize>);
+                impl Drop for Leave<'_> {
+                    fn drop(&mut self) {
+                        self.0.set(self.0.get() - 1);
+                    }
+                }
+                let _leave = Leave(readers);
+
+                // SAFETY: The pointer is either null or points to a live value owned by the slot,
+                // which can't be replaced while we're inside the critical section, other than by
+                // `f`, which `swap` prevents.
+                f(unsafe { self.ptr.borrow(cs).get().as_ref() })
+            })
+        }
+        #[cfg(not(feature = "critical-section"))]
+        {
+            // Writers wait for the counter of the generation they end to drop to zero before
+            // giving back the value they replaced. `SeqCst` on both sides ensures that either we
+            // see the new generation (and hence the new value), or the writer sees our increment.
+            let readers = loop {
+                let epoch = self.epoch.load(Ordering::SeqCst);
+                let readers = &self.readers[epoch % 2];
+                readers.fetch_add(1, Ordering::SeqCst);
+                if self.epoch.load(Ordering::SeqCst) == epoch {
+                    break readers;
+                }
+                // A writer ended the generation in between, and may not have seen our increment.
+                readers.fetch_sub(1, Ordering::Release);
+            };
+            // Leave the read even if `f` panics.
+            struct Leave<'a>(&'a AtomicUsize);
+            impl Drop for Leave<'_> {
+                fn drop(&mut self) {
+                    self.0.fetch_sub(1, Ordering::Release);
+                }
+            }
+            let _leave = Leave(readers);
+
+            // SAFETY: The pointer is either null or points to a live value owned by the slot, and
+            // writers don't free it until we leave.
+            f(unsafe { self.ptr.load(Ordering::SeqCst).as_ref() })
+        }
+    }
+
+    /// Replaces the value with `boxed`, returning the old value.
+    ///
+    /// Waits for the reads that started before the swap to finish, unless the `critical-section`
+    /// feature is enabled. See the [type documentation](Self) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from within a [`AtomicSlot::read`] closure of the same slot, with the
+    /// `critical-section` feature enabled. Without it, this deadlocks instead.
+    #[inline]
+    pub fn swap(&self, boxed: Option<Box<P>>) -> Option<Box<P>> {
+        #[cfg(feature = "critical-section")]
+        let old = critical_section::with(|cs| {
+            // Only nested reads can be in flight inside the critical section.
+            assert!(
+                self.readers.borrow(cs).get() == 0,
+                "`AtomicSlot` value replaced while being read"
+            );
+
+            self.ptr
+                .borrow(cs)
+                .replace(boxed.map_or(ptr::null_mut(), Box::into_raw))
+        });
+        #[cfg(not(feature = "critical-section"))]
+        let old = {
+            let new = boxed.map_or(ptr::null_mut(), Box::into_raw);
+            while self
+                .writing
+                .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                core::hint::spin_loop();
+            }
+
+            let old = self.ptr.swap(new, Ordering::SeqCst);
+            // Reads of the new generation can't see `old`, so only wait for the ones of the
+            // generation we end. The previous writer waited for the ones of the generation before.
+            let epoch = self.epoch.fetch_add(1, Ordering::SeqCst);
+            while self.readers[epoch % 2].load(Ordering::SeqCst) != 0 {
+                core::hint::spin_loop();
+            }
+            self.writing.store(false, Ordering::Release);
+
+            old
+        };
+
+        // SAFETY: The pointer was created by `Box::into_raw` in this method and nobody else has
+        // access to it anymore.
+        (!old.is_null()).then(|| unsafe { Box::from_raw(old) })
+    }
+
+    /// Replaces the value with `boxed`, dropping the old value.
+    #[inline]
+    pub fn store(&self, boxed: Box<P>) {
+        self.swap(Some(boxed));
+    }
+
+    /// Takes the value out of the slot, leaving it empty.
+    #[inline]
+    pub fn take(&self) -> Option<Box<P>> {
+        self.swap(None)
+    }
+
+    /// Returns `true` if the slot is empty.
+    ///
+    /// The value may be replaced right after this returns, so this is only a hint.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        #[cfg(feature = "critical-section")]
+        {
+            critical_section::with(|cs| self.ptr.borrow(cs).get().is_null())
+        }
+        #[cfg(not(feature = "critical-section"))]
+        {
+            self.ptr.load(Ordering::Relaxed).is_null()
+        }
+    }
+
+    /// Consumes the slot, returning its value.
+    #[inline]
+    pub fn into_inner(self) -> Option<Box<P>> {
+        self.take()
+    }
+}
+
+impl<P: BoxPool> Default for AtomicSlot<P> {
+    #[inline]
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<P: BoxPool> From<Box<P>> for AtomicSlot<P> {
+    #[inline]
+    fn from(boxed: Box<P>) -> Self {
+        Self::new(boxed)
+    }
+}
+
+impl<P: BoxPool> Drop for AtomicSlot<P> {
+    #[inline]
+    fn drop(&mut self) {
+        self.take();
+    }
+}
+
+impl<P: BoxPool> fmt::Debug for AtomicSlot<P>
+where
+    P::Data: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.read(|value| f.debug_tuple("AtomicSlot").field(&value).finish())
+    }
+}
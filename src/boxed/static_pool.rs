@@ -0,0 +1,267 @@
+//! A memory pool type that doesn't need to be created through a macro.
+
+#[cfg(not(alloc_backend))]
+use super::sync::Flag;
+#[cfg(not(alloc_backend))]
+use core::{cell::UnsafeCell, mem::MaybeUninit, ptr::NonNull};
+use core::{
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+/// A memory pool of `N` blocks, each able to hold a `T`.
+///
+/// Unlike the pools created through [`crate::box_pool`], this is a regular type that can be stored
+/// in a `static` and named in generic code. The allocated values are managed by [`StaticBox`]
+/// rather than [`super::Box`].
+///
+/// With `heapless`, the blocks are part of the pool itself. With `alloc`, the values are allocated
+/// on the heap and `N` is ignored.
+///
+/// # Example
+///
+/// ```
+/// use mayheap::boxed::StaticBoxPool;
+///
+/// static POOL: StaticBoxPool<[u8; 32], 2> = StaticBoxPool::new();
+///
+/// let mut frame = POOL.alloc([0; 32]).unwrap();
+/// frame[0] = 0x7e;
+/// assert_eq!(frame[..2], [0x7e, 0]);
+///
+/// let _frame = POOL.try_alloc([1; 32]).unwrap();
+///
+/// // Pool is exhausted so should fail with `heapless` but not with `alloc`.
+/// let res = POOL.try_alloc([2; 32]);
+/// #[cfg(feature = "heapless")]
+/// assert_eq!(res.unwrap_err(), mayheap::Error::PoolExhausted);
+/// #[cfg(feature = "alloc")]
+/// res.unwrap();
+///
+/// // Blocks are returned to the pool once the boxes are dropped.
+/// drop(frame);
+/// let frame = POOL.alloc_with(|| [3; 32]).unwrap();
+/// assert_eq!(frame[31], 3);
+/// ```
+pub struct StaticBoxPool<T, const N: usize> {
+    #[cfg(not(alloc_backend))]
+    blocks: [Block<T>; N],
+    _data: PhantomData<T>,
+}
+
+// SAFETY: Each block (and hence each value) is only ever accessed through the one `StaticBox` that
+// claimed it, so sharing the pool is equivalent to sending the values.
+unsafe impl<T: Send, const N: usize> Sync for StaticBoxPool<T, N> {}
+
+impl<T, const N: usize> StaticBoxPool<T, N> {
+    /// Creates a new pool.
+    pub const fn new() -> Self {
+        Self {
+            #[cfg(not(alloc_backend))]
+            blocks: [const { Block::new() }; N],
+            _data: PhantomData,
+        }
+    }
+
+    /// Allocates a new boxed value from the pool.
+    ///
+    /// The value is given back if there are no free blocks left in the pool.
+    pub fn alloc(&'static self, value: T) -> Result<StaticBox<T>, T> {
+        #[cfg(alloc_backend)]
+        {
+            Ok(StaticBox {
+                value: crate::reexports::alloc::boxed::Box::new(value),
+            })
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            match self.claim() {
+                Some(block) => Ok(block.init(value)),
+                None => Err(value),
+            }
+        }
+    }
+
+    /// Allocates a new boxed value from the pool.
+    ///
+    /// Fails with [`crate::Error::PoolExhausted`] if there are no free blocks left in the pool, in
+    /// which case the value is dropped.
+    pub fn try_alloc(&'static self, value: T) -> Result<StaticBox<T>, crate::Error> {
+        self.alloc(value).map_err(|_| crate::Error::PoolExhausted)
+    }
+
+    /// Allocates a new boxed value from the pool, holding the value returned by `f`.
+    ///
+    /// Fails with [`crate::Error::PoolExhausted`] if there are no free blocks left in the pool, in
+    /// which case `f` is not called.
+    pub fn alloc_with<F>(&'static self, f: F) -> Result<StaticBox<T>, crate::Error>
+    where
+        F: FnOnce() -> T,
+    {
+        #[cfg(alloc_backend)]
+        {
+            self.try_alloc(f())
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            self.claim()
+                .map(|block| block.init(f()))
+                .ok_or(crate::Error::PoolExhausted)
+        }
+    }
+
+    /// Reclaims all the blocks of the pool, including the ones of leaked boxes.
+    ///
+    /// This is meant for tests that exhaust or leak from a pool, which would otherwise affect all
+    /// the subsequent tests in the same process. The leaked values are not dropped. This is a no-op
+    /// with `alloc`.
+    ///
+    /// Only available with the `test-util` feature enabled.
+    ///
+    /// # Safety
+    ///
+    /// Any boxes allocated from the pool that are still alive must never be used or dropped again
+    /// (e.g because they've been leaked), since their memory will be reused.
+    #[cfg(any(test, feature = "test-util"))]
+    pub unsafe fn reset(&self) {
+        #[cfg(not(alloc_backend))]
+        for block in &self.blocks {
+            block.used.release();
+        }
+    }
+
+    #[cfg(not(alloc_backend))]
+    fn claim(&'static self) -> Option<&'static Block<T>> {
+        self.blocks.iter().find(|block| block.used.claim())
+    }
+}
+
+impl<T, const N: usize> Default for StaticBoxPool<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for StaticBoxPool<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticBoxPool")
+            .field("capacity", &N)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(not(alloc_backend))]
+struct Block<T> {
+    used: Flag,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+#[cfg(not(alloc_backend))]
+impl<T> Block<T> {
+    const fn new() -> Self {
+        Self {
+            used: Flag::new(),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    // Must only be called on a block that has just been claimed.
+    fn init(&'static self, value: T) -> StaticBox<T> {
+        // SAFETY: The block was claimed by us so nobody else is accessing it.
+        let value = unsafe { (*self.value.get()).write(value) };
+
+        StaticBox {
+            value: NonNull::from(value),
+            used: &self.used,
+        }
+    }
+}
+
+/// A boxed value managed by a [`StaticBoxPool`].
+///
+/// The block is returned to the pool when the box is dropped.
+pub struct StaticBox<T> {
+    #[cfg(alloc_backend)]
+    value: crate::reexports::alloc::boxed::Box<T>,
+    #[cfg(not(alloc_backend))]
+    value: NonNull<T>,
+    #[cfg(not(alloc_backend))]
+    used: &'static Flag,
+}
+
+// SAFETY: The box uniquely owns the value, just like `alloc::boxed::Box`.
+unsafe impl<T: Send> Send for StaticBox<T> {}
+// SAFETY: The box uniquely owns the value, just like `alloc::boxed::Box`.
+unsafe impl<T: Sync> Sync for StaticBox<T> {}
+
+impl<T> Deref for StaticBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        #[cfg(alloc_backend)]
+        {
+            &self.value
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            // SAFETY: The value is initialized and uniquely owned by us.
+            unsafe { self.value.as_ref() }
+        }
+    }
+}
+
+impl<T> DerefMut for StaticBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        #[cfg(alloc_backend)]
+        {
+            &mut self.value
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            // SAFETY: The value is initialized and uniquely owned by us.
+            unsafe { self.value.as_mut() }
+        }
+    }
+}
+
+#[cfg(not(alloc_backend))]
+impl<T> Drop for StaticBox<T> {
+    fn drop(&mut self) {
+        // SAFETY: The value is initialized and never used again.
+        unsafe { self.value.drop_in_place() };
+        self.used.release();
+    }
+}
+
+impl<T> AsRef<T> for StaticBox<T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T> AsMut<T> for StaticBox<T> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for StaticBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for StaticBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for StaticBox<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.deref().eq(other.deref())
+    }
+}
+
+impl<T: Eq> Eq for StaticBox<T> {}
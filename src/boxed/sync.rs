@@ -0,0 +1,389 @@
+//! Synchronization primitives for the memory pools.
+//!
+//! These are based on a critical section if the `critical-section` feature is enabled, and on
+//! atomic compare-and-swap operations (through `portable-atomic` with `heapless`) otherwise.
+
+#[cfg(not(alloc_backend))]
+use core::task::Waker;
+#[cfg(all(not(feature = "critical-section"), not(alloc_backend)))]
+use portable_atomic::{AtomicBool, AtomicU8, Ordering};
+#[cfg(all(feature = "critical-section", not(alloc_backend)))]
+use {
+    core::cell::{Cell, RefCell},
+    critical_section::Mutex,
+};
+
+#[cfg(not(alloc_backend))]
+/// A flag that can be claimed by one owner at a time.
+pub(crate) struct Flag {
+    #[cfg(feature = "critical-section")]
+    claimed: Mutex<Cell<bool>>,
+    #[cfg(not(feature = "critical-section"))]
+    claimed: AtomicBool,
+}
+
+#[cfg(not(alloc_backend))]
+impl Flag {
+    pub(crate) const fn new() -> Self {
+        Self {
+            #[cfg(feature = "critical-section")]
+            claimed: Mutex::new(Cell::new(false)),
+            #[cfg(not(feature = "critical-section"))]
+            claimed: AtomicBool::new(false),
+        }
+    }
+
+    /// Claims the flag, returning `false` if it's already claimed.
+    pub(crate) fn claim(&self) -> bool {
+        #[cfg(feature = "critical-section")]
+        {
+            critical_section::with(|cs| !self.claimed.borrow(cs).replace(true))
+        }
+        #[cfg(not(feature = "critical-section"))]
+        {
+            self.claimed
+                .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+        }
+    }
+
+    /// Releases the flag.
+    pub(crate) fn release(&self) {
+        #[cfg(feature = "critical-section")]
+        critical_section::with(|cs| self.claimed.borrow(cs).set(false));
+        #[cfg(not(feature = "critical-section"))]
+        self.claimed.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(not(alloc_backend))]
+/// `box_pool!` implementation detail.
+///
+/// Runs a function exactly once, even if called concurrently.
+#[doc(hidden)]
+pub struct Once {
+    #[cfg(feature = "critical-section")]
+    done: Mutex<Cell<bool>>,
+    // Only ever set inside the critical section, after initialization, so that the fast path
+    // doesn't need to enter it. Atomic loads and stores are available even without CAS.
+    #[cfg(feature = "critical-section")]
+    initialized: core::sync::atomic::AtomicBool,
+    #[cfg(not(feature = "critical-section"))]
+    state: AtomicU8,
+}
+
+#[cfg(not(alloc_backend))]
+#[cfg(not(feature = "critical-section"))]
+#[repr(u8)]
+#[derive(PartialEq)]
+enum InitState {
+    Uninitialized = 0,
+    Initializing = 1,
+    Initialized = 2,
+}
+
+#[cfg(not(alloc_backend))]
+#[cfg(not(feature = "critical-section"))]
+impl From<u8> for InitState {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => InitState::Uninitialized,
+            1 => InitState::Initializing,
+            2 => InitState::Initialized,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[cfg(not(alloc_backend))]
+impl Once {
+    /// Creates a new `Once`.
+    pub const fn new() -> Self {
+        Self {
+            #[cfg(feature = "critical-section")]
+            done: Mutex::new(Cell::new(false)),
+            #[cfg(feature = "critical-section")]
+            initialized: core::sync::atomic::AtomicBool::new(false),
+            #[cfg(not(feature = "critical-section"))]
+            state: AtomicU8::new(InitState::Uninitialized as u8),
+        }
+    }
+
+    /// Calls `f` if it's the first call, otherwise waits for the first call to have finished.
+    pub fn call_once<F: FnOnce()>(&self, f: F) {
+        #[cfg(feature = "critical-section")]
+        {
+            // Fast path for when already initialized.
+            if self.initialized.load(core::sync::atomic::Ordering::Acquire) {
+                return;
+            }
+
+            critical_section::with(|cs| {
+                if !self.done.borrow(cs).replace(true) {
+                    f();
+                    self.initialized
+                        .store(true, core::sync::atomic::Ordering::Release);
+                }
+            });
+        }
+        #[cfg(not(feature = "critical-section"))]
+        {
+            // Fast path for when already initialized.
+            if self.state.load(Ordering::Acquire) == InitState::Initialized as u8 {
+                return;
+            }
+
+            match self
+                .state
+                .compare_exchange(
+                    InitState::Uninitialized as u8,
+                    InitState::Initializing as u8,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                )
+                .map(|state| state.into())
+                .map_err(|state| state.into())
+            {
+                Ok(InitState::Uninitialized) => {
+                    // We won the race, initialize.
+                    f();
+                    self.state
+                        .store(InitState::Initialized as u8, Ordering::Release);
+                }
+                Err(InitState::Initializing) => {
+                    // Someone else is initializing, wait.
+                    while self.state.load(Ordering::Acquire) == InitState::Initializing as u8 {
+                        core::hint::spin_loop();
+                    }
+                }
+                Err(InitState::Initialized) => {
+                    // Already initialized.
+                }
+                // All other states should never happen.
+                _ => unreachable!(),
+            }
+        }
+    }
+}
+
+#[cfg(not(alloc_backend))]
+impl Default for Once {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(not(alloc_backend))]
+impl core::fmt::Debug for Once {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Once").finish_non_exhaustive()
+    }
+}
+
+/// The maximum number of tasks that can wait for a free block of the same pool at the same time.
+#[cfg(not(alloc_backend))]
+const MAX_WAITERS: usize = 8;
+
+#[cfg(not(alloc_backend))]
+/// `box_pool!` implementation detail.
+///
+/// Holds the wakers of the tasks waiting for a free block, each in a slot of its own.
+#[doc(hidden)]
+pub struct Waiters {
+    slots: [WaiterSlot; MAX_WAITERS],
+}
+
+#[cfg(not(alloc_backend))]
+struct WaiterSlot {
+    claimed: Flag,
+    #[cfg(feature = "critical-section")]
+    waker: Mutex<RefCell<Option<Waker>>>,
+    #[cfg(not(feature = "critical-section"))]
+    waker: atomic_waker::AtomicWaker,
+}
+
+#[cfg(not(alloc_backend))]
+impl Waiters {
+    /// Creates a new `Waiters` with all the slots free.
+    pub const fn new() -> Self {
+        Self {
+            slots: [const {
+                WaiterSlot {
+                    claimed: Flag::new(),
+                    #[cfg(feature = "critical-section")]
+                    waker: Mutex::new(RefCell::new(None)),
+                    #[cfg(not(feature = "critical-section"))]
+                    waker: atomic_waker::AtomicWaker::new(),
+                }
+            }; MAX_WAITERS],
+        }
+    }
+
+    /// Registers `waker` in the slot `slot`, claiming a free slot first if it's `None`.
+    ///
+    /// Returns `false` if all the slots are claimed.
+    pub fn register(&self, slot: &mut Option<usize>, waker: &Waker) -> bool {
+        let index = match *slot {
+            Some(index) => index,
+            None => match self.slots.iter().position(|s| s.claimed.claim()) {
+                Some(index) => *slot.insert(index),
+                None => return false,
+            },
+        };
+
+        let slot = &self.slots[index];
+        #[cfg(feature = "critical-section")]
+        critical_section::with(|cs| {
+            let mut registered = slot.waker.borrow_ref_mut(cs);
+            match &*registered {
+                Some(registered) if registered.will_wake(waker) => (),
+                _ => *registered = Some(waker.clone()),
+            }
+        });
+        #[cfg(not(feature = "critical-section"))]
+        slot.waker.register(waker);
+
+        true
+    }
+
+    /// Frees the slot `slot`, claimed through [`Waiters::register`].
+    pub fn unregister(&self, slot: usize) {
+        let slot = &self.slots[slot];
+        #[cfg(feature = "critical-section")]
+        let waker = critical_section::with(|cs| slot.waker.borrow_ref_mut(cs).take());
+        #[cfg(not(feature = "critical-section"))]
+        let waker = slot.waker.take();
+        slot.claimed.release();
+
+        // Drop the waker outside of the critical section.
+        drop(waker);
+    }
+
+    /// Wakes up all the registered wakers.
+    ///
+    /// All the waiting tasks get a chance to allocate the freed block, so that it's never lost to
+    /// a task that isn't waiting anymore.
+    pub fn wake(&self) {
+        for slot in &self.slots {
+            #[cfg(feature = "critical-section")]
+            let waker = critical_section::with(|cs| slot.waker.borrow_ref_mut(cs).take());
+            #[cfg(not(feature = "critical-section"))]
+            let waker = slot.waker.take();
+
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+}
+
+#[cfg(not(alloc_backend))]
+impl Default for Waiters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(not(alloc_backend))]
+impl core::fmt::Debug for Waiters {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Waiters").finish_non_exhaustive()
+    }
+}
+
+#[cfg(any(
+    feature = "portable-atomic",
+    feature = "critical-section",
+    target_has_atomic = "ptr"
+))]
+/// `box_pool!` implementation detail.
+///
+/// Counts the boxes in use.
+#[doc(hidden)]
+pub struct Counter {
+    #[cfg(feature = "critical-section")]
+    count: critical_section::Mutex<core::cell::Cell<usize>>,
+    #[cfg(all(not(feature = "critical-section"), feature = "portable-atomic"))]
+    count: portable_atomic::AtomicUsize,
+    #[cfg(all(not(feature = "critical-section"), not(feature = "portable-atomic")))]
+    count: core::sync::atomic::AtomicUsize,
+}
+
+#[cfg(any(
+    feature = "portable-atomic",
+    feature = "critical-section",
+    target_has_atomic = "ptr"
+))]
+impl Counter {
+    /// Creates a new zeroed `Counter`.
+    pub const fn new() -> Self {
+        Self {
+            #[cfg(feature = "critical-section")]
+            count: critical_section::Mutex::new(core::cell::Cell::new(0)),
+            #[cfg(all(not(feature = "critical-section"), feature = "portable-atomic"))]
+            count: portable_atomic::AtomicUsize::new(0),
+            #[cfg(all(not(feature = "critical-section"), not(feature = "portable-atomic")))]
+            count: core::sync::atomic::AtomicUsize::new(0),
+        }
+    }
+
+    /// Increments the count, returning the new value.
+    pub fn increment(&self) -> usize {
+        #[cfg(feature = "critical-section")]
+        {
+            critical_section::with(|cs| {
+                let count = self.count.borrow(cs);
+                count.set(count.get() + 1);
+
+                count.get()
+            })
+        }
+        #[cfg(not(feature = "critical-section"))]
+        {
+            self.count
+                .fetch_add(1, core::sync::atomic::Ordering::Relaxed)
+                + 1
+        }
+    }
+
+    /// Decrements the count, returning the new value.
+    pub fn decrement(&self) -> usize {
+        #[cfg(feature = "critical-section")]
+        {
+            critical_section::with(|cs| {
+                let count = self.count.borrow(cs);
+                count.set(count.get() - 1);
+
+                count.get()
+            })
+        }
+        #[cfg(not(feature = "critical-section"))]
+        {
+            self.count
+                .fetch_sub(1, core::sync::atomic::Ordering::Relaxed)
+                - 1
+        }
+    }
+}
+
+#[cfg(any(
+    feature = "portable-atomic",
+    feature = "critical-section",
+    target_has_atomic = "ptr"
+))]
+impl Default for Counter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(
+    feature = "portable-atomic",
+    feature = "critical-section",
+    target_has_atomic = "ptr"
+))]
+impl core::fmt::Debug for Counter {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Counter").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,323 @@
+//! A bounded broadcast channel, for fanning out events to multiple consumers.
+//!
+//! Every message sent through a [`Channel`] is received by all of its [`Receiver`]s, each keeping
+//! its own cursor over a shared ring of the last `N` messages. A receiver that falls behind by
+//! more than `N` messages gets a lag error, telling it how many it missed, and then resumes from
+//! the oldest message still available. Senders never wait.
+//!
+//! Receiving requires the messages to be [`Copy`], since they're copied out while the channel is
+//! locked and no user code (e.g. a `Clone` implementation) must run then. For larger messages,
+//! send e.g. an index into a table of your own.
+//!
+//! Unlike the capacities of the collections, `N` and the maximum number of receivers, `R`, are
+//! honored with both backends, since they define the behavior of the channel. With `alloc`
+//! backend, the messages are stored on the heap.
+//!
+//! This module is only available if either the `portable-atomic` or `critical-section` feature is
+//! enabled, or with `alloc` backend on targets with atomic compare-and-swap operations. The channel
+//! is synchronized through a critical section if the `critical-section` feature is enabled, and
+//! through a spin lock otherwise. Enable `critical-section` if the channel is shared with interrupt
+//! handlers.
+//!
+//! With the `futures-core` feature enabled, [`Receiver`] also implements `futures_core::Stream`.
+//!
+//! # Example
+//!
+//! ```
+//! use mayheap::broadcast::{Channel, TryRecvError};
+//!
+//! // Keep the last 2 messages, for at most 4 receivers.
+//! static EVENTS: Channel<u32, 2, 4> = Channel::new();
+//!
+//! let mut display = EVENTS.subscribe().unwrap();
+//! let mut logger = EVENTS.subscribe().unwrap();
+//!
+//! EVENTS.send(1);
+//! assert_eq!(display.try_recv(), Ok(1));
+//! assert_eq!(display.try_recv(), Err(TryRecvError::Empty));
+//!
+//! EVENTS.send(2);
+//! EVENTS.send(3);
+//! // The logger missed the first message.
+//! assert_eq!(logger.try_recv(), Err(TryRecvError::Lagged(1)));
+//! assert_eq!(logger.try_recv(), Ok(2));
+//! assert_eq!(logger.try_recv(), Ok(3));
+//! ```
+
+use core::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use crate::lock::Lock;
+
+/// A bounded broadcast channel, keeping the last `N` messages for at most `R` receivers.
+///
+/// See the [module documentation](self) for more.
+pub struct Channel<T, const N: usize, const R: usize> {
+    state: Lock<State<T, N, R>>,
+}
+
+impl<T, const N: usize, const R: usize> Channel<T, N, R> {
+    /// Creates a new channel.
+    ///
+    /// `N` must not be zero. This is checked at compile-time.
+    #[inline]
+    pub const fn new() -> Self {
+        const { assert!(N > 0, "`N` must not be zero") };
+
+        Self {
+            state: Lock::new(State {
+                #[cfg(alloc_backend)]
+                messages: crate::reexports::alloc::collections::VecDeque::new(),
+                #[cfg(not(alloc_backend))]
+                messages: heapless::Deque::new(),
+                next: 0,
+                receivers: [const { Slot::Free }; R],
+            }),
+        }
+    }
+
+    /// Creates a new receiver, receiving the messages sent from now on.
+    ///
+    /// Fails with [`crate::Error::BufferOverflow`] if there are already `R` receivers.
+    #[inline]
+    pub fn subscribe(&self) -> crate::Result<Receiver<'_, T, N, R>> {
+        self.state.with(|state| {
+            let Some(id) = state.receivers.iter().position(|s| matches!(s, Slot::Free)) else {
+                return Err(crate::Error::overflow::<Self>(R, 1, R));
+            };
+            state.receivers[id] = Slot::Idle;
+
+            Ok(Receiver {
+                channel: self,
+                id,
+                pos: state.next,
+            })
+        })
+    }
+
+    /// Sends `value` to all the receivers, dropping the oldest message if the channel is full.
+    #[inline]
+    pub fn send(&self, value: T) {
+        let mut wakers = [const { None }; R];
+        let dropped = self.state.with(|state| {
+            let dropped = if state.messages.len() == N {
+                state.messages.pop_front()
+            } else {
+                None
+            };
+            #[cfg(alloc_backend)]
+            state.messages.push_back(value);
+            // We just made sure it fits.
+            #[cfg(not(alloc_backend))]
+            let _ = state.messages.push_back(value);
+            state.next += 1;
+
+            for (slot, waker) in state.receivers.iter_mut().zip(&mut wakers) {
+                *waker = match core::mem::replace(slot, Slot::Idle) {
+                    Slot::Waiting(w) => Some(w),
+                    other => {
+                        *slot = other;
+
+                        None
+                    }
+                };
+            }
+
+            dropped
+        });
+        // Don't run any user code while holding the lock.
+        drop(dropped);
+        for waker in wakers.into_iter().flatten() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T, const N: usize, const R: usize> Default for Channel<T, N, R> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize, const R: usize> fmt::Debug for Channel<T, N, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Channel").finish_non_exhaustive()
+    }
+}
+
+/// A receiver of a [`Channel`].
+///
+/// This struct is created by [`Channel::subscribe`]. Dropping it frees its slot in the channel.
+pub struct Receiver<'a, T, const N: usize, const R: usize> {
+    channel: &'a Channel<T, N, R>,
+    id: usize,
+    // The sequence number of the next message to receive.
+    pos: u64,
+}
+
+impl<'a, T: Copy, const N: usize, const R: usize> Receiver<'a, T, N, R> {
+    /// Receives the next message, if any.
+    ///
+    /// If the receiver fell behind, [`TryRecvError::Lagged`] is returned first and the following
+    /// calls resume from the oldest message still available.
+    #[inline]
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.channel.state.with(|state| state.recv(&mut self.pos))
+    }
+
+    /// Receives the next message, waiting for one to be sent if needed.
+    ///
+    /// Fails with [`Lagged`] if the receiver fell behind, in which case the following calls
+    /// resume from the oldest message still available.
+    #[inline]
+    pub fn recv(&mut self) -> Recv<'_, 'a, T, N, R> {
+        Recv { receiver: self }
+    }
+
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Result<T, Lagged>> {
+        let res = self.channel.state.with(|state| {
+            let res = state.recv(&mut self.pos);
+            if let Err(TryRecvError::Empty) = res {
+                // Registering under the same lock as the check ensures no message is missed.
+                let slot = &mut state.receivers[self.id];
+                match slot {
+                    Slot::Waiting(waker) if waker.will_wake(cx.waker()) => (),
+                    _ => *slot = Slot::Waiting(cx.waker().clone()),
+                }
+            }
+
+            res
+        });
+
+        match res {
+            Ok(value) => Poll::Ready(Ok(value)),
+            Err(TryRecvError::Lagged(missed)) => Poll::Ready(Err(Lagged(missed))),
+            Err(TryRecvError::Empty) => Poll::Pending,
+        }
+    }
+}
+
+/// Yields the messages as they're sent, and [`Lagged`] if the receiver fell behind, the same way
+/// as [`Receiver::recv`]. The stream never ends.
+#[cfg(feature = "futures-core")]
+impl<T: Copy, const N: usize, const R: usize> futures_core::Stream for Receiver<'_, T, N, R> {
+    type Item = Result<T, Lagged>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_recv(cx).map(Some)
+    }
+}
+
+impl<T, const N: usize, const R: usize> Drop for Receiver<'_, T, N, R> {
+    #[inline]
+    fn drop(&mut self) {
+        let slot = self
+            .channel
+            .state
+            .with(|state| core::mem::replace(&mut state.receivers[self.id], Slot::Free));
+        // Drop any waker outside of the lock.
+        drop(slot);
+    }
+}
+
+impl<T, const N: usize, const R: usize> fmt::Debug for Receiver<'_, T, N, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("pos", &self.pos)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Future returned by [`Receiver::recv`].
+pub struct Recv<'r, 'a, T, const N: usize, const R: usize> {
+    receiver: &'r mut Receiver<'a, T, N, R>,
+}
+
+impl<T: Copy, const N: usize, const R: usize> Future for Recv<'_, '_, T, N, R> {
+    type Output = Result<T, Lagged>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.get_mut().receiver.poll_recv(cx)
+    }
+}
+
+impl<T, const N: usize, const R: usize> fmt::Debug for Recv<'_, '_, T, N, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recv").finish_non_exhaustive()
+    }
+}
+
+/// The error returned by [`Receiver::try_recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TryRecvError {
+    /// No new message was sent.
+    Empty,
+    /// The receiver fell behind and missed this many messages.
+    Lagged(u64),
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => f.write_str("no new message"),
+            TryRecvError::Lagged(missed) => Lagged(*missed).fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for TryRecvError {}
+
+/// The error returned by [`Receiver::recv`], when the receiver fell behind and missed this many
+/// messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Lagged(pub u64);
+
+impl fmt::Display for Lagged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "receiver lagged behind by {} messages", self.0)
+    }
+}
+
+impl core::error::Error for Lagged {}
+
+struct State<T, const N: usize, const R: usize> {
+    #[cfg(alloc_backend)]
+    messages: crate::reexports::alloc::collections::VecDeque<T>,
+    #[cfg(not(alloc_backend))]
+    messages: heapless::Deque<T, N>,
+    // The sequence number of the next message to be sent.
+    next: u64,
+    receivers: [Slot; R],
+}
+
+impl<T: Copy, const N: usize, const R: usize> State<T, N, R> {
+    // Copies the message at `pos`, advancing it.
+    fn recv(&self, pos: &mut u64) -> Result<T, TryRecvError> {
+        let oldest = self.next - self.messages.len() as u64;
+        if *pos < oldest {
+            let missed = oldest - *pos;
+            *pos = oldest;
+
+            return Err(TryRecvError::Lagged(missed));
+        }
+        let value = *self
+            .messages
+            .get((*pos - oldest) as usize)
+            .ok_or(TryRecvError::Empty)?;
+        *pos += 1;
+
+        Ok(value)
+    }
+}
+
+// The state of a receiver slot.
+enum Slot {
+    Free,
+    Idle,
+    Waiting(Waker),
+}
@@ -0,0 +1,93 @@
+//! Conversions from and to the types of the backends.
+//!
+//! These allow interoperating with code using the `heapless` or `alloc` types directly, while
+//! keeping the backend explicit in the signatures that depend on it.
+//!
+//! [`crate::Vec`] and [`crate::String`] are `#[repr(transparent)]` over the type of the backend, so
+//! all the conversions are free. In particular, existing values of the backend types (e.g owned by
+//! a driver or received over FFI) can be viewed through the `mayheap` API by reference.
+//!
+//! # Example
+//!
+//! ```
+//! # #[cfg(feature = "alloc")]
+//! # {
+//! use mayheap::{convert::TryIntoStd, String};
+//!
+//! fn log(line: std::string::String) {
+//!     assert_eq!(line, "ready");
+//! }
+//!
+//! let s = String::<8>::try_from("ready").unwrap();
+//! log(s.try_into_std().unwrap());
+//! # }
+//! ```
+
+/// Access to the `heapless` type backing a type.
+///
+/// This is only implemented when `heapless` is the backend.
+#[cfg(not(alloc_backend))]
+pub trait AsHeapless: Sized {
+    /// The `heapless` type.
+    type Heapless;
+
+    /// Returns a reference to the `heapless` type.
+    fn as_heapless(&self) -> &Self::Heapless;
+
+    /// Returns a mutable reference to the `heapless` type.
+    fn as_heapless_mut(&mut self) -> &mut Self::Heapless;
+
+    /// Converts into the `heapless` type.
+    fn into_heapless(self) -> Self::Heapless;
+
+    /// Converts from the `heapless` type.
+    fn from_heapless(heapless: Self::Heapless) -> Self;
+
+    /// Views a reference to the `heapless` type as a reference to this type, without copying.
+    fn from_heapless_ref(heapless: &Self::Heapless) -> &Self;
+
+    /// Views a mutable reference to the `heapless` type as a mutable reference to this type,
+    /// without copying.
+    fn from_heapless_mut(heapless: &mut Self::Heapless) -> &mut Self;
+}
+
+/// Access to the `alloc` type backing a type.
+///
+/// This is only implemented when `alloc` is the backend.
+#[cfg(alloc_backend)]
+pub trait AsAlloc: Sized {
+    /// The `alloc` type.
+    type Alloc;
+
+    /// Returns a reference to the `alloc` type.
+    fn as_alloc(&self) -> &Self::Alloc;
+
+    /// Returns a mutable reference to the `alloc` type.
+    fn as_alloc_mut(&mut self) -> &mut Self::Alloc;
+
+    /// Converts into the `alloc` type.
+    fn into_alloc(self) -> Self::Alloc;
+
+    /// Converts from the `alloc` type.
+    fn from_alloc(alloc: Self::Alloc) -> Self;
+
+    /// Views a reference to the `alloc` type as a reference to this type, without copying.
+    fn from_alloc_ref(alloc: &Self::Alloc) -> &Self;
+
+    /// Views a mutable reference to the `alloc` type as a mutable reference to this type, without
+    /// copying.
+    fn from_alloc_mut(alloc: &mut Self::Alloc) -> &mut Self;
+}
+
+/// Conversion into the standard (`alloc`) counterpart of a type, regardless of the backend.
+///
+/// With `alloc` backend, this is a move. Otherwise, the contents are copied to a new allocation,
+/// failing with [`crate::Error::OutOfMemory`] if the allocation fails.
+#[cfg(feature = "alloc")]
+pub trait TryIntoStd {
+    /// The standard type.
+    type Std;
+
+    /// Converts into the standard type.
+    fn try_into_std(self) -> crate::Result<Self::Std>;
+}
@@ -1,12 +1,189 @@
 /// The `Error` type for the `mayheap` crate.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Errors compare equal regardless of the name of the collection type in
+/// [`Error::BufferOverflow`], so that comparisons don't depend on the `debug-capacity` feature.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
 pub enum Error {
     /// Attempted to grow a collection beyond its capacity.
     ///
-    /// This error can only occur when `heapless` feature is enabled.
-    BufferOverflow,
+    /// The capacities of the collections are only enforced with `heapless` backend. With `alloc`
+    /// backend, this error only comes from limits that apply on both backends, e.g. the maximum
+    /// number of receivers of a `broadcast::Channel` or the maximum frame size of the `framed`
+    /// module.
+    BufferOverflow {
+        /// The number of additional elements (or bytes in case of strings) requested.
+        requested: usize,
+        /// The capacity of the collection.
+        capacity: usize,
+        /// The length of the collection at the time of the request.
+        len: usize,
+        /// The name of the collection type, if known.
+        ///
+        /// Always `None` unless the `debug-capacity` feature is enabled.
+        type_name: Option<&'static str>,
+    },
+    /// Attempted to grow a collection beyond its capacity, with no details known.
+    ///
+    /// This is only produced by the conversion from `heapless::CapacityError`, and is of the same
+    /// [`ErrorKind`] as [`Error::BufferOverflow`].
+    CapacityExceeded,
     /// Invalid UTF-8 sequence.
     Utf8Error(core::str::Utf8Error),
+    /// Invalid UTF-16 sequence.
+    Utf16Error {
+        /// The index of the first code unit of the invalid sequence.
+        valid_up_to: usize,
+    },
+    /// Non-ASCII byte encountered where only ASCII was expected.
+    NonAscii {
+        /// The index of the first non-ASCII byte.
+        valid_up_to: usize,
+    },
+    /// A formatting trait implementation returned an error.
+    FormatError,
+    /// The memory allocator failed to allocate the requested memory.
+    ///
+    /// This error can only occur when `alloc` feature is enabled.
+    OutOfMemory,
+    /// No free blocks left in a memory pool.
+    ///
+    /// This error can only occur when `heapless` feature is enabled.
+    PoolExhausted,
+    /// An index was past the end of a collection.
+    OutOfBounds {
+        /// The index.
+        index: usize,
+        /// The length of the collection.
+        len: usize,
+    },
+    /// A byte index into a string didn't lie on a [`char`] boundary.
+    NotCharBoundary {
+        /// The index.
+        index: usize,
+    },
+}
+
+/// The kind of an [`Error`], without any of the associated data.
+///
+/// This is useful for matching against the kind of error regardless of its details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// See [`Error::BufferOverflow`] and [`Error::CapacityExceeded`].
+    BufferOverflow,
+    /// See [`Error::Utf8Error`].
+    Utf8Error,
+    /// See [`Error::Utf16Error`].
+    Utf16Error,
+    /// See [`Error::NonAscii`].
+    NonAscii,
+    /// See [`Error::FormatError`].
+    FormatError,
+    /// See [`Error::OutOfMemory`].
+    OutOfMemory,
+    /// See [`Error::PoolExhausted`].
+    PoolExhausted,
+    /// See [`Error::OutOfBounds`].
+    OutOfBounds,
+    /// See [`Error::NotCharBoundary`].
+    NotCharBoundary,
+}
+
+impl Error {
+    /// The kind of this error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::BufferOverflow { .. } | Error::CapacityExceeded => ErrorKind::BufferOverflow,
+            Error::Utf8Error(_) => ErrorKind::Utf8Error,
+            Error::Utf16Error { .. } => ErrorKind::Utf16Error,
+            Error::NonAscii { .. } => ErrorKind::NonAscii,
+            Error::FormatError => ErrorKind::FormatError,
+            Error::OutOfMemory => ErrorKind::OutOfMemory,
+            Error::PoolExhausted => ErrorKind::PoolExhausted,
+            Error::OutOfBounds { .. } => ErrorKind::OutOfBounds,
+            Error::NotCharBoundary { .. } => ErrorKind::NotCharBoundary,
+        }
+    }
+
+    /// Creates an [`Error::BufferOverflow`] for a collection of type `C`, length `len` and
+    /// capacity `capacity` that `requested` additional elements didn't fit in.
+    ///
+    /// `C` is only used with the `debug-capacity` feature enabled.
+    #[cfg_attr(
+        not(feature = "debug-capacity"),
+        allow(clippy::extra_unused_type_parameters)
+    )]
+    pub(crate) fn overflow<C: ?Sized>(len: usize, requested: usize, capacity: usize) -> Self {
+        Error::BufferOverflow {
+            requested,
+            capacity,
+            len,
+            #[cfg(feature = "debug-capacity")]
+            type_name: Some(core::any::type_name::<C>()),
+            #[cfg(not(feature = "debug-capacity"))]
+            type_name: None,
+        }
+    }
+}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Error::BufferOverflow {
+                    requested,
+                    capacity,
+                    len,
+                    type_name: _,
+                },
+                Error::BufferOverflow {
+                    requested: other_requested,
+                    capacity: other_capacity,
+                    len: other_len,
+                    type_name: _,
+                },
+            ) => requested == other_requested && capacity == other_capacity && len == other_len,
+            (Error::Utf8Error(err), Error::Utf8Error(other)) => err == other,
+            (Error::Utf16Error { valid_up_to }, Error::Utf16Error { valid_up_to: other })
+            | (Error::NonAscii { valid_up_to }, Error::NonAscii { valid_up_to: other }) => {
+                valid_up_to == other
+            }
+            (Error::OutOfBounds { index, len }, Error::OutOfBounds { index: i, len: l }) => {
+                index == i && len == l
+            }
+            (Error::NotCharBoundary { index }, Error::NotCharBoundary { index: other }) => {
+                index == other
+            }
+            (Error::CapacityExceeded, Error::CapacityExceeded)
+            | (Error::FormatError, Error::FormatError)
+            | (Error::OutOfMemory, Error::OutOfMemory)
+            | (Error::PoolExhausted, Error::PoolExhausted) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Error {}
+
+/// Logs that `requested` more elements didn't fit in a collection of type `C`, length `len` and
+/// capacity `capacity`, through `log` and/or `defmt`.
+#[cfg(any(feature = "log", feature = "defmt"))]
+#[cold]
+pub(crate) fn log_overflow<C: ?Sized>(len: usize, requested: usize, capacity: usize) {
+    let type_name = core::any::type_name::<C>();
+    #[cfg(feature = "log")]
+    log::warn!(
+        "`{type_name}` overflowed: needed {requested} more at length {len} of capacity {capacity}"
+    );
+    #[cfg(feature = "defmt")]
+    defmt::warn!(
+        "`{=str}` overflowed: needed {=usize} more at length {=usize} of capacity {=usize}",
+        type_name,
+        requested,
+        len,
+        capacity
+    );
 }
 
 /// The Result type for the zlink crate.
@@ -15,8 +192,16 @@ pub type Result<T> = core::result::Result<T, Error>;
 impl core::error::Error for Error {
     fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
         match self {
-            Error::BufferOverflow => None,
+            Error::BufferOverflow { .. } => None,
+            Error::CapacityExceeded => None,
             Error::Utf8Error(err) => Some(err),
+            Error::Utf16Error { .. } => None,
+            Error::NonAscii { .. } => None,
+            Error::FormatError => None,
+            Error::OutOfMemory => None,
+            Error::PoolExhausted => None,
+            Error::OutOfBounds { .. } => None,
+            Error::NotCharBoundary { .. } => None,
         }
     }
 }
@@ -24,12 +209,52 @@ impl core::error::Error for Error {
 impl core::fmt::Display for Error {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
-            Error::BufferOverflow => {
+            Error::BufferOverflow {
+                requested,
+                capacity,
+                len,
+                type_name,
+            } => {
+                if let Some(type_name) = type_name {
+                    return write!(
+                        f,
+                        "Attempted to grow `{type_name}` beyond its capacity \
+                        (requested {requested} more with {len} of {capacity} used)"
+                    );
+                }
+                write!(
+                    f,
+                    "Attempted to grow a collection beyond its capacity \
+                    (requested {requested} more with {len} of {capacity} used)"
+                )
+            }
+            Error::CapacityExceeded => {
                 write!(f, "Attempted to grow a collection beyond its capacity")
             }
             Error::Utf8Error(err) => {
                 write!(f, "Invalid UTF-8 sequence: {err}")
             }
+            Error::Utf16Error { valid_up_to } => {
+                write!(f, "Invalid UTF-16 sequence at index {valid_up_to}")
+            }
+            Error::NonAscii { valid_up_to } => {
+                write!(f, "Non-ASCII byte at index {valid_up_to}")
+            }
+            Error::FormatError => {
+                write!(f, "A formatting trait implementation returned an error")
+            }
+            Error::OutOfMemory => {
+                write!(f, "Memory allocation failed")
+            }
+            Error::PoolExhausted => {
+                write!(f, "No free blocks left in the memory pool")
+            }
+            Error::OutOfBounds { index, len } => {
+                write!(f, "Index {index} out of bounds for length {len}")
+            }
+            Error::NotCharBoundary { index } => {
+                write!(f, "Index {index} is not on a char boundary")
+            }
         }
     }
 }
@@ -39,3 +264,77 @@ impl From<core::str::Utf8Error> for Error {
         Error::Utf8Error(err)
     }
 }
+
+/// Converts to [`Error::CapacityExceeded`], since `heapless::CapacityError` carries no details.
+#[cfg(feature = "heapless")]
+impl From<heapless::CapacityError> for Error {
+    fn from(_: heapless::CapacityError) -> Self {
+        Error::CapacityExceeded
+    }
+}
+
+/// Converts to [`Error::OutOfMemory`].
+#[cfg(feature = "alloc")]
+impl From<crate::reexports::alloc::collections::TryReserveError> for Error {
+    fn from(_: crate::reexports::alloc::collections::TryReserveError) -> Self {
+        Error::OutOfMemory
+    }
+}
+
+/// Maps to the closest matching [`embedded_io::ErrorKind`].
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Error for Error {
+    fn kind(&self) -> embedded_io::ErrorKind {
+        match self {
+            Error::BufferOverflow { .. } | Error::CapacityExceeded => {
+                embedded_io::ErrorKind::WriteZero
+            }
+            Error::Utf8Error(_) | Error::Utf16Error { .. } | Error::NonAscii { .. } => {
+                embedded_io::ErrorKind::InvalidData
+            }
+            Error::OutOfMemory | Error::PoolExhausted => embedded_io::ErrorKind::OutOfMemory,
+            Error::OutOfBounds { .. } | Error::NotCharBoundary { .. } => {
+                embedded_io::ErrorKind::InvalidInput
+            }
+            Error::FormatError => embedded_io::ErrorKind::Other,
+        }
+    }
+}
+
+/// Converts to an [`std::io::Error`] of the closest matching [`std::io::ErrorKind`], which can be
+/// converted back to the original error through [`TryFrom`].
+#[cfg(feature = "std")]
+impl From<Error> for std::io::Error {
+    fn from(err: Error) -> Self {
+        let kind = match err {
+            Error::BufferOverflow { .. } | Error::CapacityExceeded => std::io::ErrorKind::WriteZero,
+            Error::Utf8Error(_) | Error::Utf16Error { .. } | Error::NonAscii { .. } => {
+                std::io::ErrorKind::InvalidData
+            }
+            Error::OutOfMemory | Error::PoolExhausted => std::io::ErrorKind::OutOfMemory,
+            Error::OutOfBounds { .. } | Error::NotCharBoundary { .. } => {
+                std::io::ErrorKind::InvalidInput
+            }
+            Error::FormatError => std::io::ErrorKind::Other,
+        };
+
+        std::io::Error::new(kind, err)
+    }
+}
+
+/// Extracts the [`Error`] wrapped by an [`std::io::Error`], giving back the latter if it doesn't
+/// wrap one.
+#[cfg(feature = "std")]
+impl TryFrom<std::io::Error> for Error {
+    type Error = std::io::Error;
+
+    fn try_from(err: std::io::Error) -> core::result::Result<Self, std::io::Error> {
+        match err
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<Error>())
+        {
+            Some(inner) => Ok(*inner),
+            None => Err(err),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! String escaping that avoids the `core::fmt` machinery.
+
+const HEX_DIGITS: &str = "0123456789abcdef";
+
+/// Writes `s` escaped as the contents of a JSON string, through `write`.
+///
+/// Quotes, backslashes and control characters are escaped. Everything else is written as is.
+pub(crate) fn write_json<W>(s: &str, mut write: W) -> crate::Result<()>
+where
+    W: FnMut(&str) -> crate::Result<()>,
+{
+    let mut start = 0;
+    // All the bytes to escape are ASCII so the indices are on `char` boundaries.
+    for (i, byte) in s.bytes().enumerate() {
+        let escaped = match byte {
+            b'"' => "\\\"",
+            b'\\' => "\\\\",
+            b'\n' => "\\n",
+            b'\r' => "\\r",
+            b'\t' => "\\t",
+            0x08 => "\\b",
+            0x0c => "\\f",
+            0x00..=0x1f => {
+                write(&s[start..i])?;
+                write("\\u00")?;
+                let (high, low) = (usize::from(byte >> 4), usize::from(byte & 0xf));
+                write(&HEX_DIGITS[high..=high])?;
+                write(&HEX_DIGITS[low..=low])?;
+                start = i + 1;
+
+                continue;
+            }
+            _ => continue,
+        };
+        write(&s[start..i])?;
+        write(escaped)?;
+        start = i + 1;
+    }
+
+    write(&s[start..])
+}
+
+/// Writes `s` escaped the same way as [`str::escape_debug`], through `write`.
+pub(crate) fn write_debug<W>(s: &str, mut write: W) -> crate::Result<()>
+where
+    W: FnMut(&str) -> crate::Result<()>,
+{
+    let mut buf = [0; 4];
+    for c in s.escape_debug() {
+        write(c.encode_utf8(&mut buf))?;
+    }
+
+    Ok(())
+}
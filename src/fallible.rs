@@ -0,0 +1,427 @@
+//! Traits for fallible operations on collections, independent of the backend.
+//!
+//! These are implemented by [`crate::Vec`] and [`crate::String`] of any capacity, as well as the
+//! `heapless` (when the `heapless` feature is enabled) and `alloc` (when the `alloc` feature is
+//! enabled) types they wrap. This allows code to be written once against these traits and used with
+//! any of them, e.g library APIs accepting any buffer without being generic over its capacity.
+//!
+//! For the `alloc` types, the operations fail with [`crate::Error::OutOfMemory`] when the
+//! allocation fails, instead of aborting.
+//!
+//! # Example
+//!
+//! ```
+//! use mayheap::fallible::{FallibleString, FallibleVec};
+//!
+//! fn encode<B: FallibleVec<u8>>(buf: &mut B, payload: &[u8]) -> mayheap::Result<()> {
+//!     buf.try_reserve(payload.len() + 1)?;
+//!     buf.try_push(payload.len() as u8).unwrap();
+//!     buf.try_extend_from_slice(payload)
+//! }
+//!
+//! fn greet<S: FallibleString>(out: &mut S) -> mayheap::Result<()> {
+//!     out.try_push_str("hello")?;
+//!     out.try_push('!')
+//! }
+//!
+//! let mut buf = mayheap::Vec::<u8, 8>::new();
+//! encode(&mut buf, b"ping").unwrap();
+//! assert_eq!(buf, b"\x04ping");
+//!
+//! let mut s = mayheap::String::<8>::new();
+//! greet(&mut s).unwrap();
+//! assert_eq!(s, "hello!");
+//!
+//! #[cfg(feature = "heapless")]
+//! {
+//!     let mut buf = heapless::Vec::<u8, 4>::new();
+//!     let err = encode(&mut buf, b"ping").unwrap_err();
+//!     assert_eq!(err.kind(), mayheap::ErrorKind::BufferOverflow);
+//!     assert!(buf.is_empty());
+//! }
+//! ```
+//!
+//! # Mixing backends
+//!
+//! The backend of [`crate::Vec`] and [`crate::String`] is selected for the whole crate graph
+//! through cargo features. If a single binary needs both static and growable buffers (e.g a fixed
+//! buffer in an interrupt handler and a growable one for logging), enable both the `heapless` and
+//! `alloc` features and write the shared code against these traits, using `heapless` and `alloc`
+//! types directly where the choice matters:
+//!
+//! ```
+//! # #[cfg(all(feature = "alloc", feature = "heapless"))]
+//! # {
+//! use mayheap::fallible::FallibleVec;
+//!
+//! fn record<B: FallibleVec<u8>>(buf: &mut B, sample: u8) -> mayheap::Result<()> {
+//!     buf.try_extend_from_slice(&[b'S', sample])
+//! }
+//!
+//! let mut isr_buf = heapless::Vec::<u8, 2>::new();
+//! record(&mut isr_buf, 1).unwrap();
+//! record(&mut isr_buf, 2).unwrap_err();
+//!
+//! let mut log_buf = std::vec::Vec::new();
+//! record(&mut log_buf, 1).unwrap();
+//! record(&mut log_buf, 2).unwrap();
+//! assert_eq!(log_buf, b"S\x01S\x02");
+//! # }
+//! ```
+
+use core::ops;
+
+#[cfg(feature = "alloc")]
+use crate::reexports::alloc;
+
+/// A collection whose storage can be reserved in advance, without panicking.
+pub trait FallibleCollection {
+    /// Returns the number of elements (or bytes in case of strings) the collection can hold
+    /// without reallocating.
+    fn capacity(&self) -> usize;
+
+    /// Reserves capacity for at least `additional` more elements.
+    ///
+    /// Fails if the collection can not hold `additional` more elements.
+    fn try_reserve(&mut self, additional: usize) -> crate::Result<()>;
+}
+
+/// A vector that can be appended to, without panicking.
+///
+/// The contents are accessible as a slice through [`ops::Deref`] and [`ops::DerefMut`].
+pub trait FallibleVec<T>: FallibleCollection + ops::DerefMut<Target = [T]> {
+    /// Appends an `item` to the back of the collection.
+    ///
+    /// The item is given back if the collection can not hold it.
+    fn try_push(&mut self, item: T) -> Result<(), T>;
+
+    /// Clones and appends all elements in a slice to the collection.
+    ///
+    /// Nothing is appended on failure.
+    fn try_extend_from_slice(&mut self, other: &[T]) -> crate::Result<()>
+    where
+        T: Clone;
+
+    /// Removes the last element and returns it, or `None` if the collection is empty.
+    fn pop(&mut self) -> Option<T>;
+
+    /// Shortens the collection, keeping the first `len` elements and dropping the rest.
+    fn truncate(&mut self, len: usize);
+
+    /// Removes all the elements.
+    fn clear(&mut self);
+}
+
+/// A string that can be appended to, without panicking.
+///
+/// The contents are accessible as a string slice through [`ops::Deref`] and [`ops::DerefMut`].
+pub trait FallibleString: FallibleCollection + ops::DerefMut<Target = str> {
+    /// Appends the given [`char`] to the end of the string.
+    fn try_push(&mut self, c: char) -> crate::Result<()>;
+
+    /// Appends a given string slice onto the end of the string.
+    ///
+    /// Nothing is appended on failure.
+    fn try_push_str(&mut self, string: &str) -> crate::Result<()>;
+
+    /// Removes the last character and returns it, or `None` if the string is empty.
+    fn pop(&mut self) -> Option<char>;
+
+    /// Shortens the string to `new_len` bytes.
+    ///
+    /// Fails with [`crate::Error::NotCharBoundary`] if `new_len` does not lie on a [`char`]
+    /// boundary, in which case the string is left unchanged.
+    fn try_truncate(&mut self, new_len: usize) -> crate::Result<()>;
+
+    /// Removes all the contents.
+    fn clear(&mut self);
+}
+
+impl<T, const N: usize> FallibleCollection for crate::Vec<T, N> {
+    #[inline]
+    fn capacity(&self) -> usize {
+        crate::Vec::capacity(self)
+    }
+
+    #[inline]
+    fn try_reserve(&mut self, additional: usize) -> crate::Result<()> {
+        crate::Vec::try_reserve(self, additional)
+    }
+}
+
+impl<T, const N: usize> FallibleVec<T> for crate::Vec<T, N> {
+    #[inline]
+    fn try_push(&mut self, item: T) -> Result<(), T> {
+        self.push(item)
+    }
+
+    #[inline]
+    fn try_extend_from_slice(&mut self, other: &[T]) -> crate::Result<()>
+    where
+        T: Clone,
+    {
+        self.extend_from_slice(other)
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        crate::Vec::pop(self)
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        crate::Vec::truncate(self, len)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        crate::Vec::clear(self)
+    }
+}
+
+impl<const N: usize> FallibleCollection for crate::String<N> {
+    #[inline]
+    fn capacity(&self) -> usize {
+        crate::String::capacity(self)
+    }
+
+    #[inline]
+    fn try_reserve(&mut self, additional: usize) -> crate::Result<()> {
+        crate::String::try_reserve(self, additional)
+    }
+}
+
+impl<const N: usize> FallibleString for crate::String<N> {
+    #[inline]
+    fn try_push(&mut self, c: char) -> crate::Result<()> {
+        self.push(c)
+    }
+
+    #[inline]
+    fn try_push_str(&mut self, string: &str) -> crate::Result<()> {
+        self.push_str(string)
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<char> {
+        crate::String::pop(self)
+    }
+
+    #[inline]
+    fn try_truncate(&mut self, new_len: usize) -> crate::Result<()> {
+        crate::String::try_truncate(self, new_len)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        crate::String::clear(self)
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<T, const N: usize> FallibleCollection for heapless::Vec<T, N> {
+    #[inline]
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    #[inline]
+    fn try_reserve(&mut self, additional: usize) -> crate::Result<()> {
+        if additional > N - self.len() {
+            return Err(crate::Error::overflow::<Self>(self.len(), additional, N));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<T, const N: usize> FallibleVec<T> for heapless::Vec<T, N> {
+    #[inline]
+    fn try_push(&mut self, item: T) -> Result<(), T> {
+        self.push(item)
+    }
+
+    #[inline]
+    fn try_extend_from_slice(&mut self, other: &[T]) -> crate::Result<()>
+    where
+        T: Clone,
+    {
+        let len = self.len();
+        self.extend_from_slice(other)
+            .map_err(|_| crate::Error::overflow::<Self>(len, other.len(), N))
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        heapless::Vec::pop(self)
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        heapless::Vec::truncate(self, len)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        heapless::Vec::clear(self)
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> FallibleCollection for heapless::String<N> {
+    #[inline]
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    #[inline]
+    fn try_reserve(&mut self, additional: usize) -> crate::Result<()> {
+        if additional > N - self.len() {
+            return Err(crate::Error::overflow::<Self>(self.len(), additional, N));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> FallibleString for heapless::String<N> {
+    #[inline]
+    fn try_push(&mut self, c: char) -> crate::Result<()> {
+        let len = self.len();
+        self.push(c)
+            .map_err(|_| crate::Error::overflow::<Self>(len, c.len_utf8(), N))
+    }
+
+    #[inline]
+    fn try_push_str(&mut self, string: &str) -> crate::Result<()> {
+        let len = self.len();
+        self.push_str(string)
+            .map_err(|_| crate::Error::overflow::<Self>(len, string.len(), N))
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<char> {
+        heapless::String::pop(self)
+    }
+
+    #[inline]
+    fn try_truncate(&mut self, new_len: usize) -> crate::Result<()> {
+        if new_len < self.len() && !self.is_char_boundary(new_len) {
+            return Err(crate::Error::NotCharBoundary { index: new_len });
+        }
+        heapless::String::truncate(self, new_len);
+
+        Ok(())
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        heapless::String::clear(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> FallibleCollection for alloc::vec::Vec<T> {
+    #[inline]
+    fn capacity(&self) -> usize {
+        alloc::vec::Vec::capacity(self)
+    }
+
+    #[inline]
+    fn try_reserve(&mut self, additional: usize) -> crate::Result<()> {
+        alloc::vec::Vec::try_reserve(self, additional).map_err(Into::into)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> FallibleVec<T> for alloc::vec::Vec<T> {
+    #[inline]
+    fn try_push(&mut self, item: T) -> Result<(), T> {
+        if alloc::vec::Vec::try_reserve(self, 1).is_err() {
+            return Err(item);
+        }
+        self.push(item);
+
+        Ok(())
+    }
+
+    #[inline]
+    fn try_extend_from_slice(&mut self, other: &[T]) -> crate::Result<()>
+    where
+        T: Clone,
+    {
+        alloc::vec::Vec::try_reserve(self, other.len())?;
+        self.extend_from_slice(other);
+
+        Ok(())
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        alloc::vec::Vec::pop(self)
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        alloc::vec::Vec::truncate(self, len)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        alloc::vec::Vec::clear(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FallibleCollection for alloc::string::String {
+    #[inline]
+    fn capacity(&self) -> usize {
+        alloc::string::String::capacity(self)
+    }
+
+    #[inline]
+    fn try_reserve(&mut self, additional: usize) -> crate::Result<()> {
+        alloc::string::String::try_reserve(self, additional).map_err(Into::into)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FallibleString for alloc::string::String {
+    #[inline]
+    fn try_push(&mut self, c: char) -> crate::Result<()> {
+        alloc::string::String::try_reserve(self, c.len_utf8())?;
+        self.push(c);
+
+        Ok(())
+    }
+
+    #[inline]
+    fn try_push_str(&mut self, string: &str) -> crate::Result<()> {
+        alloc::string::String::try_reserve(self, string.len())?;
+        self.push_str(string);
+
+        Ok(())
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<char> {
+        alloc::string::String::pop(self)
+    }
+
+    #[inline]
+    fn try_truncate(&mut self, new_len: usize) -> crate::Result<()> {
+        if new_len < self.len() && !self.is_char_boundary(new_len) {
+            return Err(crate::Error::NotCharBoundary { index: new_len });
+        }
+        alloc::string::String::truncate(self, new_len);
+
+        Ok(())
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        alloc::string::String::clear(self)
+    }
+}
@@ -0,0 +1,340 @@
+//! Length-prefixed framing over [`embedded_io`] streams.
+//!
+//! Each frame is written as its payload length, encoded as configured through [`Prefix`],
+//! followed by the payload itself. [`FrameEncoder`] writes frames and [`FrameDecoder`] reads them
+//! back into a [`Vec<u8, N>`], keeping track of partially read frames across calls.
+//!
+//! Unlike the capacities of the collections, the maximum payload length of the decoder, `N`, is
+//! honored with both backends, since it guards against malformed or malicious length prefixes.
+//!
+//! This module is only available with the `embedded-io` feature enabled.
+//!
+//! # Example
+//!
+//! ```
+//! use mayheap::{
+//!     framed::{Error, FrameDecoder, FrameEncoder, Prefix},
+//!     io::Cursor,
+//!     Vec,
+//! };
+//!
+//! let encoder = FrameEncoder::new(Prefix::U16Be);
+//! let mut stream = Vec::<u8, 64>::new();
+//! encoder.write_frame(&mut stream, b"hello").unwrap();
+//! encoder.write_frame(&mut stream, b"too long for the decoder").unwrap();
+//! encoder.write_frame(&mut stream, b"bye").unwrap();
+//! assert_eq!(&stream[..7], b"\x00\x05hello");
+//!
+//! let mut decoder = FrameDecoder::<8>::new(Prefix::U16Be);
+//! let mut stream = Cursor::new(stream);
+//! assert_eq!(decoder.read_frame(&mut stream).unwrap(), Some(&b"hello"[..]));
+//! // Frames that don't fit are reported, and then skipped.
+//! let err = decoder.read_frame(&mut stream).unwrap_err();
+//! assert!(matches!(err, Error::Buffer(e) if e.kind() == mayheap::ErrorKind::BufferOverflow));
+//! assert_eq!(decoder.read_frame(&mut stream).unwrap(), Some(&b"bye"[..]));
+//! // The end of the stream, at a frame boundary.
+//! assert_eq!(decoder.read_frame(&mut stream).unwrap(), None);
+//! ```
+
+use core::fmt;
+
+use crate::Vec;
+
+/// The encoding of the length prefix of a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Prefix {
+    /// A single byte, for payloads of up to 255 bytes.
+    U8,
+    /// A little-endian `u16`.
+    U16Le,
+    /// A big-endian `u16`.
+    U16Be,
+    /// A little-endian `u32`.
+    U32Le,
+    /// A big-endian `u32`.
+    U32Be,
+}
+
+impl Prefix {
+    /// Returns the length of the prefix in bytes.
+    #[inline]
+    pub const fn size(self) -> usize {
+        match self {
+            Prefix::U8 => 1,
+            Prefix::U16Le | Prefix::U16Be => 2,
+            Prefix::U32Le | Prefix::U32Be => 4,
+        }
+    }
+
+    /// Returns the maximum payload length this prefix can encode.
+    #[inline]
+    pub const fn max_payload_len(self) -> usize {
+        match self {
+            Prefix::U8 => u8::MAX as usize,
+            Prefix::U16Le | Prefix::U16Be => u16::MAX as usize,
+            Prefix::U32Le | Prefix::U32Be => {
+                if usize::BITS < u32::BITS {
+                    usize::MAX
+                } else {
+                    u32::MAX as usize
+                }
+            }
+        }
+    }
+
+    // Encodes `len` into the first `self.size()` bytes of the returned buffer. `len` must fit.
+    fn encode(self, len: usize) -> [u8; 4] {
+        let mut bytes = [0; 4];
+        match self {
+            Prefix::U8 => bytes[0] = len as u8,
+            Prefix::U16Le => bytes[..2].copy_from_slice(&(len as u16).to_le_bytes()),
+            Prefix::U16Be => bytes[..2].copy_from_slice(&(len as u16).to_be_bytes()),
+            Prefix::U32Le => bytes = (len as u32).to_le_bytes(),
+            Prefix::U32Be => bytes = (len as u32).to_be_bytes(),
+        }
+
+        bytes
+    }
+
+    // Decodes the length from the first `self.size()` bytes of `bytes`, saturating if it doesn't
+    // fit in a `usize`.
+    fn decode(self, bytes: [u8; 4]) -> usize {
+        let len = match self {
+            Prefix::U8 => u32::from(bytes[0]),
+            Prefix::U16Le => u32::from(u16::from_le_bytes([bytes[0], bytes[1]])),
+            Prefix::U16Be => u32::from(u16::from_be_bytes([bytes[0], bytes[1]])),
+            Prefix::U32Le => u32::from_le_bytes(bytes),
+            Prefix::U32Be => u32::from_be_bytes(bytes),
+        };
+
+        usize::try_from(len).unwrap_or(usize::MAX)
+    }
+}
+
+/// Writes length-prefixed frames.
+///
+/// See the [module documentation](self) for an example.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FrameEncoder {
+    prefix: Prefix,
+}
+
+impl FrameEncoder {
+    /// Creates a new encoder, prefixing the frames as given.
+    #[inline]
+    pub const fn new(prefix: Prefix) -> Self {
+        Self { prefix }
+    }
+
+    /// Returns the encoding of the length prefix.
+    #[inline]
+    pub const fn prefix(&self) -> Prefix {
+        self.prefix
+    }
+
+    /// Writes `payload` as a frame to `writer`.
+    ///
+    /// Fails with [`Error::Buffer`] if `payload` is too long for the prefix, in which case
+    /// nothing is written.
+    #[inline]
+    pub fn write_frame<W>(&self, writer: &mut W, payload: &[u8]) -> Result<(), Error<W::Error>>
+    where
+        W: embedded_io::Write,
+    {
+        let header = self.header(payload).map_err(Error::Buffer)?;
+        writer.write_all(&header[..self.prefix.size()])?;
+        writer.write_all(payload)?;
+
+        Ok(())
+    }
+
+    /// Appends `payload` as a frame to `buf`.
+    ///
+    /// Fails with [`crate::Error::BufferOverflow`] if `payload` is too long for the prefix or the
+    /// frame doesn't fit in `buf`, in which case nothing is appended.
+    #[inline]
+    pub fn encode_into<const N: usize>(
+        &self,
+        buf: &mut Vec<u8, N>,
+        payload: &[u8],
+    ) -> crate::Result<()> {
+        let header = self.header(payload)?;
+        let len = buf.len();
+        let res = buf
+            .extend_from_slice(&header[..self.prefix.size()])
+            .and_then(|_| buf.extend_from_slice(payload));
+        if res.is_err() {
+            buf.truncate(len);
+        }
+
+        res
+    }
+
+    // The length prefix of `payload`.
+    fn header(&self, payload: &[u8]) -> crate::Result<[u8; 4]> {
+        let max = self.prefix.max_payload_len();
+        if payload.len() > max {
+            return Err(crate::Error::overflow::<[u8]>(0, payload.len(), max));
+        }
+
+        Ok(self.prefix.encode(payload.len()))
+    }
+}
+
+/// Reads length-prefixed frames with payloads of up to `N` bytes.
+///
+/// The progress of the current frame is kept if reading fails (e.g. on a timeout), so the next
+/// [`FrameDecoder::read_frame`] call resumes where the previous one left off. See the
+/// [module documentation](self) for an example.
+#[derive(Debug, Clone)]
+pub struct FrameDecoder<const N: usize> {
+    prefix: Prefix,
+    header: [u8; 4],
+    // The number of bytes of the header read so far.
+    header_len: usize,
+    payload: Vec<u8, N>,
+    // The number of bytes of the payload read so far.
+    payload_len: usize,
+    // The number of bytes left of an oversized frame being skipped.
+    skip: usize,
+}
+
+impl<const N: usize> FrameDecoder<N> {
+    /// Creates a new decoder, expecting frames prefixed as given.
+    #[inline]
+    pub fn new(prefix: Prefix) -> Self {
+        Self {
+            prefix,
+            header: [0; 4],
+            header_len: 0,
+            payload: Vec::new(),
+            payload_len: 0,
+            skip: 0,
+        }
+    }
+
+    /// Returns the encoding of the length prefix.
+    #[inline]
+    pub const fn prefix(&self) -> Prefix {
+        self.prefix
+    }
+
+    /// Reads the next frame from `reader`, returning its payload.
+    ///
+    /// Blocks until the frame is complete, returning `None` if the stream ends right before it.
+    /// Fails with:
+    ///
+    /// - [`Error::Buffer`] if the payload is longer than `N` bytes. The frame is then skipped by
+    ///   the following calls.
+    /// - [`Error::UnexpectedEof`] if the stream ends in the middle of the frame.
+    /// - [`Error::Io`] if reading fails, in which case the next call resumes the frame.
+    pub fn read_frame<R>(&mut self, reader: &mut R) -> Result<Option<&[u8]>, Error<R::Error>>
+    where
+        R: embedded_io::Read,
+    {
+        // Start over if the previous frame was returned.
+        if self.header_len == self.prefix.size() && self.payload_len == self.payload.len() {
+            self.header_len = 0;
+        }
+
+        loop {
+            if self.skip > 0 {
+                let mut scratch = [0; 32];
+                let len = self.skip.min(scratch.len());
+                self.skip -= read_some(reader, &mut scratch[..len])?;
+            } else if self.header_len < self.prefix.size() {
+                let n = reader.read(&mut self.header[self.header_len..self.prefix.size()])?;
+                if n == 0 {
+                    return match self.header_len {
+                        0 => Ok(None),
+                        _ => Err(Error::UnexpectedEof),
+                    };
+                }
+                self.header_len += n;
+                if self.header_len == self.prefix.size() {
+                    self.start_payload()?;
+                }
+            } else if self.payload_len < self.payload.len() {
+                let buf = &mut self.payload[self.payload_len..];
+                self.payload_len += read_some(reader, buf)?;
+            } else {
+                return Ok(Some(&self.payload));
+            }
+        }
+    }
+
+    // Prepares for reading the payload whose length was just read, or for skipping it if it
+    // doesn't fit.
+    fn start_payload<E>(&mut self) -> Result<(), Error<E>> {
+        let len = self.prefix.decode(self.header);
+        self.payload_len = 0;
+        self.payload.clear();
+        if len > N {
+            self.header_len = 0;
+            self.skip = len;
+
+            return Err(Error::Buffer(crate::Error::overflow::<Vec<u8, N>>(
+                0, len, N,
+            )));
+        }
+
+        self.payload.resize(len, 0).map_err(|e| {
+            self.header_len = 0;
+            self.skip = len;
+
+            Error::Buffer(e)
+        })
+    }
+}
+
+// Reads into `buf`, which must not be empty, treating the end of the stream as an error.
+fn read_some<R>(reader: &mut R, buf: &mut [u8]) -> Result<usize, Error<R::Error>>
+where
+    R: embedded_io::Read,
+{
+    match reader.read(buf)? {
+        0 => Err(Error::UnexpectedEof),
+        n => Ok(n),
+    }
+}
+
+/// The error returned by [`FrameEncoder`] and [`FrameDecoder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error<E> {
+    /// The underlying stream failed.
+    Io(E),
+    /// The frame is too long for the prefix or the buffer.
+    Buffer(crate::Error),
+    /// The stream ended in the middle of a frame.
+    UnexpectedEof,
+}
+
+impl<E> From<E> for Error<E> {
+    #[inline]
+    fn from(err: E) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl<E: fmt::Debug> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "I/O error: {err:?}"),
+            Error::Buffer(err) => err.fmt(f),
+            Error::UnexpectedEof => f.write_str("stream ended in the middle of a frame"),
+        }
+    }
+}
+
+impl<E: fmt::Debug> core::error::Error for Error<E> {}
+
+/// Maps to the closest matching [`embedded_io::ErrorKind`].
+impl<E: embedded_io::Error> embedded_io::Error for Error<E> {
+    fn kind(&self) -> embedded_io::ErrorKind {
+        match self {
+            Error::Io(err) => err.kind(),
+            Error::Buffer(_) => embedded_io::ErrorKind::InvalidData,
+            Error::UnexpectedEof => embedded_io::ErrorKind::Other,
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! A [`GlobalAlloc`] backed by a fixed memory region.
+//!
+//! [`Heap`] allows enabling the `alloc` backend (or using the `alloc` crate in general) on targets
+//! without an allocator of their own, such as bare metal, with no other allocator crate involved.
+//! It hands out blocks from a statically sized region through a first-fit free list, which is
+//! kept sorted by address so that adjacent free blocks are merged back together.
+//!
+//! This module is only available under the same conditions as the [`crate::broadcast`] module.
+//! The heap is synchronized through a critical section if the `critical-section` feature is
+//! enabled, and through a spin lock otherwise. Enable `critical-section` if interrupt handlers
+//! allocate, or if the target lacks atomic compare-and-swap operations (e.g. Cortex-M0).
+//!
+//! # Example
+//!
+//! ```
+//! extern crate alloc;
+//!
+//! use mayheap::heap::Heap;
+//!
+//! #[global_allocator]
+//! static HEAP: Heap<65536> = Heap::new();
+//!
+//! let before = HEAP.used();
+//! let v = alloc::vec![1u32, 2, 3];
+//! assert!(HEAP.used() > before);
+//! drop(v);
+//! ```
+
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    cell::UnsafeCell,
+    fmt,
+    mem::size_of,
+    ptr,
+};
+
+use crate::lock::Lock;
+
+/// A heap of `N` bytes, implementing [`GlobalAlloc`].
+///
+/// Every allocation is rounded up to a multiple of two `usize`s, which is also the minimum
+/// alignment of the blocks. See the [module documentation](self) for more.
+pub struct Heap<const N: usize> {
+    region: UnsafeCell<Region<N>>,
+    state: Lock<State>,
+}
+
+// SAFETY: The region is only accessed through the blocks handed out, which don't overlap, and the
+// free blocks, which are only accessed under the lock.
+unsafe impl<const N: usize> Sync for Heap<N> {}
+
+impl<const N: usize> Heap<N> {
+    /// Creates a new, empty heap.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            region: UnsafeCell::new(Region([0; N])),
+            state: Lock::new(State {
+                head: NONE,
+                init: false,
+                used: 0,
+            }),
+        }
+    }
+
+    /// Returns the number of bytes currently allocated, including the rounding.
+    #[inline]
+    pub fn used(&self) -> usize {
+        self.state.with(|state| state.used)
+    }
+
+    /// Returns the number of bytes currently free.
+    ///
+    /// The free bytes may be fragmented, so an allocation of this size may still fail.
+    #[inline]
+    pub fn free(&self) -> usize {
+        Self::CAPACITY - self.used()
+    }
+
+    // The usable size of the region, which is a whole number of units.
+    const CAPACITY: usize = N / UNIT * UNIT;
+
+    fn base(&self) -> *mut u8 {
+        self.region.get().cast()
+    }
+
+    // Reads the free block at `offset`.
+    //
+    // SAFETY: `offset` must be the offset of a free block and the lock must be held.
+    unsafe fn block(&self, offset: usize) -> Free {
+        unsafe { self.base().add(offset).cast::<Free>().read() }
+    }
+
+    // Writes the free block at `offset`.
+    //
+    // SAFETY: `offset` must be a multiple of `UNIT`, within the region and not part of an
+    // allocated block, and the lock must be held.
+    unsafe fn set_block(&self, offset: usize, block: Free) {
+        unsafe { self.base().add(offset).cast::<Free>().write(block) }
+    }
+
+    // Links the block at `offset` after `prev`, or makes it the head if `prev` is `NONE`.
+    //
+    // SAFETY: Same as `set_block`, for `prev`.
+    unsafe fn link(&self, state: &mut State, prev: usize, offset: usize) {
+        if prev == NONE {
+            state.head = offset;
+        } else {
+            let mut block = unsafe { self.block(prev) };
+            block.next = offset;
+            unsafe { self.set_block(prev, block) };
+        }
+    }
+
+    // Returns the size of the block for `layout` and its alignment.
+    fn block_layout(layout: Layout) -> (usize, usize) {
+        let size = layout.size().max(UNIT).div_ceil(UNIT) * UNIT;
+
+        (size, layout.align().max(UNIT))
+    }
+}
+
+impl<const N: usize> Default for Heap<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<const N: usize> GlobalAlloc for Heap<N> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let (size, align) = Self::block_layout(layout);
+        let base = self.base();
+
+        self.state.with(|state| unsafe {
+            if !state.init {
+                state.init = true;
+                if Self::CAPACITY > 0 {
+                    state.head = 0;
+                    self.set_block(
+                        0,
+                        Free {
+                            size: Self::CAPACITY,
+                            next: NONE,
+                        },
+                    );
+                }
+            }
+
+            let (mut prev, mut offset) = (NONE, state.head);
+            while offset != NONE {
+                let block = self.block(offset);
+                let addr = base as usize + offset;
+                // Both are multiples of `UNIT`, so the gap is too.
+                let gap = addr.next_multiple_of(align) - addr;
+                if gap.checked_add(size).is_some_and(|end| end <= block.size) {
+                    // Put the free space around the allocation in place of the block.
+                    let rest = block.size - gap - size;
+                    let mut next = block.next;
+                    if rest > 0 {
+                        let rest_offset = offset + gap + size;
+                        self.set_block(rest_offset, Free { size: rest, next });
+                        next = rest_offset;
+                    }
+                    if gap > 0 {
+                        self.set_block(offset, Free { size: gap, next });
+                        next = offset;
+                    }
+                    self.link(state, prev, next);
+                    state.used += size;
+
+                    return base.add(offset + gap);
+                }
+                (prev, offset) = (offset, block.next);
+            }
+
+            ptr::null_mut()
+        })
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let (mut size, _) = Self::block_layout(layout);
+        let offset = ptr as usize - self.base() as usize;
+
+        self.state.with(|state| unsafe {
+            state.used -= size;
+
+            // Find the free blocks around the freed one.
+            let (mut prev, mut next) = (NONE, state.head);
+            while next != NONE && next < offset {
+                prev = next;
+                next = self.block(next).next;
+            }
+
+            if next != NONE && offset + size == next {
+                let block = self.block(next);
+                size += block.size;
+                next = block.next;
+            }
+            if prev != NONE {
+                let block = self.block(prev);
+                if prev + block.size == offset {
+                    self.set_block(
+                        prev,
+                        Free {
+                            size: block.size + size,
+                            next,
+                        },
+                    );
+
+                    return;
+                }
+            }
+            self.set_block(offset, Free { size, next });
+            self.link(state, prev, offset);
+        })
+    }
+}
+
+impl<const N: usize> fmt::Debug for Heap<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Heap")
+            .field("capacity", &Self::CAPACITY)
+            .field("used", &self.used())
+            .finish()
+    }
+}
+
+// The region, aligned so that the blocks are aligned to `UNIT` no matter where it's placed.
+#[repr(C, align(16))]
+struct Region<const N: usize>([u8; N]);
+
+// A free block, stored at its start. The offsets are relative to the start of the region.
+#[derive(Clone, Copy)]
+struct Free {
+    size: usize,
+    next: usize,
+}
+
+// The size and minimum alignment of all blocks.
+const UNIT: usize = size_of::<Free>();
+
+// The offset marking the end of the free list.
+const NONE: usize = usize::MAX;
+
+struct State {
+    // The offset of the first free block.
+    head: usize,
+    // Whether the region was set up as a single free block.
+    init: bool,
+    used: usize,
+}
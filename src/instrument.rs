@@ -0,0 +1,181 @@
+//! Occupancy instrumentation for right-sizing the capacities.
+//!
+//! When the `instrument` feature is enabled, all [`crate::Vec`]s and [`crate::String`]s record the
+//! largest length they reached (or would have reached, if not for overflows) and the number of
+//! times they overflowed. The statistics are kept for each type (e.g. `Vec<u8, 64>` separately from
+//! `Vec<u8, 128>`), for up to [`MAX_TYPES`] types, and in total for each kind of collection.
+//!
+//! The statistics are updated under a lock, which is a critical section if the `critical-section`
+//! feature is enabled, and a spin lock otherwise. Enable `critical-section` if the collections are
+//! used in interrupt handlers. On targets without atomic compare-and-swap operations, either
+//! `critical-section` or `portable-atomic` is required.
+//!
+//! # Example
+//!
+//! ```
+//! use mayheap::{instrument, String, Vec};
+//!
+//! let mut v = Vec::<u8, 4>::new();
+//! v.extend_from_slice(b"abc").unwrap();
+//! let mut s = String::<4>::new();
+//! // Doesn't fit so should fail with `heapless` but not with `alloc`.
+//! let res = s.push_str("hello");
+//! #[cfg(feature = "heapless")]
+//! res.unwrap_err();
+//! #[cfg(feature = "alloc")]
+//! res.unwrap();
+//!
+//! assert_eq!(instrument::of::<Vec<u8, 4>>().unwrap().high_water_mark, 3);
+//! let stats = instrument::of::<String<4>>().unwrap();
+//! assert_eq!(stats.high_water_mark, 5);
+//! #[cfg(feature = "heapless")]
+//! assert_eq!(stats.overflows, 1);
+//!
+//! instrument::for_each(|name, stats| println!("{name}: {stats:?}"));
+//! assert!(instrument::string().high_water_mark >= 5);
+//! ```
+
+use core::any::type_name;
+
+use crate::lock::Lock;
+
+#[cfg(not(any(
+    feature = "portable-atomic",
+    feature = "critical-section",
+    target_has_atomic = "ptr"
+)))]
+compile_error!(
+    "the `instrument` feature requires atomic compare-and-swap operations, or either the \
+     `portable-atomic` or `critical-section` feature"
+);
+
+/// The maximum number of collection types whose statistics are kept separately.
+///
+/// The statistics of any further types are only accounted for in the totals.
+pub const MAX_TYPES: usize = 32;
+
+/// Occupancy statistics of a collection type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub struct Stats {
+    /// The largest length reached.
+    ///
+    /// On overflow, the length that would have been reached is recorded. Hence, with `heapless`,
+    /// this is the capacity that would have avoided all the overflows.
+    pub high_water_mark: usize,
+    /// The number of times an operation failed because of overflow.
+    pub overflows: usize,
+}
+
+impl Stats {
+    const ZERO: Self = Self {
+        high_water_mark: 0,
+        overflows: 0,
+    };
+
+    fn record(&mut self, len: usize, overflow: Option<usize>) {
+        let len = match overflow {
+            Some(requested) => {
+                self.overflows += 1;
+
+                len.saturating_add(requested)
+            }
+            None => len,
+        };
+        self.high_water_mark = self.high_water_mark.max(len);
+    }
+}
+
+/// Returns the statistics of the collection type `C` (e.g. `Vec<u8, 64>`).
+///
+/// Returns `None` if the type hasn't been used yet, or if it was used after [`MAX_TYPES`] other
+/// types.
+pub fn of<C: ?Sized>() -> Option<Stats> {
+    let name = type_name::<C>();
+
+    REGISTRY.with(|registry| {
+        registry
+            .types
+            .iter()
+            .flatten()
+            .find(|(n, _)| *n == name)
+            .map(|(_, stats)| *stats)
+    })
+}
+
+/// Calls `f` with the name and the statistics of each collection type used so far, in the order
+/// they were first used.
+pub fn for_each<F>(mut f: F)
+where
+    F: FnMut(&'static str, Stats),
+{
+    // Don't run any user code while holding the lock.
+    let types = REGISTRY.with(|registry| registry.types);
+    for (name, stats) in types.into_iter().flatten() {
+        f(name, stats);
+    }
+}
+
+/// Returns the total statistics of all [`crate::Vec`] types.
+pub fn vec() -> Stats {
+    REGISTRY.with(|registry| registry.vec)
+}
+
+/// Returns the total statistics of all [`crate::String`] types.
+pub fn string() -> Stats {
+    REGISTRY.with(|registry| registry.string)
+}
+
+/// Resets all the statistics.
+pub fn reset() {
+    REGISTRY.with(|registry| *registry = Registry::new());
+}
+
+/// The kind of collection whose statistics are recorded.
+pub(crate) enum Kind {
+    Vec,
+    String,
+}
+
+/// Records the current `len` of a collection of type `C`, along with the `requested` number of
+/// elements in case of overflow.
+pub(crate) fn record<C: ?Sized>(kind: Kind, len: usize, overflow: Option<usize>) {
+    let name = type_name::<C>();
+
+    REGISTRY.with(|registry| {
+        match kind {
+            Kind::Vec => registry.vec.record(len, overflow),
+            Kind::String => registry.string.record(len, overflow),
+        }
+
+        let entry = registry
+            .types
+            .iter_mut()
+            .find(|entry| entry.is_none_or(|(n, _)| n == name));
+        if let Some(entry) = entry {
+            entry
+                .get_or_insert((name, Stats::ZERO))
+                .1
+                .record(len, overflow);
+        }
+    });
+}
+
+static REGISTRY: Lock<Registry> = Lock::new(Registry::new());
+
+struct Registry {
+    vec: Stats,
+    string: Stats,
+    // The statistics of each type, keyed by its name.
+    types: [Option<(&'static str, Stats)>; MAX_TYPES],
+}
+
+impl Registry {
+    const fn new() -> Self {
+        Self {
+            vec: Stats::ZERO,
+            string: Stats::ZERO,
+            types: [None; MAX_TYPES],
+        }
+    }
+}
@@ -0,0 +1,463 @@
+//! Blocking and async I/O over [`crate::Vec`] buffers.
+//!
+//! With the `embedded-io` feature enabled, `Vec<u8, N>` implements [`embedded_io::Write`],
+//! appending the written bytes, and [`Cursor`] implements [`embedded_io::Read`] and
+//! [`embedded_io::BufRead`] (and [`embedded_io::Write`] when wrapping a `Vec<u8, N>`). With the
+//! `embedded-io-async` feature enabled, the same types also implement the `embedded_io_async`
+//! counterparts, so the same buffers can be used by blocking and async drivers alike.
+//!
+//! With the `futures-io` feature enabled, they implement the `futures-io` traits (`AsyncRead`,
+//! `AsyncBufRead` and `AsyncWrite`) instead, for use as in-memory streams on hosts. None of the
+//! async implementations are ever pending.
+//!
+//! [`BufWriter`] batches small writes to an [`embedded_io::Write`] (or `std::io::Write`, with the
+//! `std` feature enabled) implementation.
+//!
+//! # Example
+//!
+//! ```
+//! # #[cfg(feature = "embedded-io")]
+//! # {
+//! use embedded_io::{Read, Write};
+//! use mayheap::{io::Cursor, Vec};
+//!
+//! let mut buf = Vec::<u8, 8>::new();
+//! buf.write_all(b"ping").unwrap();
+//! // Doesn't fit so should fail with `heapless` but not with `alloc`.
+//! let res = buf.write_all(b"-pong");
+//! #[cfg(feature = "heapless")]
+//! res.unwrap_err();
+//! #[cfg(feature = "alloc")]
+//! res.unwrap();
+//!
+//! let mut cursor = Cursor::new(buf);
+//! let mut head = [0; 4];
+//! cursor.read_exact(&mut head).unwrap();
+//! assert_eq!(&head, b"ping");
+//! assert_eq!(cursor.position(), 4);
+//! # }
+//! ```
+
+#[cfg(feature = "futures-io")]
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::Vec;
+
+/// A cursor over an in-memory buffer, keeping track of the read/write position.
+///
+/// Reading consumes the bytes after the position. When wrapping a [`Vec<u8, N>`], writing
+/// overwrites the bytes after the position and appends the rest, as much as the capacity allows.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cursor<T> {
+    inner: T,
+    pos: usize,
+}
+
+impl<T> Cursor<T> {
+    /// Creates a new cursor wrapping `inner`, with the position set to `0`.
+    #[inline]
+    pub const fn new(inner: T) -> Self {
+        Self { inner, pos: 0 }
+    }
+
+    /// Returns the current position.
+    #[inline]
+    pub const fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Sets the current position.
+    ///
+    /// The position can be set beyond the end of the buffer, in which case reads return nothing
+    /// and writes first fill the gap with zeros.
+    #[inline]
+    pub fn set_position(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
+    /// Returns a reference to the underlying buffer.
+    #[inline]
+    pub const fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying buffer.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consumes the cursor, returning the underlying buffer.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: AsRef<[u8]>> Cursor<T> {
+    /// Returns the bytes after the current position.
+    #[inline]
+    pub fn remaining_slice(&self) -> &[u8] {
+        let buf = self.inner.as_ref();
+
+        &buf[self.pos.min(buf.len())..]
+    }
+
+    /// Decodes a variable-length `u32` after the current position, advancing the position.
+    ///
+    /// Returns `None`, leaving the position unchanged, if the value is truncated or doesn't fit.
+    /// See the [`crate::varint`] module for details.
+    #[inline]
+    pub fn get_varint_u32(&mut self) -> Option<u32> {
+        let (value, len) = crate::varint::decode_u32(self.remaining_slice())?;
+        self.pos += len;
+
+        Some(value)
+    }
+
+    /// Decodes a variable-length `u64` after the current position, advancing the position.
+    ///
+    /// Returns `None`, leaving the position unchanged, if the value is truncated or doesn't fit.
+    /// See the [`crate::varint`] module for details.
+    #[inline]
+    pub fn get_varint_u64(&mut self) -> Option<u64> {
+        let (value, len) = crate::varint::decode_u64(self.remaining_slice())?;
+        self.pos += len;
+
+        Some(value)
+    }
+
+    // Copies as many of the bytes after the position as fit in `buf`, advancing the position.
+    #[cfg(any(feature = "embedded-io", feature = "futures-io"))]
+    #[inline]
+    fn read_bytes(&mut self, buf: &mut [u8]) -> usize {
+        let remaining = self.remaining_slice();
+        let n = remaining.len().min(buf.len());
+        buf[..n].copy_from_slice(&remaining[..n]);
+        self.pos += n;
+
+        n
+    }
+}
+
+#[cfg(any(feature = "embedded-io", feature = "futures-io"))]
+impl<const N: usize> Cursor<Vec<u8, N>> {
+    // Overwrites the bytes after the position and appends the rest, as much as the capacity
+    // allows, advancing the position. Fails only if nothing could be written at all.
+    fn write_bytes(&mut self, buf: &[u8]) -> crate::Result<usize> {
+        if self.pos > self.inner.len() {
+            self.inner.resize(self.pos, 0)?;
+        }
+        let overwritten = (self.inner.len() - self.pos).min(buf.len());
+        self.inner[self.pos..self.pos + overwritten].copy_from_slice(&buf[..overwritten]);
+        let appended = match self.inner.write_bytes(&buf[overwritten..]) {
+            Ok(n) => n,
+            Err(e) if overwritten == 0 => return Err(e),
+            Err(_) => 0,
+        };
+        self.pos += overwritten + appended;
+
+        Ok(overwritten + appended)
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<T> embedded_io::ErrorType for Cursor<T> {
+    type Error = crate::Error;
+}
+
+#[cfg(feature = "embedded-io")]
+impl<T: AsRef<[u8]>> embedded_io::Read for Cursor<T> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        Ok(self.read_bytes(buf))
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<T: AsRef<[u8]>> embedded_io::BufRead for Cursor<T> {
+    #[inline]
+    fn fill_buf(&mut self) -> Result<&[u8], Self::Error> {
+        Ok(self.remaining_slice())
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt;
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<const N: usize> embedded_io::Write for Cursor<Vec<u8, N>> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.write_bytes(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Same as the [`embedded_io::Read`] implementation, never pending.
+#[cfg(feature = "embedded-io-async")]
+impl<T: AsRef<[u8]>> embedded_io_async::Read for Cursor<T> {
+    #[inline]
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        embedded_io::Read::read(self, buf)
+    }
+}
+
+/// Same as the [`embedded_io::BufRead`] implementation, never pending.
+#[cfg(feature = "embedded-io-async")]
+impl<T: AsRef<[u8]>> embedded_io_async::BufRead for Cursor<T> {
+    #[inline]
+    async fn fill_buf(&mut self) -> Result<&[u8], Self::Error> {
+        embedded_io::BufRead::fill_buf(self)
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        embedded_io::BufRead::consume(self, amt)
+    }
+}
+
+/// Same as the [`embedded_io::Write`] implementation, never pending.
+#[cfg(feature = "embedded-io-async")]
+impl<const N: usize> embedded_io_async::Write for Cursor<Vec<u8, N>> {
+    #[inline]
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        embedded_io::Write::write(self, buf)
+    }
+
+    #[inline]
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl<T: AsRef<[u8]> + Unpin> futures_io::AsyncRead for Cursor<T> {
+    #[inline]
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Poll::Ready(Ok(self.get_mut().read_bytes(buf)))
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl<T: AsRef<[u8]> + Unpin> futures_io::AsyncBufRead for Cursor<T> {
+    #[inline]
+    fn poll_fill_buf(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<&[u8]>> {
+        Poll::Ready(Ok(self.get_mut().remaining_slice()))
+    }
+
+    #[inline]
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.get_mut().pos += amt;
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl<const N: usize> futures_io::AsyncWrite for Cursor<Vec<u8, N>> {
+    #[inline]
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Poll::Ready(self.get_mut().write_bytes(buf).map_err(Into::into))
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A writer adaptor, batching small writes into a buffer of `N` bytes.
+///
+/// The buffered bytes are written to the underlying writer once the next write doesn't fit, or on
+/// `flush`. Writes of `N` bytes or more bypass the buffer altogether. This makes a big difference
+/// with writers for which each call is costly (e.g a UART or a flash driver), when writing a byte
+/// at a time or formatting values into them.
+///
+/// Unlike the capacities of the collections, `N` is honored with both backends. The buffered
+/// bytes are **not** written when the `BufWriter` is dropped, so make sure to flush it first.
+///
+/// With the `embedded-io` feature enabled, this implements [`embedded_io::Write`] if the
+/// underlying writer does. With the `std` feature enabled, it implements `std::io::Write` if the
+/// underlying writer does.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "embedded-io")]
+/// # {
+/// use embedded_io::Write;
+/// use mayheap::{io::BufWriter, Vec};
+///
+/// let mut writer = BufWriter::<_, 4>::new(Vec::<u8, 16>::new());
+/// writer.write_all(b"ab").unwrap();
+/// writer.write_all(b"c").unwrap();
+/// // Nothing written to the underlying writer yet.
+/// assert_eq!(writer.buffer(), b"abc");
+/// assert!(writer.get_ref().is_empty());
+///
+/// writer.write_all(b"de").unwrap();
+/// assert_eq!(writer.get_ref(), b"abc");
+/// writer.flush().unwrap();
+/// assert_eq!(writer.get_ref(), b"abcde");
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct BufWriter<W, const N: usize> {
+    inner: W,
+    buf: Vec<u8, N>,
+}
+
+impl<W, const N: usize> BufWriter<W, N> {
+    /// Creates a new `BufWriter` wrapping `inner`.
+    #[inline]
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Returns a reference to the underlying writer.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    ///
+    /// Writing to it directly would reorder the written bytes before the buffered ones.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the buffered bytes, not yet written to the underlying writer.
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Returns the capacity of the buffer, `N`.
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Consumes the `BufWriter`, returning the underlying writer and the buffered bytes, without
+    /// writing them.
+    #[inline]
+    pub fn into_parts(self) -> (W, Vec<u8, N>) {
+        (self.inner, self.buf)
+    }
+
+    // Buffers `buf` if it fits, first writing out the buffered bytes if needed.
+    fn write_buffered<E, F, Z>(&mut self, buf: &[u8], mut write: F, zero: Z) -> Result<usize, E>
+    where
+        F: FnMut(&mut W, &[u8]) -> Result<usize, E>,
+        Z: FnOnce() -> E,
+    {
+        if self.buf.len() + buf.len() > N {
+            self.flush_buf(&mut write, zero)?;
+        }
+        if buf.len() >= N {
+            return write(&mut self.inner, buf);
+        }
+
+        // Only fails on allocation failure with `alloc`, in which case we just don't buffer.
+        match self.buf.extend_from_slice(buf) {
+            Ok(()) => Ok(buf.len()),
+            Err(_) => write(&mut self.inner, buf),
+        }
+    }
+
+    // Writes out all the buffered bytes. On failure, the bytes not yet written are kept.
+    fn flush_buf<E, F, Z>(&mut self, write: &mut F, zero: Z) -> Result<(), E>
+    where
+        F: FnMut(&mut W, &[u8]) -> Result<usize, E>,
+        Z: FnOnce() -> E,
+    {
+        let mut written = 0;
+        let res = loop {
+            if written == self.buf.len() {
+                break Ok(());
+            }
+            match write(&mut self.inner, &self.buf[written..]) {
+                Ok(0) => break Err(zero()),
+                Ok(n) => written += n,
+                Err(e) => break Err(e),
+            }
+        };
+        self.buf.copy_within(written.., 0);
+        self.buf.truncate(self.buf.len() - written);
+
+        res
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<W: embedded_io::ErrorType, const N: usize> embedded_io::ErrorType for BufWriter<W, N> {
+    type Error = W::Error;
+}
+
+#[cfg(feature = "embedded-io")]
+impl<W: embedded_io::Write, const N: usize> embedded_io::Write for BufWriter<W, N> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.write_buffered(buf, W::write, zero_write)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.flush_buf(&mut W::write, zero_write)?;
+
+        self.inner.flush()
+    }
+}
+
+// `embedded_io::Write::write` must not return `Ok(0)`, so there's no error to report in that
+// case. Panic like `embedded_io::Write::write_all` does.
+#[cfg(feature = "embedded-io")]
+fn zero_write<E>() -> E {
+    panic!("write() returned Ok(0)")
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write, const N: usize> std::io::Write for BufWriter<W, N> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.write_buffered(buf, W::write, zero_write_std)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.flush_buf(&mut W::write, zero_write_std)?;
+
+        self.inner.flush()
+    }
+}
+
+#[cfg(feature = "std")]
+fn zero_write_std() -> std::io::Error {
+    std::io::ErrorKind::WriteZero.into()
+}
@@ -12,6 +12,12 @@
 #[cfg(all(not(feature = "alloc"), not(feature = "heapless")))]
 compile_error!("Either the `alloc` or `heapless` feature must be enabled");
 
+#[cfg(all(feature = "unicode-normalization", not(feature = "alloc")))]
+compile_error!("The `unicode-normalization` feature requires the `alloc` feature");
+
+#[cfg(feature = "std")]
+extern crate std;
+
 // Re-exports for the macros.
 #[doc(hidden)]
 pub mod reexports {
@@ -27,14 +33,71 @@ pub mod string;
 pub use string::String;
 
 mod error;
-pub use error::{Error, Result};
+pub use error::{Error, ErrorKind, Result};
+
+pub mod bits;
+
+pub mod convert;
+
+pub mod fallible;
+
+#[cfg(feature = "instrument")]
+pub mod instrument;
+
+#[cfg(feature = "embedded-io")]
+pub mod framed;
+#[cfg(any(feature = "embedded-io", feature = "futures-io", feature = "std"))]
+pub mod io;
+
+#[cfg(feature = "percent-encoding")]
+pub mod percent;
+
+pub mod multimap;
+
+pub mod prelude;
+
+pub mod linked_list;
+
+pub mod sorted_linked_list;
+
+pub mod varint;
+
+#[cfg(feature = "itoa")]
+pub mod num;
+
+mod escape;
 
 #[cfg(any(
-    all(feature = "portable-atomic", feature = "heapless"),
-    feature = "alloc"
+    all(
+        not(alloc_backend),
+        any(feature = "portable-atomic", feature = "critical-section")
+    ),
+    alloc_backend
 ))]
 pub mod boxed;
 
+#[cfg(any(
+    feature = "portable-atomic",
+    feature = "critical-section",
+    all(alloc_backend, target_has_atomic = "ptr")
+))]
+pub mod broadcast;
+
+#[cfg(any(
+    feature = "portable-atomic",
+    feature = "critical-section",
+    all(alloc_backend, target_has_atomic = "ptr")
+))]
+pub mod heap;
+
+// Used by the `broadcast`, `heap` and `instrument` modules.
+#[cfg(any(
+    feature = "portable-atomic",
+    feature = "critical-section",
+    all(target_has_atomic = "ptr", any(alloc_backend, feature = "instrument"))
+))]
+mod lock;
+
 #[cfg(test)]
 mod tests {
     #[cfg(feature = "serde")]
@@ -54,9 +117,1985 @@ mod tests {
 
         // Doesn't fit so should fail with `heapless` but not with `alloc`.
         let res = serde_json::from_str::<crate::Vec<u8, 1>>(&json);
-        #[cfg(not(feature = "alloc"))]
+        #[cfg(not(alloc_backend))]
+        {
+            use core::fmt::Write;
+
+            let mut msg = crate::String::<128>::new();
+            write!(msg, "{}", res.unwrap_err()).unwrap();
+            assert_eq!(
+                msg,
+                "invalid length 5, expected a sequence of at most 1 elements at line 1 column 11"
+            );
+        }
+        #[cfg(alloc_backend)]
+        res.unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_seed() {
+        use crate::{string::StringSeed, vec::VecSeed};
+        use serde_core::de::DeserializeSeed;
+
+        let mut v = crate::Vec::<u8, 3>::from_slice(&[9, 9]).unwrap();
+        let mut de = serde_json::Deserializer::from_str("[1, 2]");
+        VecSeed::new(&mut v).deserialize(&mut de).unwrap();
+        assert_eq!(v, [1, 2]);
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let mut de = serde_json::Deserializer::from_str("[1, 2, 3, 4, 5]");
+        let res = VecSeed::new(&mut v).deserialize(&mut de);
+        #[cfg(not(alloc_backend))]
+        {
+            use core::fmt::Write;
+
+            let mut msg = crate::String::<128>::new();
+            write!(msg, "{}", res.unwrap_err()).unwrap();
+            assert_eq!(
+                msg,
+                "invalid length 5, expected a sequence of at most 3 elements at line 1 column 15"
+            );
+        }
+        #[cfg(alloc_backend)]
+        {
+            res.unwrap();
+            assert_eq!(v, [1, 2, 3, 4, 5]);
+        }
+
+        let mut s = crate::String::<5>::try_from("old").unwrap();
+        let mut de = serde_json::Deserializer::from_str(r#""new""#);
+        StringSeed::new(&mut s).deserialize(&mut de).unwrap();
+        assert_eq!(s, "new");
+        let mut de = serde_json::Deserializer::from_str("42");
+        StringSeed::new(&mut s).deserialize(&mut de).unwrap_err();
+        assert_eq!(s, "");
+        s.push_str("new").unwrap();
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let mut de = serde_json::Deserializer::from_str(r#""too long""#);
+        let res = StringSeed::new(&mut s).deserialize(&mut de);
+        #[cfg(not(alloc_backend))]
+        {
+            res.unwrap_err();
+            assert_eq!(s, "");
+        }
+        #[cfg(alloc_backend)]
+        {
+            res.unwrap();
+            assert_eq!(s, "too long");
+        }
+    }
+
+    #[cfg(all(
+        feature = "serde",
+        any(
+            all(
+                not(alloc_backend),
+                any(feature = "portable-atomic", feature = "critical-section")
+            ),
+            alloc_backend
+        )
+    ))]
+    #[test]
+    fn serde_box() {
+        crate::box_pool!(SerdePool: crate::String<8>, 1);
+
+        let boxed: crate::boxed::Box<SerdePool> = serde_json::from_str(r#""hello""#).unwrap();
+        assert_eq!(*boxed, "hello");
+        let json = serde_json::to_string(&boxed).unwrap();
+        assert_eq!(json, r#""hello""#);
+
+        // Pool is exhausted so should fail with `heapless` but not with `alloc`.
+        let res = serde_json::from_str::<crate::boxed::Box<SerdePool>>(&json);
+        #[cfg(not(alloc_backend))]
+        res.unwrap_err();
+        #[cfg(alloc_backend)]
+        res.unwrap();
+    }
+
+    #[cfg(any(
+        all(
+            not(alloc_backend),
+            any(feature = "portable-atomic", feature = "critical-section")
+        ),
+        alloc_backend
+    ))]
+    #[test]
+    fn alloc_uninit() {
+        use crate::boxed::BoxPool;
+
+        crate::box_pool!(UninitPool: crate::String<8>, 1);
+
+        // Dropping an uninitialized block frees it, without dropping a value.
+        let block = UninitPool.alloc_uninit().unwrap();
+        // Pool is exhausted so should fail with `heapless` but not with `alloc`.
+        let res = UninitPool.alloc_uninit();
+        #[cfg(not(alloc_backend))]
+        assert_eq!(res.unwrap_err(), crate::Error::PoolExhausted);
+        #[cfg(alloc_backend)]
+        drop(res.unwrap());
+        drop(block);
+
+        let mut block = UninitPool.alloc_uninit().unwrap();
+        core::mem::MaybeUninit::write(&mut *block, crate::String::try_from("uninit").unwrap());
+        // SAFETY: We just initialized it.
+        let boxed = unsafe { block.assume_init() };
+        assert_eq!(*boxed, "uninit");
+    }
+
+    #[cfg(any(
+        all(
+            not(alloc_backend),
+            any(feature = "portable-atomic", feature = "critical-section")
+        ),
+        alloc_backend
+    ))]
+    #[test]
+    fn size_class_pool() {
+        crate::size_class_pool!(SizedPool: {
+            4 => 1,
+            #[storage(cfg_attr(target_os = "linux", unsafe(link_section = ".data.mayheap")))]
+            8 => 1,
+        });
+
+        let mut small = SizedPool.alloc_bytes(&[1, 2]).unwrap();
+        small[0] = 3;
+        assert_eq!(&*small, [3, 2]);
+        assert_eq!(SizedPool::CLASSES, [4, 8]);
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let res = SizedPool.alloc_bytes(&[0; 9]);
+        #[cfg(not(alloc_backend))]
+        assert_eq!(res.unwrap_err().kind(), crate::ErrorKind::BufferOverflow);
+        #[cfg(alloc_backend)]
+        assert_eq!(res.unwrap().len(), 9);
+    }
+
+    #[cfg(any(
+        all(
+            not(alloc_backend),
+            any(feature = "portable-atomic", feature = "critical-section")
+        ),
+        alloc_backend
+    ))]
+    #[test]
+    fn box_pool_storage() {
+        use crate::boxed::BoxPool;
+
+        crate::box_pool!(
+            #[storage(cfg_attr(target_os = "linux", unsafe(link_section = ".data.mayheap")))]
+            /// Not a storage attribute.
+            DmaPool: [u8; 4],
+            1
+        );
+
+        let buf = DmaPool.alloc([1, 2, 3, 4]).unwrap();
+        assert_eq!(*buf, [1, 2, 3, 4]);
+
+        crate::bytes_pool!(
+            #[storage(cfg_attr(target_os = "linux", unsafe(link_section = ".data.mayheap")))]
+            #[cfg(test)]
+            DmaBytesPool: 4,
+            1
+        );
+        let buf = DmaBytesPool.alloc_bytes(&[1, 2]).unwrap();
+        assert_eq!(**buf, [1, 2]);
+    }
+
+    #[cfg(any(
+        all(
+            not(alloc_backend),
+            any(feature = "portable-atomic", feature = "critical-section")
+        ),
+        alloc_backend
+    ))]
+    #[test]
+    fn box_traits() {
+        use crate::boxed::BoxPool;
+        use core::fmt::Write;
+
+        crate::box_pool!(OrdPool: u32, 2);
+
+        let a = OrdPool.alloc(1).unwrap();
+        let b = OrdPool.alloc(2).unwrap();
+        assert!(a < b);
+        assert_eq!(a.as_ref(), &1);
+
+        let mut s = crate::String::<8>::new();
+        write!(s, "{a}{b}").unwrap();
+        assert_eq!(s, "12");
+    }
+
+    #[cfg(any(
+        all(
+            not(alloc_backend),
+            any(feature = "portable-atomic", feature = "critical-section")
+        ),
+        alloc_backend
+    ))]
+    #[test]
+    fn atomic_slot() {
+        extern crate std;
+
+        use crate::boxed::{AtomicSlot, BoxPool};
+
+        crate::box_pool!(SlotPool: u32, 3);
+
+        let slot = AtomicSlot::new(SlotPool.alloc(0).unwrap());
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for i in 1..=100 {
+                    let old = slot.swap(Some(SlotPool.alloc(i).unwrap())).unwrap();
+                    assert_eq!(*old, i - 1);
+                }
+            });
+            s.spawn(|| {
+                let mut last = 0;
+                while last != 100 {
+                    let current = slot.read(|value| *value.unwrap());
+                    assert!(current >= last);
+                    last = current;
+                }
+            });
+        });
+
+        assert_eq!(slot.take().as_deref(), Some(&100));
+        assert!(slot.is_empty());
+        assert!(slot.into_inner().is_none());
+    }
+
+    #[cfg(any(
+        all(
+            not(alloc_backend),
+            any(feature = "portable-atomic", feature = "critical-section")
+        ),
+        alloc_backend
+    ))]
+    #[test]
+    fn alloc_async() {
+        extern crate std;
+
+        use crate::boxed::BoxPool;
+        use core::{
+            future::Future,
+            pin::pin,
+            task::{Context, Poll},
+        };
+        use std::{
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Arc,
+            },
+            task::Wake,
+        };
+
+        struct Woken(AtomicUsize);
+        impl Wake for Woken {
+            fn wake(self: Arc<Self>) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        crate::box_pool!(AsyncPool: u32, 1);
+
+        let woken = Arc::new(Woken(AtomicUsize::new(0)));
+        let waker = woken.clone().into();
+        let mut cx = Context::from_waker(&waker);
+
+        let boxed = AsyncPool.alloc(1).unwrap();
+        let mut fut = pin!(AsyncPool.alloc_async(2));
+        // Pool is exhausted so should wait with `heapless` but not with `alloc`.
+        #[cfg(not(alloc_backend))]
+        let boxed = {
+            let other_woken = Arc::new(Woken(AtomicUsize::new(0)));
+            let other_waker = other_woken.clone().into();
+            let mut other_cx = Context::from_waker(&other_waker);
+            let mut other = pin!(AsyncPool.alloc_async(3));
+
+            // Both tasks wait without waking each other up.
+            assert!(fut.as_mut().poll(&mut cx).is_pending());
+            assert!(other.as_mut().poll(&mut other_cx).is_pending());
+            assert!(fut.as_mut().poll(&mut cx).is_pending());
+            assert_eq!(woken.0.load(Ordering::SeqCst), 0);
+            assert_eq!(other_woken.0.load(Ordering::SeqCst), 0);
+
+            // Both are woken up, and the first one polled gets the block.
+            drop(boxed);
+            assert_eq!(woken.0.load(Ordering::SeqCst), 1);
+            assert_eq!(other_woken.0.load(Ordering::SeqCst), 1);
+            let Poll::Ready(boxed) = other.as_mut().poll(&mut other_cx) else {
+                panic!("allocation should have succeeded");
+            };
+            assert_eq!(*boxed, 3);
+            assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+            boxed
+        };
+        drop(boxed);
+        let Poll::Ready(boxed) = fut.as_mut().poll(&mut cx) else {
+            panic!("allocation should have succeeded");
+        };
+        assert_eq!(*boxed, 2);
+    }
+
+    #[cfg(any(
+        feature = "portable-atomic",
+        feature = "critical-section",
+        all(alloc_backend, target_has_atomic = "ptr")
+    ))]
+    #[test]
+    fn broadcast() {
+        extern crate std;
+
+        use crate::broadcast::{Channel, Lagged, TryRecvError};
+        use core::{
+            future::Future,
+            pin::pin,
+            task::{Context, Poll},
+        };
+        use std::{
+            sync::{
+                atomic::{AtomicBool, Ordering},
+                Arc,
+            },
+            task::Wake,
+        };
+
+        struct Woken(AtomicBool);
+        impl Wake for Woken {
+            fn wake(self: Arc<Self>) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let channel = Channel::<u8, 2, 2>::new();
+        // Nothing to receive before subscribing.
+        channel.send(0);
+        let mut a = channel.subscribe().unwrap();
+        let b = channel.subscribe().unwrap();
+        channel.subscribe().unwrap_err();
+        drop(b);
+        let mut b = channel.subscribe().unwrap();
+        assert_eq!(a.try_recv(), Err(TryRecvError::Empty));
+
+        for v in 1..=5 {
+            channel.send(v);
+        }
+        assert_eq!(a.try_recv(), Err(TryRecvError::Lagged(3)));
+        assert_eq!(a.try_recv(), Ok(4));
+        assert_eq!(a.try_recv(), Ok(5));
+        assert_eq!(a.try_recv(), Err(TryRecvError::Empty));
+
+        let woken = Arc::new(Woken(AtomicBool::new(false)));
+        let waker = woken.clone().into();
+        let mut cx = Context::from_waker(&waker);
+
+        let mut fut = pin!(a.recv());
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+        channel.send(6);
+        assert!(woken.0.load(Ordering::SeqCst));
+        assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(Ok(6)));
+
+        let fut = pin!(b.recv());
+        assert_eq!(fut.poll(&mut cx), Poll::Ready(Err(Lagged(4))));
+        assert_eq!(b.try_recv(), Ok(5));
+        assert_eq!(b.try_recv(), Ok(6));
+
+        #[cfg(feature = "futures-core")]
+        {
+            use futures_core::Stream;
+
+            woken.0.store(false, Ordering::SeqCst);
+            let mut a = pin!(a);
+            assert!(a.as_mut().poll_next(&mut cx).is_pending());
+            channel.send(7);
+            assert!(woken.0.load(Ordering::SeqCst));
+            assert_eq!(a.as_mut().poll_next(&mut cx), Poll::Ready(Some(Ok(7))));
+            for v in 8..=10 {
+                channel.send(v);
+            }
+            assert_eq!(
+                a.as_mut().poll_next(&mut cx),
+                Poll::Ready(Some(Err(Lagged(1))))
+            );
+            assert_eq!(a.poll_next(&mut cx), Poll::Ready(Some(Ok(9))));
+        }
+    }
+
+    #[cfg(any(
+        feature = "portable-atomic",
+        feature = "critical-section",
+        all(alloc_backend, target_has_atomic = "ptr")
+    ))]
+    #[test]
+    fn heap() {
+        use crate::heap::Heap;
+        use core::alloc::{GlobalAlloc, Layout};
+
+        let unit = 2 * core::mem::size_of::<usize>();
+        let heap = Heap::<256>::new();
+        assert_eq!(heap.free(), 256);
+
+        unsafe {
+            let small = Layout::from_size_align(1, 1).unwrap();
+            let a = heap.alloc(small);
+            let b = heap.alloc(small);
+            assert!(!a.is_null() && !b.is_null());
+            assert_eq!(heap.used(), 2 * unit);
+            // Over-aligned blocks leave a gap that's reused.
+            let aligned = Layout::from_size_align(8, 64).unwrap();
+            let c = heap.alloc(aligned);
+            assert_eq!(c as usize % 64, 0);
+
+            // Doesn't fit at all.
+            assert!(heap
+                .alloc(Layout::from_size_align(256, 1).unwrap())
+                .is_null());
+
+            heap.dealloc(a, small);
+            // The freed block is reused first.
+            assert_eq!(heap.alloc(small), a);
+            heap.dealloc(a, small);
+            heap.dealloc(c, aligned);
+            heap.dealloc(b, small);
+            assert_eq!(heap.used(), 0);
+
+            // Everything was merged back into a single block.
+            let all = Layout::from_size_align(256, unit).unwrap();
+            let d = heap.alloc(all);
+            assert!(!d.is_null());
+            assert_eq!(heap.free(), 0);
+            heap.dealloc(d, all);
+        }
+    }
+
+    #[cfg(any(
+        all(
+            not(alloc_backend),
+            any(feature = "portable-atomic", feature = "critical-section")
+        ),
+        alloc_backend
+    ))]
+    #[test]
+    fn pool_reset() {
+        use crate::boxed::BoxPool;
+
+        crate::box_pool!(ResetPool: u32, 2);
+
+        let boxed = ResetPool.alloc(1).unwrap();
+        core::mem::forget(ResetPool.alloc(2).unwrap());
+        drop(boxed);
+        core::mem::forget(ResetPool.alloc(3).unwrap());
+        // Leaked all blocks so should fail with `heapless` but not with `alloc`.
+        let res = ResetPool.alloc(4);
+        #[cfg(not(alloc_backend))]
         res.unwrap_err();
+        #[cfg(alloc_backend)]
+        res.unwrap();
+
+        // SAFETY: The leaked boxes are never used again.
+        unsafe { ResetPool.reset() };
+        let boxes = ResetPool.alloc_iter::<_, 2>([5, 6]).unwrap();
+        assert_eq!(*boxes[1], 6);
+    }
+
+    #[test]
+    fn from_ascii() {
+        let s = crate::String::<8>::from_ascii(b"AT+OK").unwrap();
+        assert_eq!(s, "AT+OK");
+
+        let err = crate::String::<8>::from_ascii(b"AT\xc3\xa9").unwrap_err();
+        assert_eq!(err, crate::Error::NonAscii { valid_up_to: 2 });
+    }
+
+    #[test]
+    fn try_reserve() {
+        let mut v = crate::Vec::<u8, 4>::new();
+        v.try_reserve(4).unwrap();
+        let mut s = crate::String::<4>::new();
+        s.try_reserve(4).unwrap();
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let res = v.try_reserve(5);
+        #[cfg(not(alloc_backend))]
+        assert_eq!(res.unwrap_err().kind(), crate::ErrorKind::BufferOverflow);
+        #[cfg(alloc_backend)]
+        res.unwrap();
+
+        // Too much for any allocator.
+        let res = s.try_reserve(usize::MAX);
+        #[cfg(not(alloc_backend))]
+        assert_eq!(res.unwrap_err().kind(), crate::ErrorKind::BufferOverflow);
+        #[cfg(alloc_backend)]
+        assert_eq!(res.unwrap_err(), crate::Error::OutOfMemory);
+    }
+
+    #[test]
+    fn vec_try_extend() {
+        let mut v = crate::Vec::<u8, 4>::try_from_iter([1, 2]).unwrap();
+        v.try_extend([3, 4]).unwrap();
+        assert_eq!(v, [1, 2, 3, 4]);
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let res = v.try_extend([5, 6]);
+        #[cfg(not(alloc_backend))]
+        {
+            assert_eq!(
+                res.unwrap_err(),
+                crate::Error::overflow::<crate::Vec<u8, 4>>(4, 1, 4)
+            );
+            assert_eq!(v, [1, 2, 3, 4]);
+        }
+        #[cfg(alloc_backend)]
+        {
+            res.unwrap();
+            assert_eq!(v, [1, 2, 3, 4, 5, 6]);
+        }
+    }
+
+    #[cfg(not(feature = "panic-free"))]
+    #[test]
+    fn drain() {
+        let mut v = crate::Vec::<u8, 8>::from_array([1, 2, 3, 4]);
+        let drained: crate::Vec<_, 2> = v.drain(1..3).rev().collect();
+        assert_eq!(drained, [3, 2]);
+        assert_eq!(v, [1, 4]);
+
+        let spare = v.spare_capacity_mut();
+        assert!(spare.len() >= 6);
+        spare[0].write(5);
+        // SAFETY: We just initialized the element.
+        unsafe { v.set_len(3) };
+        assert_eq!(v, [1, 4, 5]);
+        let mut cloned = v.clone();
+        assert!(cloned.capacity() >= 8);
+        assert!(cloned.spare_capacity_mut().len() >= 5);
+        // Conversions from `alloc` vectors keep the capacity they had.
+        #[cfg(alloc_backend)]
+        {
+            let mut v = crate::Vec::<u8, 8>::from(crate::reexports::alloc::vec![1, 4, 5]);
+            assert!(v.spare_capacity_mut().len() >= 5);
+        }
+
+        let mut s = crate::String::<16>::try_from("a€b€c").unwrap();
+        let mut drain = s.drain(1..8);
+        assert_eq!(drain.next(), Some('€'));
+        assert_eq!(drain.as_str(), "b€");
+        drop(drain);
+        assert_eq!(s, "ac");
+    }
+
+    #[test]
+    fn into_chars() {
+        let s = crate::String::<16>::try_from("a€b€c").unwrap();
+        let mut chars = s.clone().into_iter();
+        assert_eq!(chars.size_hint(), (3, Some(9)));
+        assert_eq!(chars.next(), Some('a'));
+        assert_eq!(chars.next_back(), Some('c'));
+        assert_eq!(chars.next_back(), Some('€'));
+        assert_eq!(chars.as_str(), "€b");
+        assert_eq!(chars.next(), Some('€'));
+        assert_eq!(chars.next(), Some('b'));
+        assert_eq!(chars.next(), None);
+        assert_eq!(chars.next_back(), None);
+
+        let reversed = crate::String::<16>::try_from_iter(s.into_iter().rev()).unwrap();
+        assert_eq!(reversed, "c€b€a");
+    }
+
+    #[test]
+    fn try_map() {
+        #[derive(Debug, PartialEq)]
+        enum Error {
+            Mayheap(crate::Error),
+            Zero,
+        }
+        impl From<crate::Error> for Error {
+            fn from(e: crate::Error) -> Self {
+                Error::Mayheap(e)
+            }
+        }
+        let parse = |x: u8| core::num::NonZeroU8::new(x).ok_or(Error::Zero);
+
+        let v = crate::Vec::<u8, 4>::from_array([1, 2, 3, 4]);
+        let parsed: crate::Vec<_, 4> = v.clone().try_map(parse).unwrap();
+        let mapped = parsed.map(u8::from);
+        assert_eq!(mapped, v);
+        assert!(mapped.capacity() >= 4);
+        let v = crate::Vec::<u8, 4>::from_array([1, 0, 3, 4]);
+        assert_eq!(
+            v.clone().try_map::<_, _, 4>(parse).unwrap_err(),
+            Error::Zero
+        );
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let res = v.try_map::<_, _, 2>(|x| Ok::<_, crate::Error>(x + 1));
+        #[cfg(not(alloc_backend))]
+        assert_eq!(res.unwrap_err().kind(), crate::ErrorKind::BufferOverflow);
+        #[cfg(alloc_backend)]
+        assert_eq!(res.unwrap(), [2, 1, 4, 5]);
+    }
+
+    #[test]
+    fn split_to() {
+        let mut v = crate::Vec::<u8, 4>::from_slice(&[1, 2, 3, 4]).unwrap();
+        assert!(v.split_to(0).unwrap().is_empty());
+        assert_eq!(v.split_to(1).unwrap(), [1]);
+        assert_eq!(v, [2, 3, 4]);
+        assert_eq!(v.split_to(3).unwrap(), [2, 3, 4]);
+        assert!(v.is_empty());
+        v.push(5).unwrap();
+        assert_eq!(v, [5]);
+        assert_eq!(
+            v.split_to(2).unwrap_err(),
+            crate::Error::OutOfBounds { index: 2, len: 1 }
+        );
+        assert_eq!(v, [5]);
+    }
+
+    #[test]
+    fn display() {
+        use core::fmt::Write;
+
+        let mut v = crate::Vec::<u8, 4>::new();
+        let mut s = crate::String::<32>::new();
+        write!(s, "{}", v.display(", ")).unwrap();
+        assert_eq!(s, "");
+
+        v.extend_from_slice(&[1, 20, 3]).unwrap();
+        write!(s, "{:>3}", v.display("|")).unwrap();
+        assert_eq!(s, "  1| 20|  3");
+    }
+
+    #[test]
+    fn zero_sized() {
+        // Zero-sized elements only take up capacity with `heapless`.
+        let mut v = crate::Vec::<(), 3>::new();
+        assert!(v.capacity() >= 3);
+        for _ in 0..3 {
+            v.push(()).unwrap();
+        }
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        #[cfg(not(alloc_backend))]
+        {
+            assert!(v.is_full());
+            v.push(()).unwrap_err();
+            v.try_reserve(1).unwrap_err();
+        }
+        #[cfg(alloc_backend)]
+        {
+            assert!(!v.is_full());
+            v.push(()).unwrap();
+            v.pop().unwrap();
+        }
+        assert_eq!(v.len(), 3);
+        let mut iter = v.clone().into_iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next_back(), Some(()));
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+        assert_eq!(iter.count(), 2);
+        let v2: crate::Vec<(), 4> = v.clone().recapacity().unwrap();
+        assert_eq!(v2.len(), 3);
+        v.truncate(1);
+        assert_eq!(v.into_array::<1>(), Ok([()]));
+
+        // No capacity at all.
+        let mut v = crate::Vec::<u8, 0>::new();
+        assert!(v.is_empty());
+        assert_eq!(v.capacity(), 0);
+        assert!(v.is_full());
+        assert!(crate::Vec::<u8, 0>::fits(0));
+        assert!(!crate::Vec::<u8, 0>::fits(1));
+        v.extend_from_slice(&[]).unwrap();
+        assert_eq!(v.into_iter().len(), 0);
+        let mut v = crate::Vec::<u8, 0>::new();
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        #[cfg(not(alloc_backend))]
+        v.push(1).unwrap_err();
+        #[cfg(alloc_backend)]
+        v.push(1).unwrap();
+
+        let mut s = crate::String::<0>::new();
+        assert_eq!(s.capacity(), 0);
+        s.push_str("").unwrap();
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        #[cfg(not(alloc_backend))]
+        s.push('a').unwrap_err();
+        #[cfg(alloc_backend)]
+        s.push('a').unwrap();
+    }
+
+    #[test]
+    fn ord() {
+        use core::cmp::Ordering;
+
+        let v = crate::Vec::<u8, 4>::from_slice(&[1, 2, 3]).unwrap();
+        let mut other = [1, 2, 4];
+        assert!(v < other);
+        assert!(v < other[..]);
+        assert!(other > v);
+        assert!(other[..] > v);
+        let less = Some(Ordering::Less);
+        assert_eq!(v.partial_cmp(&&other), less);
+        assert_eq!(v.partial_cmp(&&other[..]), less);
+        assert_eq!(v.partial_cmp(&&mut other[..]), less);
+        let greater = Some(Ordering::Greater);
+        assert_eq!(PartialOrd::partial_cmp(&&other, &v), greater);
+        assert_eq!(PartialOrd::partial_cmp(&&other[..], &v), greater);
+        assert_eq!(PartialOrd::partial_cmp(&&mut other[..], &v), greater);
+        assert!(v > [1, 2]);
+        assert_eq!(v.partial_cmp(&[1, 2, 3]), Some(Ordering::Equal));
+
+        let w = crate::Vec::<u8, 8>::from_slice(&[1, 2, 3, 0]).unwrap();
+        assert!(v < w);
+        assert_eq!(v.cmp_slice(&w), Ordering::Less);
+        assert_eq!(w.cmp_slice(&v), Ordering::Greater);
+        assert_eq!(v.cmp_slice(&[1, 2, 3]), Ordering::Equal);
+    }
+
+    #[test]
+    fn from_elem() {
+        let v = crate::Vec::<u8, 4>::from_elem(0, 4).unwrap();
+        assert_eq!(v, [0; 4]);
+        assert!(crate::Vec::<u8, 4>::from_elem(1, 0).unwrap().is_empty());
+
+        let v = crate::Vec::<crate::String<4>, 2>::from_elem("ab".try_into().unwrap(), 2).unwrap();
+        assert_eq!(v, ["ab", "ab"]);
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let res = crate::Vec::<u8, 4>::from_elem(7, 5);
+        #[cfg(not(alloc_backend))]
+        res.unwrap_err();
+        #[cfg(alloc_backend)]
+        assert_eq!(res.unwrap(), [7; 5]);
+    }
+
+    #[test]
+    fn sorted_linked_list() {
+        use crate::sorted_linked_list::{Max, Min, SortedLinkedList};
+
+        let mut list = SortedLinkedList::<u8, Min, 4>::new();
+        assert!(list.is_empty());
+        assert_eq!(list.pop(), None);
+        assert!(list.find_mut(|_| true).is_none());
+        for v in [3, 1, 4, 1] {
+            list.push(v).unwrap();
+        }
+        assert!(list.iter().eq(&[1, 1, 3, 4]));
+
+        // Unchanged values keep their position.
+        list.find_mut(|v| *v == 3).unwrap().finish();
+        assert!(list.iter().eq(&[1, 1, 3, 4]));
+        *list.find_mut(|v| *v == 1).unwrap() = 5;
+        assert!(list.iter().eq(&[1, 3, 4, 5]));
+        assert_eq!(list.find_mut(|v| *v == 4).unwrap().pop(), 4);
+        assert!(list.iter().eq(&[1, 3, 5]));
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        list.push(2).unwrap();
+        #[cfg(not(alloc_backend))]
+        {
+            assert!(list.is_full());
+            assert_eq!(list.push(0), Err(0));
+        }
+        #[cfg(alloc_backend)]
+        {
+            assert!(!list.is_full());
+            assert_eq!(list.push(0), Ok(()));
+            assert_eq!(list.pop(), Some(0));
+        }
+        assert_eq!(list.peek(), Some(&1));
+
+        let mut list = SortedLinkedList::<u8, Max, 0>::default();
+        #[cfg(not(alloc_backend))]
+        list.push(1).unwrap_err();
+        #[cfg(alloc_backend)]
+        list.push(1).unwrap();
+    }
+
+    #[test]
+    fn linked_list() {
+        let mut list = crate::linked_list::LinkedList::<u8, 4>::default();
+        assert_eq!(list.pop(), None);
+        assert_eq!(list.remove(|_| true), None);
+        for v in [3, 1, 4, 1] {
+            list.push(v).unwrap();
+        }
+        assert!(list.iter().eq(&[3, 1, 4, 1]));
+        assert_eq!(list.remove(|v| *v == 1), Some(1));
+        assert!(list.iter().eq(&[3, 4, 1]));
+        for v in &mut list {
+            *v += 1;
+        }
+        assert!(list.iter().rev().eq(&[2, 5, 4]));
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        list.push(6).unwrap();
+        #[cfg(not(alloc_backend))]
+        {
+            assert!(list.is_full());
+            assert_eq!(list.push(0), Err(0));
+        }
+        #[cfg(alloc_backend)]
+        {
+            assert!(!list.is_full());
+            assert_eq!(list.push(0), Ok(()));
+            assert_eq!(list.remove(|v| *v == 0), Some(0));
+        }
+        assert_eq!(list.pop(), Some(4));
+        assert_eq!(list.peek(), Some(&5));
+        assert_eq!(list.len(), 3);
+        list.clear();
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn multimap() {
+        let mut map = crate::multimap::MultiMap::<u8, char, 4>::new();
+        assert!(map.is_empty());
+        assert_eq!(map.get(&1), None);
+        assert_eq!(map.remove_all(&1), 0);
+        map.insert(2, 'a').unwrap();
+        map.insert(1, 'b').unwrap();
+        map.insert(2, 'c').unwrap();
+        map.insert(3, 'd').unwrap();
+        assert!(map.iter().eq(&[(1, 'b'), (2, 'a'), (2, 'c'), (3, 'd')]));
+        assert!(map.contains_key(&2));
+        assert!(!map.contains_key(&4));
+        assert_eq!(map.get(&2), Some(&'a'));
+        assert!(map.get_all(&2).rev().eq(&['c', 'a']));
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let res = map.insert(0, 'e');
+        #[cfg(not(alloc_backend))]
+        {
+            assert!(map.is_full());
+            assert_eq!(res, Err((0, 'e')));
+        }
+        #[cfg(alloc_backend)]
+        {
+            assert_eq!(res, Ok(()));
+            assert_eq!(map.remove_all(&0), 1);
+        }
+
+        for value in map.get_all_mut(&2) {
+            *value = value.to_ascii_uppercase();
+        }
+        let mut groups = map.groups();
+        let (key, values) = groups.next().unwrap();
+        assert_eq!((*key, values.len()), (1, 1));
+        let (key, values) = groups.next().unwrap();
+        assert_eq!(*key, 2);
+        assert!(values.eq(&['A', 'C']));
+        assert_eq!(groups.next().unwrap().0, &3);
+        assert!(groups.next().is_none());
+
+        assert_eq!(map.remove_all(&2), 2);
+        assert!(map.iter().eq(&[(1, 'b'), (3, 'd')]));
+        map.retain(|key, _| *key > 1);
+        assert!(map.iter().eq(&[(3, 'd')]));
+        map.clear();
+        assert!(map.groups().next().is_none());
+    }
+
+    #[test]
+    fn retain_unordered() {
+        let mut v = crate::Vec::<u8, 8>::from_slice(&[1, 2, 3, 4, 5, 6]).unwrap();
+        let mut visited = 0;
+        v.retain_unordered(|x| {
+            visited += 1;
+            x % 2 == 0 && *x != 6
+        });
+        assert_eq!(visited, 6);
+        v.sort_unstable();
+        assert_eq!(v, [2, 4]);
+    }
+
+    #[test]
+    fn sorted_set_ops() {
+        let mut a = crate::Vec::<u8, 8>::from_slice(&[1, 1, 3, 5, 5, 7]).unwrap();
+        a.dedup_sorted();
+        assert_eq!(a, [1, 3, 5, 7]);
+        let b = [2, 3, 7, 8];
+
+        let union: crate::Vec<u8, 8> = a.union_sorted(&b).unwrap();
+        assert_eq!(union, [1, 2, 3, 5, 7, 8]);
+        let intersection: crate::Vec<u8, 2> = a.intersect_sorted(&b).unwrap();
+        assert_eq!(intersection, [3, 7]);
+        let difference: crate::Vec<u8, 2> = a.difference_sorted(&b).unwrap();
+        assert_eq!(difference, [1, 5]);
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let res = a.union_sorted::<4>(&b);
+        #[cfg(not(alloc_backend))]
+        assert_eq!(res.unwrap_err().kind(), crate::ErrorKind::BufferOverflow);
+        #[cfg(alloc_backend)]
+        assert_eq!(res.unwrap(), union);
+    }
+
+    #[test]
+    fn sort_stable() {
+        // Pairs of a key with many duplicates and the original position.
+        let mut v = crate::Vec::<(u8, u8), 100>::new();
+        for i in 0..100u8 {
+            v.push((i.wrapping_mul(37) % 7, i)).unwrap();
+        }
+        v.sort_stable_by_key(|&(key, _)| key);
+        assert!(v.windows(2).all(|w| w[0] < w[1]));
+
+        let mut v = crate::Vec::<u8, 4>::from_slice(&[3, 1, 2]).unwrap();
+        v.sort_stable();
+        assert_eq!(v, [1, 2, 3]);
+        v.sort_stable_by(|a, b| b.cmp(a));
+        assert_eq!(v, [3, 2, 1]);
+    }
+
+    #[test]
+    fn get_many_mut() {
+        let mut v = crate::Vec::<u8, 4>::from_array([1, 2, 3, 4]);
+        let [a, b] = v.get_many_mut([3, 0]).unwrap();
+        core::mem::swap(a, b);
+        assert_eq!(v, [4, 2, 3, 1]);
+        assert!(v.get_many_mut([1, 1]).is_none());
+        assert!(v.get_many_mut([0, 4]).is_none());
+    }
+
+    #[test]
+    fn convert() {
+        let mut v = crate::Vec::<u8, 4>::from_slice(&[1, 2]).unwrap();
+        #[cfg(not(alloc_backend))]
+        {
+            use crate::convert::AsHeapless;
+
+            v.as_heapless_mut().push(3).unwrap();
+            let mut h: heapless::Vec<u8, 4> = v.into_heapless();
+            crate::Vec::from_heapless_mut(&mut h).truncate(2);
+            assert_eq!(*crate::Vec::from_heapless_ref(&h), [1, 2]);
+            h.push(3).unwrap();
+            v = crate::Vec::from_heapless(h);
+
+            let mut h = heapless::String::<4>::try_from("ab").unwrap();
+            crate::String::from_heapless_mut(&mut h).push('c').unwrap();
+            assert_eq!(crate::String::from_heapless_ref(&h), "abc");
+        }
+        #[cfg(alloc_backend)]
+        {
+            use crate::convert::AsAlloc;
+
+            v.as_alloc_mut().push(3);
+            let mut a: crate::reexports::alloc::vec::Vec<u8> = v.into_alloc();
+            crate::Vec::<u8, 4>::from_alloc_mut(&mut a).truncate(2);
+            assert_eq!(*crate::Vec::<u8, 4>::from_alloc_ref(&a), [1, 2]);
+            a.push(3);
+            v = crate::Vec::from_alloc(a);
+
+            let mut a = crate::reexports::alloc::string::String::from("ab");
+            crate::String::<4>::from_alloc_mut(&mut a)
+                .push('c')
+                .unwrap();
+            assert_eq!(crate::String::<4>::from_alloc_ref(&a), "abc");
+        }
+        assert_eq!(v, [1, 2, 3]);
+
+        #[cfg(feature = "alloc")]
+        {
+            use crate::convert::TryIntoStd;
+
+            assert_eq!(v.try_into_std().unwrap(), [1, 2, 3]);
+            let s = crate::String::<4>::try_from("abc").unwrap();
+            let std = crate::reexports::alloc::string::String::from("abc");
+            assert_eq!(s, std);
+            assert_eq!(std, s);
+            let cow = crate::reexports::alloc::borrow::Cow::Borrowed("abc");
+            assert_eq!(s, cow);
+            assert_eq!(cow, s);
+            assert_eq!(s.try_into_std().unwrap(), "abc");
+
+            let a = crate::reexports::alloc::string::String::from("abcde");
+            // Doesn't fit so should fail with `heapless` but not with `alloc`.
+            #[cfg(not(alloc_backend))]
+            assert_eq!(
+                crate::String::<4>::try_from(a).unwrap_err().kind(),
+                crate::ErrorKind::BufferOverflow
+            );
+            #[cfg(alloc_backend)]
+            assert_eq!(crate::String::<4>::from(a), "abcde");
+        }
+    }
+
+    #[test]
+    fn fallible() {
+        use crate::fallible::{FallibleString, FallibleVec};
+
+        fn fill<V: FallibleVec<u8>, S: FallibleString>(v: &mut V, s: &mut S) -> crate::Result<()> {
+            v.clear();
+            s.clear();
+            v.try_push(b'a').unwrap();
+            v.try_extend_from_slice(b"bcd")?;
+            s.try_push('a')?;
+            s.try_push_str("bcd")
+        }
+
+        let (mut v, mut s) = (crate::Vec::<u8, 4>::new(), crate::String::<4>::new());
+        fill(&mut v, &mut s).unwrap();
+        assert_eq!((&v[..], &s[..]), (&b"abcd"[..], "abcd"));
+        fill(&mut v, &mut s).unwrap();
+        assert_eq!(FallibleVec::pop(&mut v), Some(b'd'));
+        FallibleString::try_truncate(&mut s, 1).unwrap();
+        assert_eq!((&v[..], &s[..]), (&b"abc"[..], "a"));
+
         #[cfg(feature = "alloc")]
+        {
+            use crate::fallible::FallibleCollection;
+
+            let (mut v, mut s) = (
+                crate::reexports::alloc::vec::Vec::new(),
+                crate::reexports::alloc::string::String::new(),
+            );
+            fill(&mut v, &mut s).unwrap();
+            assert_eq!((&v[..], &s[..]), (&b"abcd"[..], "abcd"));
+            assert_eq!(
+                FallibleCollection::try_reserve(&mut v, usize::MAX).unwrap_err(),
+                crate::Error::OutOfMemory
+            );
+        }
+
+        #[cfg(feature = "heapless")]
+        {
+            let (mut v, mut s) = (heapless::Vec::<u8, 3>::new(), heapless::String::<3>::new());
+            assert_eq!(
+                fill(&mut v, &mut s).unwrap_err(),
+                crate::Error::overflow::<heapless::Vec<u8, 3>>(1, 3, 3)
+            );
+            let err = s.try_push_str("abcd").unwrap_err();
+            assert_eq!(err.kind(), crate::ErrorKind::BufferOverflow);
+            let err = crate::Error::from(heapless::CapacityError::default());
+            assert_eq!(err, crate::Error::CapacityExceeded);
+            assert_eq!(err.kind(), crate::ErrorKind::BufferOverflow);
+        }
+    }
+
+    #[cfg(all(feature = "debug-capacity", feature = "std", not(alloc_backend)))]
+    #[test]
+    fn debug_capacity() {
+        let err = crate::String::<4>::try_from("hello").unwrap_err();
+        assert_eq!(
+            std::format!("{err}"),
+            "Attempted to grow `mayheap::string::String<4>` beyond its capacity \
+            (requested 5 more with 0 of 4 used)"
+        );
+        // The type name doesn't take part in comparisons.
+        assert_eq!(err, crate::Error::overflow::<()>(0, 5, 4));
+    }
+
+    #[cfg(all(feature = "linear-growth", alloc_backend))]
+    #[test]
+    fn linear_growth() {
+        let mut v = crate::Vec::<u8, 4>::new();
+        let mut s = crate::String::<4>::new();
+        let capacity = v.capacity();
+        for _ in 0..=capacity {
+            v.push(0).unwrap();
+            s.push('0').unwrap();
+        }
+        assert_eq!(v.capacity(), (capacity / 4 + 1) * 4);
+        assert_eq!(s.capacity(), (capacity / 4 + 1) * 4);
+
+        v.extend_from_slice(&[0; 10]).unwrap();
+        assert_eq!(v.capacity() % 4, 0);
+        assert!(v.capacity() - v.len() < 4);
+    }
+
+    #[cfg(feature = "embedded-io")]
+    #[test]
+    fn framed() {
+        use crate::framed::{Error, FrameDecoder, FrameEncoder, Prefix};
+
+        // Yields a byte at a time, timing out every other call.
+        struct Serial<'a> {
+            bytes: &'a [u8],
+            timeout: bool,
+        }
+        impl embedded_io::ErrorType for Serial<'_> {
+            type Error = embedded_io::ErrorKind;
+        }
+        impl embedded_io::Read for Serial<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+                self.timeout = !self.timeout;
+                if self.timeout {
+                    return Err(embedded_io::ErrorKind::TimedOut);
+                }
+                let Some((first, rest)) = self.bytes.split_first() else {
+                    return Ok(0);
+                };
+                buf[0] = *first;
+                self.bytes = rest;
+                Ok(1)
+            }
+        }
+
+        let encoder = FrameEncoder::new(Prefix::U32Le);
+        let mut stream = crate::Vec::<u8, 32>::new();
+        encoder.encode_into(&mut stream, b"abc").unwrap();
+        encoder.encode_into(&mut stream, b"").unwrap();
+        assert_eq!(stream, b"\x03\0\0\0abc\0\0\0\0");
+        encoder.encode_into(&mut stream, b"def").unwrap();
+
+        let mut decoder = FrameDecoder::<4>::new(Prefix::U32Le);
+        let mut serial = Serial {
+            bytes: &stream[..stream.len() - 1],
+            timeout: false,
+        };
+        let mut frames = crate::Vec::<crate::Vec<u8, 4>, 2>::new();
+        let err = loop {
+            match decoder.read_frame(&mut serial) {
+                Ok(Some(frame)) => frames.push(frame.try_into().unwrap()).unwrap(),
+                Ok(None) => unreachable!(),
+                Err(Error::Io(embedded_io::ErrorKind::TimedOut)) => (),
+                Err(e) => break e,
+            }
+        };
+        assert_eq!(frames, [&b"abc"[..], b""]);
+        assert_eq!(err, Error::UnexpectedEof);
+
+        // Doesn't fit in the prefix.
+        let res = FrameEncoder::new(Prefix::U8).encode_into(&mut stream, &[0; 256]);
+        assert_eq!(res.unwrap_err().kind(), crate::ErrorKind::BufferOverflow);
+    }
+
+    #[cfg(all(feature = "log", feature = "std"))]
+    #[test]
+    fn log_overflow() {
+        use std::{string::String, sync::Mutex};
+
+        static LOGGED: Mutex<String> = Mutex::new(String::new());
+
+        // Tests run in parallel so only keep the messages about our own type.
+        #[derive(Debug)]
+        struct Marker;
+
+        struct Logger;
+        impl log::Log for Logger {
+            fn enabled(&self, _metadata: &log::Metadata<'_>) -> bool {
+                true
+            }
+
+            fn log(&self, record: &log::Record<'_>) {
+                use core::fmt::Write;
+
+                let mut message = String::new();
+                let _ = write!(message, "{}", record.args());
+                if message.contains("Marker") {
+                    *LOGGED.lock().unwrap() = message;
+                }
+            }
+
+            fn flush(&self) {}
+        }
+        log::set_logger(&Logger).unwrap();
+        log::set_max_level(log::LevelFilter::Warn);
+
+        let mut v = crate::Vec::<Marker, 1>::new();
+        v.push(Marker).unwrap();
+        // Doesn't fit so should fail (and be logged) with `heapless` but not with `alloc`.
+        let res = v.push(Marker);
+        let logged = LOGGED.lock().unwrap();
+        #[cfg(not(alloc_backend))]
+        {
+            res.unwrap_err();
+            assert_eq!(
+                *logged,
+                "`mayheap::vec::Vec<mayheap::tests::log_overflow::Marker, 1>` overflowed: needed 1 \
+                 more at length 1 of capacity 1"
+            );
+        }
+        #[cfg(alloc_backend)]
+        {
+            res.unwrap();
+            assert!(logged.is_empty());
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn buf_writer() {
+        use std::io::Write;
+
+        // Accepts at most 2 bytes per call, and fails once `limit` bytes are written.
+        struct Uart {
+            written: crate::Vec<u8, 16>,
+            calls: usize,
+            limit: usize,
+        }
+        impl Write for Uart {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                if self.written.len() == self.limit {
+                    return Err(std::io::ErrorKind::Other.into());
+                }
+                let n = buf.len().min(2).min(self.limit - self.written.len());
+                self.written.extend_from_slice(&buf[..n]).unwrap();
+                self.calls += 1;
+                Ok(n)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let uart = Uart {
+            written: crate::Vec::new(),
+            calls: 0,
+            limit: 5,
+        };
+        let mut writer = crate::io::BufWriter::<_, 4>::new(uart);
+        for b in b"abc" {
+            writer.write_all(&[*b]).unwrap();
+        }
+        assert_eq!(writer.get_ref().calls, 0);
+        writer.write_all(b"de").unwrap();
+        assert_eq!(writer.get_ref().written, b"abc");
+        assert_eq!(writer.buffer(), b"de");
+
+        writer.flush().unwrap();
+        assert_eq!(writer.get_ref().written, b"abcde");
+
+        // The bytes not written are kept for the next attempt.
+        writer.write_all(b"fg").unwrap();
+        writer.flush().unwrap_err();
+        assert_eq!(writer.buffer(), b"fg");
+        writer.get_mut().limit = 16;
+        writer.flush().unwrap();
+
+        // Large writes bypass the buffer.
+        let calls = writer.get_ref().calls;
+        assert_eq!(writer.write(b"hijk").unwrap(), 2);
+        assert_eq!(writer.get_ref().calls, calls + 1);
+        let (uart, buf) = writer.into_parts();
+        assert_eq!(uart.written, b"abcdefghi");
+        assert!(buf.is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn read_to_end_std() {
+        use std::io::Read;
+
+        let mut v = crate::Vec::<u8, 8>::new();
+        let res = v
+            .read_to_end_std(&mut std::io::repeat(b'a').take(3))
+            .unwrap();
+        assert!(res.eof);
+        assert_eq!(v, b"aaa");
+        let res = v
+            .read_to_end_std(&mut std::io::repeat(b'b').take(6))
+            .unwrap();
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        #[cfg(not(alloc_backend))]
+        assert_eq!((res.read, res.eof), (5, false));
+        #[cfg(alloc_backend)]
+        assert_eq!((res.read, res.eof), (6, true));
+
+        // Only the bytes read count towards the high-water mark.
+        #[cfg(feature = "instrument")]
+        {
+            let mut v = crate::Vec::<u8, 9>::new();
+            v.read_to_end_std(&mut std::io::repeat(b'a').take(3))
+                .unwrap();
+            let stats = crate::instrument::of::<crate::Vec<u8, 9>>().unwrap();
+            assert_eq!(stats.high_water_mark, 3);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn io_error() {
+        let err = crate::Error::overflow::<crate::Vec<u8, 5>>(4, 2, 5);
+        let io_err = std::io::Error::from(err);
+        assert_eq!(io_err.kind(), std::io::ErrorKind::WriteZero);
+        assert_eq!(crate::Error::try_from(io_err).unwrap(), err);
+
+        let io_err = std::io::Error::from(std::io::ErrorKind::WriteZero);
+        crate::Error::try_from(io_err).unwrap_err();
+    }
+
+    #[test]
+    fn recapacity() {
+        let s = crate::String::<8>::try_from("hello").unwrap();
+        let s: crate::String<16> = s.grow_capacity();
+        assert_eq!(s, "hello");
+        let s = s.recapacity::<5>().unwrap();
+        assert_eq!(s, "hello");
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let res = s.recapacity::<4>();
+        #[cfg(not(alloc_backend))]
+        assert_eq!(res.unwrap_err().kind(), crate::ErrorKind::BufferOverflow);
+        #[cfg(alloc_backend)]
+        assert_eq!(res.unwrap(), "hello");
+    }
+
+    #[test]
+    fn try_from_iter() {
+        let s = crate::String::<8>::try_from_iter("hello".chars()).unwrap();
+        assert_eq!(s, "hello");
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let res = crate::String::<8>::try_from_iter(["hello", " ", "world"]);
+        #[cfg(not(alloc_backend))]
+        assert_eq!(
+            res.unwrap_err(),
+            crate::Error::overflow::<crate::String<8>>(6, 5, 8)
+        );
+        #[cfg(alloc_backend)]
+        assert_eq!(res.unwrap(), "hello world");
+
+        let mut s = crate::String::<8>::try_from("hi").unwrap();
+        s.try_extend([',', ' ']).unwrap();
+        let res = s.try_extend(["you", "!!!"]);
+        #[cfg(not(alloc_backend))]
+        {
+            assert_eq!(res.unwrap_err().kind(), crate::ErrorKind::BufferOverflow);
+            assert_eq!(s, "hi, ");
+        }
+        #[cfg(alloc_backend)]
+        {
+            res.unwrap();
+            assert_eq!(s, "hi, you!!!");
+        }
+    }
+
+    #[test]
+    fn trim_in_place() {
+        let mut s = crate::String::<16>::try_from("  AT+OK\r\n").unwrap();
+        s.trim_end_in_place();
+        assert_eq!(s, "  AT+OK");
+        s.push('\t').unwrap();
+        s.trim_in_place();
+        assert_eq!(s, "AT+OK");
+
+        assert!(s.strip_prefix_in_place("AT+"));
+        assert!(!s.strip_suffix_in_place("\r\n"));
+        assert_eq!(s, "OK");
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn normalization() {
+        // "é" as a single `char` and as an "e" followed by a combining acute accent.
+        let composed = crate::String::<8>::try_from("caf\u{e9}").unwrap();
+        let decomposed = crate::String::<8>::try_from("cafe\u{301}").unwrap();
+        assert!(composed.is_nfc() && !composed.is_nfd());
+        assert!(decomposed.is_nfd() && !decomposed.is_nfc());
+
+        let mut nfc = crate::String::<8>::new();
+        decomposed.nfc_into(&mut nfc).unwrap();
+        assert_eq!(nfc, composed);
+        let mut nfd = crate::String::<8>::new();
+        composed.nfd_into(&mut nfd).unwrap();
+        assert_eq!(nfd, decomposed);
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let mut nfd = crate::String::<5>::try_from("x").unwrap();
+        let res = composed.nfd_into(&mut nfd);
+        #[cfg(not(alloc_backend))]
+        {
+            assert_eq!(res.unwrap_err().kind(), crate::ErrorKind::BufferOverflow);
+            assert_eq!(nfd, "x");
+        }
+        #[cfg(alloc_backend)]
+        {
+            res.unwrap();
+            assert_eq!(nfd, "xcafe\u{301}");
+        }
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn graphemes() {
+        // "e" + combining acute accent, then a family emoji made of 3 `char`s.
+        let mut s = crate::String::<32>::try_from("ae\u{301}\u{1f468}\u{200d}\u{1f467}!").unwrap();
+        assert_eq!(s.chars().count(), 7);
+        assert_eq!(s.grapheme_len(), 4);
+        assert_eq!(s.graphemes().nth(1), Some("e\u{301}"));
+
+        let mut t = s.clone();
+        t.truncate_graphemes(10);
+        assert_eq!(t, s);
+        t.truncate_graphemes(3);
+        assert_eq!(t, "ae\u{301}\u{1f468}\u{200d}\u{1f467}");
+
+        // Cutting into the emoji drops all of it.
+        s.truncate_to_grapheme_boundary(s.len() - 2);
+        assert_eq!(s, "ae\u{301}");
+        s.truncate_to_grapheme_boundary(2);
+        assert_eq!(s, "a");
+        s.truncate_to_grapheme_boundary(0);
+        assert_eq!(s, "");
+    }
+
+    #[test]
+    fn wrap() {
+        let s =
+            crate::String::<64>::try_from("  hello   wonderful\tworld \r\n\nsupercalifragilistic")
+                .unwrap();
+        let mut lines = s.wrap_lines(9);
+        assert_eq!(lines.next(), Some("hello"));
+        assert_eq!(lines.next(), Some("wonderful"));
+        assert_eq!(lines.next(), Some("world"));
+        assert_eq!(lines.next(), Some(""));
+        assert_eq!(lines.next(), Some("supercali"));
+        assert_eq!(lines.next(), Some("fragilist"));
+        assert_eq!(lines.next(), Some("ic"));
+        assert_eq!(lines.next(), None);
+        assert_eq!(
+            crate::String::<8>::try_from("ab")
+                .unwrap()
+                .wrap_lines(0)
+                .count(),
+            2
+        );
+        assert_eq!(crate::String::<8>::new().wrap_lines(4).next(), None);
+
+        let mut lines = crate::Vec::<crate::String<8>, 4>::new();
+        crate::String::<16>::try_from("a bc def")
+            .unwrap()
+            .wrap_into(4, &mut lines)
+            .unwrap();
+        assert_eq!(lines, ["a bc", "def"]);
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let res = crate::String::<32>::try_from("one two three")
+            .unwrap()
+            .wrap_into(3, &mut lines);
+        #[cfg(not(alloc_backend))]
+        {
+            res.unwrap_err();
+            assert_eq!(lines, ["a bc", "def"]);
+        }
+        #[cfg(alloc_backend)]
+        {
+            res.unwrap();
+            assert_eq!(lines, ["a bc", "def", "one", "two", "thr", "ee"]);
+        }
+    }
+
+    #[test]
+    fn pad() {
+        let mut s = crate::String::<16>::try_from("€5").unwrap();
+        s.pad_left(4, ' ').unwrap();
+        assert_eq!(s, "  €5");
+        s.pad_right(5, '.').unwrap();
+        assert_eq!(s, "  €5.");
+        // Already wide enough.
+        s.center(3, '-').unwrap();
+        assert_eq!(s, "  €5.");
+
+        let mut s = crate::String::<16>::try_from("ab").unwrap();
+        s.center(7, '·').unwrap();
+        assert_eq!(s, "··ab···");
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let mut s = crate::String::<4>::try_from("ab").unwrap();
+        let res = s.pad_left(5, ' ');
+        #[cfg(not(alloc_backend))]
+        {
+            res.unwrap_err();
+            assert_eq!(s, "ab");
+        }
+        #[cfg(alloc_backend)]
+        {
+            res.unwrap();
+            assert_eq!(s, "   ab");
+        }
+    }
+
+    #[test]
+    fn split_collect() {
+        let s = crate::String::<32>::try_from("set  led on").unwrap();
+        let parts = s.split_whitespace_collect::<4, 3>().unwrap();
+        assert_eq!(parts, ["set", "led", "on"]);
+        let parts = s.split_collect::<4, 4>(" ").unwrap();
+        assert_eq!(parts, ["set", "", "led", "on"]);
+
+        let joined = crate::String::<16>::try_join(&parts, "/").unwrap();
+        assert_eq!(joined, "set//led/on");
+
+        // Too many parts so should fail with `heapless` but not with `alloc`.
+        let res = s.split_collect::<4, 3>(" ");
+        #[cfg(not(alloc_backend))]
+        assert_eq!(res.unwrap_err().kind(), crate::ErrorKind::BufferOverflow);
+        #[cfg(alloc_backend)]
+        assert_eq!(res.unwrap().len(), 4);
+    }
+
+    #[test]
+    fn try_write_fmt() {
+        let mut s = crate::String::<8>::try_from("id=").unwrap();
+        s.try_write_fmt(format_args!("{}", 42)).unwrap();
+        assert_eq!(s, "id=42");
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let res = s.try_write_fmt(format_args!(",{}", 1234));
+        #[cfg(not(alloc_backend))]
+        {
+            assert_eq!(res.unwrap_err().kind(), crate::ErrorKind::BufferOverflow);
+            assert_eq!(s, "id=42");
+        }
+        #[cfg(alloc_backend)]
+        {
+            res.unwrap();
+            assert_eq!(s, "id=42,1234");
+        }
+    }
+
+    #[test]
+    fn try_truncate() {
+        let mut s = crate::String::<8>::try_from("21.5°C").unwrap();
+        assert_eq!(
+            s.try_truncate(5).unwrap_err(),
+            crate::Error::NotCharBoundary { index: 5 }
+        );
+        assert_eq!(s, "21.5°C");
+        s.try_truncate(16).unwrap();
+        s.try_truncate(4).unwrap();
+        assert_eq!(s, "21.5");
+    }
+
+    #[test]
+    fn truncate_with_ellipsis() {
+        let mut s = crate::String::<16>::try_from("temp: 21.5°C").unwrap();
+        assert!(!s.truncate_with_ellipsis(13, "…"));
+        assert!(s.truncate_with_ellipsis(12, "…"));
+        assert_eq!(s, "temp: 21.…");
+        assert!(s.truncate_with_ellipsis(2, "…"));
+        assert_eq!(s, "te");
+
+        // Doesn't fit so should get truncated with `heapless` but not with `alloc`.
+        let (mut s, truncated) = crate::String::<4>::from_str_truncating("21.5°C");
+        #[cfg(not(alloc_backend))]
+        assert_eq!((s.as_str(), truncated), ("21.5", true));
+        #[cfg(alloc_backend)]
+        assert_eq!((s.as_str(), truncated), ("21.5°C", false));
+
+        s.clear();
+        assert!(!s.push_str_truncating("21"));
+        let mut v = crate::Vec::<u8, 4>::from_slice(b"21").unwrap();
+        // Doesn't fit so should get truncated with `heapless` but not with `alloc`.
+        let truncated = (
+            s.push_str_truncating(".5°C"),
+            v.extend_from_slice_truncating(b".5C"),
+        );
+        #[cfg(not(alloc_backend))]
+        {
+            assert_eq!((s.as_str(), &v[..]), ("21.5", &b"21.5"[..]));
+            assert_eq!(truncated, (true, true));
+        }
+        #[cfg(alloc_backend)]
+        {
+            assert_eq!((s.as_str(), &v[..]), ("21.5°C", &b"21.5C"[..]));
+            assert_eq!(truncated, (false, false));
+        }
+    }
+
+    #[test]
+    fn utf16() {
+        let s = crate::String::<8>::try_from("h€𝄞").unwrap();
+        let utf16 = s.to_utf16::<4>().unwrap();
+        assert_eq!(utf16, [0x68, 0x20ac, 0xd834, 0xdd1e]);
+        assert_eq!(crate::String::<8>::from_utf16(&utf16).unwrap(), s);
+
+        let invalid = [0x68, 0x20ac, 0xd834, 0x68];
+        let err = crate::String::<8>::from_utf16(&invalid).unwrap_err();
+        assert_eq!(err, crate::Error::Utf16Error { valid_up_to: 2 });
+        let lossy = crate::String::<8>::from_utf16_lossy(&invalid).unwrap();
+        assert_eq!(lossy, "h€\u{fffd}h");
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let mut utf16 = crate::Vec::<u16, 4>::new();
+        utf16.push(0x68).unwrap();
+        let res = s.encode_utf16_into(&mut utf16);
+        #[cfg(not(alloc_backend))]
+        {
+            assert_eq!(res.unwrap_err().kind(), crate::ErrorKind::BufferOverflow);
+            assert_eq!(utf16, [0x68]);
+        }
+        #[cfg(alloc_backend)]
+        {
+            res.unwrap();
+            assert_eq!(utf16.len(), 5);
+        }
+    }
+
+    #[test]
+    fn from_utf8() {
+        let bytes = crate::Vec::<u8, 4>::from_slice(b"ok").unwrap();
+        assert_eq!(crate::String::from_utf8(bytes).unwrap(), "ok");
+
+        let bytes = crate::Vec::<u8, 4>::from_slice(b"ok\xff").unwrap();
+        let err = crate::String::from_utf8(bytes).unwrap_err();
+        assert_eq!(err.utf8_error().valid_up_to(), 2);
+        assert_eq!(err.into_bytes(), b"ok\xff");
+    }
+
+    #[test]
+    fn bytes_mut() {
+        let mut s = crate::String::<8>::try_from("hello").unwrap();
+        s.bytes_mut()[0] = b'j';
+        assert_eq!(s, "jello");
+
+        let mut bytes = s.bytes_mut();
+        bytes.extend_from_slice(&[b'!', 0xff]).unwrap();
+        assert!(matches!(bytes.commit(), Err(crate::Error::Utf8Error(_))));
+        assert_eq!(s, "jello!");
+    }
+
+    #[cfg(feature = "itoa")]
+    #[test]
+    fn push_num() {
+        let mut s = crate::String::<64>::new();
+        s.push_int(-42i8).unwrap();
+        s.push(' ').unwrap();
+        s.push_uint(u64::MAX).unwrap();
+        s.push(' ').unwrap();
+        s.push_float(21.875f32, 2).unwrap();
+        s.push(' ').unwrap();
+        s.push_float(-0.999, 2).unwrap();
+        s.push(' ').unwrap();
+        s.push_float(0.05, 3).unwrap();
+        assert_eq!(s, "-42 18446744073709551615 21.88 -1.00 0.050");
+        s.clear();
+        s.push_int(i128::MIN).unwrap();
+        s.push(' ').unwrap();
+        s.push_int(-1isize).unwrap();
+        s.push(' ').unwrap();
+        s.push_uint(usize::MIN).unwrap();
+        assert_eq!(s, "-170141183460469231731687303715884105728 -1 0");
+
+        let mut v = crate::Vec::<u8, 8>::new();
+        v.push_float(f64::NAN, 2).unwrap();
+        v.push_int(7).unwrap();
+        v.push_uint(8u128).unwrap();
+        assert_eq!(v, b"NaN78");
+    }
+
+    #[test]
+    fn bits() {
+        use crate::bits::{BitOrder, BitReader, BitWriter};
+
+        let mut buf = crate::Vec::<u8, 32>::from_slice(&[0xff]).unwrap();
+        let mut writer = BitWriter::new(&mut buf, BitOrder::LsbFirst);
+        writer.write_bits(0b110, 3).unwrap();
+        assert!(!writer.is_aligned());
+        writer.write_bits(0x1ff, 9).unwrap();
+        writer.align();
+        writer.write_bits(u64::MAX, 64).unwrap();
+        writer.write_bits(1, 70).unwrap();
+        assert_eq!(&buf[..3], [0xff, 0b1111_1110, 0b0000_1111]);
+
+        let mut reader = BitReader::new(&buf, BitOrder::LsbFirst);
+        assert_eq!(reader.read_bits(8), Some(0xff));
+        assert_eq!(reader.read_bits(3), Some(0b110));
+        assert_eq!(reader.read_bits(9), Some(0x1ff));
+        reader.align();
+        assert_eq!(reader.position(), 24);
+        assert_eq!(reader.read_bits(64), Some(u64::MAX));
+        assert_eq!(reader.read_bits(65), None);
+        assert_eq!(reader.read_bool(), Some(true));
+        assert_eq!(reader.read_bits(64), Some(0));
+        assert_eq!(reader.remaining(), 7);
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let mut buf = crate::Vec::<u8, 1>::new();
+        let mut writer = BitWriter::new(&mut buf, BitOrder::MsbFirst);
+        writer.write_bits(0, 7).unwrap();
+        let res = writer.write_bits(0b11, 2);
+        #[cfg(not(alloc_backend))]
+        {
+            res.unwrap_err();
+            assert_eq!(buf, [0]);
+        }
+        #[cfg(alloc_backend)]
+        {
+            res.unwrap();
+            assert_eq!(buf, [0b0000_0001, 0b1000_0000]);
+        }
+    }
+
+    #[test]
+    fn varint() {
+        use crate::varint::{decode_u32, decode_u64};
+
+        let mut v = crate::Vec::<u8, 16>::new();
+        v.put_varint_u32(0).unwrap();
+        v.put_varint_u32(u32::MAX).unwrap();
+        assert_eq!(v, [0x00, 0xff, 0xff, 0xff, 0xff, 0x0f]);
+        assert_eq!(decode_u32(&v), Some((0, 1)));
+        assert_eq!(decode_u32(&v[1..]), Some((u32::MAX, 5)));
+        assert_eq!(decode_u32(&v[1..5]), None);
+        v.clear();
+        v.put_varint_u64(u64::MAX).unwrap();
+        assert_eq!(decode_u64(&v), Some((u64::MAX, 10)));
+        // Overflows.
+        assert_eq!(decode_u32(&v), None);
+        v[9] = 0x02;
+        assert_eq!(decode_u64(&v), None);
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let mut v = crate::Vec::<u8, 2>::new();
+        let res = v.put_varint_u32(1 << 14);
+        #[cfg(not(alloc_backend))]
+        {
+            res.unwrap_err();
+            assert!(v.is_empty());
+        }
+        #[cfg(alloc_backend)]
         res.unwrap();
     }
+
+    #[test]
+    fn escape() {
+        let input = "say \"hi\"\\\n\u{1}\u{1f}é";
+        let mut s = crate::String::<64>::new();
+        s.push_str_json_escaped(input).unwrap();
+        assert_eq!(s, r#"say \"hi\"\\\n\u0001\u001fé"#);
+        let mut v = crate::Vec::<u8, 64>::new();
+        v.push_str_json_escaped(input).unwrap();
+        assert_eq!(v, s.as_bytes());
+
+        let mut s = crate::String::<64>::new();
+        s.push_str_debug_escaped(input).unwrap();
+        assert_eq!(s, r#"say \"hi\"\\\n\u{1}\u{1f}é"#);
+        let mut v = crate::Vec::<u8, 64>::new();
+        v.push_str_debug_escaped(input).unwrap();
+        assert_eq!(v, s.as_bytes());
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let mut s = crate::String::<4>::try_from("a").unwrap();
+        let res = s.push_str_json_escaped("\"\"");
+        #[cfg(not(alloc_backend))]
+        {
+            res.unwrap_err();
+            assert_eq!(s, "a");
+        }
+        #[cfg(alloc_backend)]
+        res.unwrap();
+    }
+
+    #[cfg(feature = "percent-encoding")]
+    #[test]
+    fn percent_encoding() {
+        let mut s = crate::String::<32>::try_from("a=").unwrap();
+        s.percent_encode_into("€ & ü", crate::percent::FORM)
+            .unwrap();
+        assert_eq!(s, "a=%E2%82%AC%20%26%20%C3%BC");
+
+        let mut decoded = crate::String::<8>::try_from("x").unwrap();
+        decoded.percent_decode_into("%E2%82%AC+").unwrap();
+        assert_eq!(decoded, "x€+");
+        // Invalid UTF-8.
+        let err = decoded.percent_decode_into("%E2%82").unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Utf8Error);
+        assert_eq!(decoded, "x€+");
+        let mut form = crate::String::<8>::new();
+        form.percent_decode_form_into("a+b%20c%2B").unwrap();
+        assert_eq!(form, "a b c+");
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let mut s = crate::String::<8>::try_from("a=").unwrap();
+        let res = s.percent_encode_into("ü ü", crate::percent::PATH);
+        #[cfg(not(alloc_backend))]
+        {
+            res.unwrap_err();
+            assert_eq!(s, "a=");
+        }
+        #[cfg(alloc_backend)]
+        res.unwrap();
+    }
+
+    #[cfg(feature = "unicode-width")]
+    #[test]
+    fn truncate_to_width() {
+        let mut s = crate::String::<16>::try_from("温度: 21").unwrap();
+        assert_eq!(s.width(), 8);
+        s.truncate_to_width(3);
+        assert_eq!(s, "温");
+    }
+
+    #[cfg(feature = "embedded-io")]
+    #[test]
+    fn embedded_io() {
+        use embedded_io::{BufRead, Read, Write};
+
+        let mut buf = crate::Vec::<u8, 4>::new();
+        assert_eq!(buf.write(b"abc").unwrap(), 3);
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let res = buf.write(b"de");
+        #[cfg(not(alloc_backend))]
+        {
+            assert_eq!(res.unwrap(), 1);
+            let err = buf.write(b"e").unwrap_err();
+            assert_eq!(
+                embedded_io::Error::kind(&err),
+                embedded_io::ErrorKind::WriteZero
+            );
+        }
+        #[cfg(alloc_backend)]
+        assert_eq!(res.unwrap(), 2);
+
+        let mut cursor = crate::io::Cursor::new(buf);
+        cursor.set_position(1);
+        cursor.write_all(b"BC").unwrap();
+        assert_eq!(cursor.position(), 3);
+        assert_eq!(&cursor.get_ref()[..3], b"aBC");
+
+        cursor.set_position(0);
+        let mut head = [0; 2];
+        cursor.read_exact(&mut head).unwrap();
+        assert_eq!(&head, b"aB");
+        assert_eq!(&cursor.fill_buf().unwrap()[..1], b"C");
+        cursor.consume(1);
+        assert_eq!(cursor.position(), 3);
+
+        let mut buf = crate::Vec::<u8, 4>::new();
+        buf.put_varint_u32(300).unwrap();
+        let mut cursor = crate::io::Cursor::new(buf);
+        assert_eq!(cursor.get_varint_u64(), Some(300));
+        assert_eq!(cursor.get_varint_u32(), None);
+        assert_eq!(cursor.position(), 2);
+    }
+
+    #[cfg(feature = "embedded-io")]
+    #[test]
+    fn read_to_end() {
+        let mut v = crate::Vec::<u8, 4>::from_slice(b"ab").unwrap();
+        let mut reader: &[u8] = b"cd";
+        let res = v.read_to_end(&mut reader).unwrap();
+        assert_eq!(res.read, 2);
+        assert_eq!(v, b"abcd");
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let mut reader: &[u8] = b"efgh";
+        let res = v.read_to_end(&mut reader).unwrap();
+        #[cfg(not(alloc_backend))]
+        {
+            assert!(!res.eof);
+            assert_eq!(res.read, 0);
+            assert_eq!(v, b"abcd");
+        }
+        #[cfg(alloc_backend)]
+        {
+            assert!(res.eof);
+            assert_eq!(res.read, 4);
+            assert_eq!(v, b"abcdefgh");
+        }
+    }
+
+    #[cfg(feature = "embedded-io-async")]
+    #[test]
+    fn embedded_io_async() {
+        use core::{
+            future::Future,
+            pin::pin,
+            task::{Context, Poll, Waker},
+        };
+        use embedded_io_async::{Read, Write};
+
+        let fut = pin!(async {
+            let mut buf = crate::Vec::<u8, 8>::new();
+            buf.write_all(b"ping").await.unwrap();
+
+            let mut cursor = crate::io::Cursor::new(buf);
+            let mut out = [0; 4];
+            cursor.read_exact(&mut out).await.unwrap();
+            assert_eq!(&out, b"ping");
+            assert_eq!(cursor.read(&mut out).await.unwrap(), 0);
+        });
+        // None of the implementations are ever pending.
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(fut.poll(&mut cx), Poll::Ready(()));
+    }
+
+    #[cfg(feature = "futures-io")]
+    #[test]
+    fn futures_io() {
+        use core::{
+            pin::Pin,
+            task::{Context, Poll, Waker},
+        };
+        use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+
+        // None of the implementations are ever pending.
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut buf = crate::Vec::<u8, 4>::new();
+        let res = Pin::new(&mut buf).poll_write(&mut cx, b"ping");
+        assert!(matches!(res, Poll::Ready(Ok(4))));
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let res = Pin::new(&mut buf).poll_write(&mut cx, b"!");
+        #[cfg(not(alloc_backend))]
+        match res {
+            Poll::Ready(Err(e)) => assert_eq!(e.kind(), std::io::ErrorKind::WriteZero),
+            _ => panic!("expected an overflow"),
+        }
+        #[cfg(alloc_backend)]
+        assert!(matches!(res, Poll::Ready(Ok(1))));
+
+        let mut cursor = crate::io::Cursor::new(buf);
+        let mut head = [0; 2];
+        let res = Pin::new(&mut cursor).poll_read(&mut cx, &mut head);
+        assert!(matches!(res, Poll::Ready(Ok(2))));
+        assert_eq!(&head, b"pi");
+        let res = Pin::new(&mut cursor).poll_write(&mut cx, b"NG");
+        assert!(matches!(res, Poll::Ready(Ok(2))));
+        assert_eq!(&cursor.get_ref()[..4], b"piNG");
+        match Pin::new(&mut cursor).poll_fill_buf(&mut cx) {
+            #[cfg(not(alloc_backend))]
+            Poll::Ready(Ok(rest)) => assert!(rest.is_empty()),
+            #[cfg(alloc_backend)]
+            Poll::Ready(Ok(rest)) => assert_eq!(rest, b"!"),
+            _ => panic!("expected the rest"),
+        }
+        Pin::new(&mut cursor).consume(1);
+    }
+
+    #[cfg(feature = "musli")]
+    #[test]
+    fn musli() {
+        let s = crate::String::<8>::try_from("hello").unwrap();
+        let bytes = musli::storage::to_vec(&s).unwrap();
+        let s2: crate::String<8> = musli::storage::from_slice(&bytes).unwrap();
+        assert_eq!(s, s2);
+
+        let v = crate::Vec::<u16, 4>::from_array([1, 2, 300, 4]);
+        let bytes = musli::storage::to_vec(&v).unwrap();
+        let v2: crate::Vec<u16, 4> = musli::storage::from_slice(&bytes).unwrap();
+        assert_eq!(v, v2);
+
+        // Doesn't fit so should fail with `heapless` but not with `alloc`.
+        let res = musli::storage::from_slice::<crate::Vec<u16, 2>>(&bytes);
+        #[cfg(not(alloc_backend))]
+        res.unwrap_err();
+        #[cfg(alloc_backend)]
+        assert_eq!(res.unwrap(), [1, 2, 300, 4]);
+        let res =
+            musli::storage::from_slice::<crate::String<2>>(&musli::storage::to_vec(&s).unwrap());
+        #[cfg(not(alloc_backend))]
+        res.unwrap_err();
+        #[cfg(alloc_backend)]
+        assert_eq!(res.unwrap(), "hello");
+    }
+
+    #[cfg(feature = "ufmt")]
+    #[test]
+    fn ufmt() {
+        let name = crate::String::<8>::try_from("say \"hi\"").unwrap();
+        let mut s = crate::String::<32>::new();
+        ufmt::uwrite!(s, "{} {:?}", name, name).unwrap();
+        assert_eq!(s, r#"say "hi" "say \"hi\"""#);
+
+        let v = crate::Vec::<u8, 3>::from_slice(&[1, 2, 3]).unwrap();
+        let mut buf = crate::Vec::<u8, 16>::new();
+        ufmt::uwrite!(buf, "{:?}", v).unwrap();
+        assert_eq!(buf, b"[1, 2, 3]");
+    }
 }
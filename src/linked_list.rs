@@ -0,0 +1,277 @@
+//! A list with insertion order, over `heapless::Deque` and `alloc::collections::LinkedList`.
+//!
+//! Unlike [`crate::sorted_linked_list::SortedLinkedList`], [`LinkedList`] doesn't require the
+//! values to be [`Ord`], making it a good fit for waker lists and free lists. Values are pushed to
+//! the back and popped from the front, and can be removed from anywhere in between.
+//!
+//! With `heapless` backend, the list is backed by a ring buffer, so removing a value from the
+//! middle moves the values after it. With `alloc` backend, each value is allocated in a node of its
+//! own and never moved.
+//!
+//! # Usage
+//!
+//! ```
+//! use core::task::Waker;
+//! use mayheap::linked_list::LinkedList;
+//!
+//! let mut wakers = LinkedList::<Waker, 2>::new();
+//! wakers.push(Waker::noop().clone()).unwrap();
+//! assert_eq!(wakers.len(), 1);
+//!
+//! // Remove a waker that's no longer interested.
+//! assert!(wakers.remove(|w| w.will_wake(Waker::noop())).is_some());
+//! assert!(wakers.is_empty());
+//!
+//! let mut list = LinkedList::<u8, 2>::new();
+//! list.push(1).unwrap();
+//! list.push(2).unwrap();
+//! assert!(list.iter().eq(&[1, 2]));
+//! // Doesn't fit so should fail with `heapless` but not with `alloc`.
+//! let res = list.push(3);
+//! #[cfg(feature = "heapless")]
+//! assert_eq!(res, Err(3));
+//! #[cfg(feature = "alloc")]
+//! assert_eq!(res, Ok(()));
+//! assert_eq!(list.pop(), Some(1));
+//! ```
+
+use core::fmt;
+
+#[cfg(alloc_backend)]
+type Inner<T, const N: usize> = crate::reexports::alloc::collections::LinkedList<T>;
+#[cfg(not(alloc_backend))]
+type Inner<T, const N: usize> = heapless::Deque<T, N>;
+
+/// A list with a capacity of `N` values, in insertion order.
+///
+/// With `heapless` backend, `N` must be greater than zero. With `alloc` backend, the capacity is
+/// irrelevant and the list is never full. See the [module documentation](self) for more.
+pub struct LinkedList<T, const N: usize> {
+    list: Inner<T, N>,
+}
+
+impl<T, const N: usize> LinkedList<T, N> {
+    /// Creates a new, empty list.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { list: Inner::new() }
+    }
+
+    /// Appends a value to the back of the list.
+    ///
+    /// The value is given back if the list is full.
+    #[inline]
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        #[cfg(alloc_backend)]
+        {
+            self.list.push_back(value);
+
+            Ok(())
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            self.list.push_back(value)
+        }
+    }
+
+    /// Returns the first value, if any.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.list.front()
+    }
+
+    /// Removes the first value and returns it, or `None` if the list is empty.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        self.list.pop_front()
+    }
+
+    /// Removes the first value matching the predicate `f` and returns it.
+    ///
+    /// The order of the other values is kept. Complexity is worst-case *O*(n).
+    #[inline]
+    pub fn remove<F>(&mut self, f: F) -> Option<T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let index = self.list.iter().position(f)?;
+        #[cfg(alloc_backend)]
+        {
+            let mut rest = self.list.split_off(index);
+            let value = rest.pop_front();
+            self.list.append(&mut rest);
+
+            value
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            self.list.make_contiguous()[index..].rotate_left(1);
+
+            self.list.pop_back()
+        }
+    }
+
+    /// Removes all the values.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.list.clear()
+    }
+
+    /// Returns the number of values in the list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns `true` if the list is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns `true` if the list is at full capacity.
+    ///
+    /// Always returns `false` with `alloc` backend.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        #[cfg(alloc_backend)]
+        {
+            false
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            self.list.is_full()
+        }
+    }
+
+    /// Returns an iterator over the values, from front to back.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self.list.iter(),
+        }
+    }
+
+    /// Returns an iterator over the values, from front to back, allowing to modify them.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            iter: self.list.iter_mut(),
+        }
+    }
+}
+
+impl<T, const N: usize> Default for LinkedList<T, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for LinkedList<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a LinkedList<T, N> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut LinkedList<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator over the values of a [`LinkedList`].
+///
+/// This struct is created by [`LinkedList::iter`].
+pub struct Iter<'a, T> {
+    #[cfg(alloc_backend)]
+    iter: crate::reexports::alloc::collections::linked_list::Iter<'a, T>,
+    #[cfg(not(alloc_backend))]
+    iter: heapless::deque::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> core::iter::FusedIterator for Iter<'_, T> {}
+
+impl<T> fmt::Debug for Iter<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Iter").finish_non_exhaustive()
+    }
+}
+
+/// A mutable iterator over the values of a [`LinkedList`].
+///
+/// This struct is created by [`LinkedList::iter_mut`].
+pub struct IterMut<'a, T> {
+    #[cfg(alloc_backend)]
+    iter: crate::reexports::alloc::collections::linked_list::IterMut<'a, T>,
+    #[cfg(not(alloc_backend))]
+    iter: heapless::deque::IterMut<'a, T>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> core::iter::FusedIterator for IterMut<'_, T> {}
+
+impl<T> fmt::Debug for IterMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IterMut").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,66 @@
+//! A lock for the synchronization primitives that don't fit atomic operations.
+
+#[cfg(all(not(feature = "critical-section"), not(feature = "portable-atomic")))]
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(all(not(feature = "critical-section"), feature = "portable-atomic"))]
+use portable_atomic::{AtomicBool, Ordering};
+
+/// A lock based on a critical section if the `critical-section` feature is enabled, and a spin lock
+/// otherwise.
+pub(crate) struct Lock<T> {
+    #[cfg(feature = "critical-section")]
+    inner: critical_section::Mutex<core::cell::RefCell<T>>,
+    #[cfg(not(feature = "critical-section"))]
+    locked: AtomicBool,
+    #[cfg(not(feature = "critical-section"))]
+    inner: core::cell::UnsafeCell<T>,
+}
+
+// SAFETY: Access to the inner value is serialized by the spin lock.
+#[cfg(not(feature = "critical-section"))]
+unsafe impl<T: Send> Sync for Lock<T> {}
+
+impl<T> Lock<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Self {
+            #[cfg(feature = "critical-section")]
+            inner: critical_section::Mutex::new(core::cell::RefCell::new(value)),
+            #[cfg(not(feature = "critical-section"))]
+            locked: AtomicBool::new(false),
+            #[cfg(not(feature = "critical-section"))]
+            inner: core::cell::UnsafeCell::new(value),
+        }
+    }
+
+    /// Runs `f` with exclusive access to the value.
+    pub(crate) fn with<F, U>(&self, f: F) -> U
+    where
+        F: FnOnce(&mut T) -> U,
+    {
+        #[cfg(feature = "critical-section")]
+        {
+            critical_section::with(|cs| f(&mut self.inner.borrow_ref_mut(cs)))
+        }
+        #[cfg(not(feature = "critical-section"))]
+        {
+            while self
+                .locked
+                .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                core::hint::spin_loop();
+            }
+            // Release the lock even if `f` panics.
+            struct Unlock<'a>(&'a AtomicBool);
+            impl Drop for Unlock<'_> {
+                fn drop(&mut self) {
+                    self.0.store(false, Ordering::Release);
+                }
+            }
+            let _unlock = Unlock(&self.locked);
+
+            // SAFETY: We hold the lock.
+            f(unsafe { &mut *self.inner.get() })
+        }
+    }
+}
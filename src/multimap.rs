@@ -0,0 +1,305 @@
+//! A map from keys to several values each, with a capacity of `N` entries in total.
+//!
+//! The entries are kept sorted by key in a [`Vec`], so that the values of a key are contiguous and
+//! looked up through a binary search, rather than a linear scan.
+//!
+//! # Example
+//!
+//! ```
+//! use mayheap::multimap::MultiMap;
+//!
+//! let mut subscribers = MultiMap::<&str, u8, 4>::new();
+//! subscribers.insert("temperature", 1).unwrap();
+//! subscribers.insert("humidity", 2).unwrap();
+//! subscribers.insert("temperature", 3).unwrap();
+//!
+//! assert!(subscribers.get_all("temperature").eq(&[1, 3]));
+//! assert_eq!(subscribers.get_all("pressure").len(), 0);
+//!
+//! let mut groups = subscribers.groups();
+//! let (topic, ids) = groups.next().unwrap();
+//! assert_eq!((*topic, ids.as_slice().len()), ("humidity", 1));
+//! assert_eq!(groups.next().unwrap().0, &"temperature");
+//! assert!(groups.next().is_none());
+//!
+//! assert_eq!(subscribers.remove_all("temperature"), 2);
+//! assert_eq!(subscribers.len(), 1);
+//! ```
+
+use core::{borrow::Borrow, iter::FusedIterator, slice};
+
+use crate::Vec;
+
+/// A map from keys to several values each, with a capacity of `N` entries in total.
+///
+/// The values of each key are kept in insertion order. See the [module documentation](self) for
+/// more.
+#[derive(Debug, Clone)]
+pub struct MultiMap<K, V, const N: usize> {
+    entries: Vec<(K, V), N>,
+}
+
+impl<K, V, const N: usize> MultiMap<K, V, N> {
+    /// Creates a new, empty map.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns the total number of values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the map is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns `true` if the map is at full capacity.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.entries.is_full()
+    }
+
+    /// Removes all the entries.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns an iterator over all the entries, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, (K, V)> {
+        self.entries.iter()
+    }
+
+    /// Returns an iterator over the keys and all their values, sorted by key.
+    #[inline]
+    pub fn groups(&self) -> Groups<'_, K, V> {
+        Groups {
+            entries: &self.entries,
+        }
+    }
+
+    /// Retains only the entries specified by the predicate.
+    #[inline]
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.entries.retain_mut(|(key, value)| f(key, value));
+    }
+}
+
+impl<K: Ord, V, const N: usize> MultiMap<K, V, N> {
+    /// Adds `value` to the values of `key`, after the existing ones.
+    ///
+    /// Returns the entry back if the map is full.
+    #[inline]
+    pub fn insert(&mut self, key: K, value: V) -> Result<(), (K, V)> {
+        let index = self.entries.partition_point(|(k, _)| *k <= key);
+        self.entries.push((key, value))?;
+        self.entries[index..].rotate_right(1);
+
+        Ok(())
+    }
+
+    /// Returns `true` if there are any values for `key`.
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        !self.range(key).is_empty()
+    }
+
+    /// Returns the first value of `key`, if any.
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_all(key).next()
+    }
+
+    /// Returns an iterator over the values of `key`.
+    #[inline]
+    pub fn get_all<Q>(&self, key: &Q) -> Values<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let range = self.range(key);
+
+        Values {
+            iter: self.entries[range].iter(),
+        }
+    }
+
+    /// Returns an iterator over mutable references to the values of `key`.
+    #[inline]
+    pub fn get_all_mut<Q>(&mut self, key: &Q) -> ValuesMut<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let range = self.range(key);
+
+        ValuesMut {
+            iter: self.entries[range].iter_mut(),
+        }
+    }
+
+    /// Removes all the values of `key`, returning how many there were.
+    #[inline]
+    pub fn remove_all<Q>(&mut self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let range = self.range(key);
+        let removed = range.len();
+        self.entries[range.start..].rotate_left(removed);
+        self.entries.truncate(self.entries.len() - removed);
+
+        removed
+    }
+
+    // The range of the entries of `key`.
+    fn range<Q>(&self, key: &Q) -> core::ops::Range<usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let start = self.entries.partition_point(|(k, _)| k.borrow() < key);
+        let len = self.entries[start..].partition_point(|(k, _)| k.borrow() <= key);
+
+        start..start + len
+    }
+}
+
+impl<K, V, const N: usize> Default for MultiMap<K, V, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, K, V, const N: usize> IntoIterator for &'a MultiMap<K, V, N> {
+    type Item = &'a (K, V);
+    type IntoIter = slice::Iter<'a, (K, V)>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the values of a key in a [`MultiMap`].
+///
+/// This struct is created by [`MultiMap::get_all`] and [`MultiMap::groups`].
+#[derive(Debug, Clone)]
+pub struct Values<'a, K, V> {
+    iter: slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Values<'a, K, V> {
+    /// Returns the remaining entries as a slice.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [(K, V)] {
+        self.iter.as_slice()
+    }
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a V> {
+        self.iter.next().map(|(_, value)| value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Values<'_, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(_, value)| value)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+
+impl<K, V> FusedIterator for Values<'_, K, V> {}
+
+/// An iterator over mutable references to the values of a key in a [`MultiMap`].
+///
+/// This struct is created by [`MultiMap::get_all_mut`].
+#[derive(Debug)]
+pub struct ValuesMut<'a, K, V> {
+    iter: slice::IterMut<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a mut V> {
+        self.iter.next().map(|(_, value)| value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for ValuesMut<'_, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(_, value)| value)
+    }
+}
+
+impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {}
+
+impl<K, V> FusedIterator for ValuesMut<'_, K, V> {}
+
+/// An iterator over the keys of a [`MultiMap`] and all their values.
+///
+/// This struct is created by [`MultiMap::groups`].
+#[derive(Debug, Clone)]
+pub struct Groups<'a, K, V> {
+    entries: &'a [(K, V)],
+}
+
+impl<'a, K: PartialEq, V> Iterator for Groups<'a, K, V> {
+    type Item = (&'a K, Values<'a, K, V>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, _) = self.entries.first()?;
+        let len = self
+            .entries
+            .iter()
+            .position(|(k, _)| k != key)
+            .unwrap_or(self.entries.len());
+        let (group, rest) = self.entries.split_at(len);
+        self.entries = rest;
+
+        Some((key, Values { iter: group.iter() }))
+    }
+}
+
+impl<K: PartialEq, V> FusedIterator for Groups<'_, K, V> {}
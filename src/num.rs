@@ -0,0 +1,104 @@
+//! Number formatting that avoids the `core::fmt` machinery.
+//!
+//! This defines the integer types accepted by the `push_int` and `push_uint` methods of
+//! [`crate::String`] and [`crate::Vec<u8, N>`].
+
+/// A primitive signed integer type.
+///
+/// This trait is sealed and implemented for all the primitive signed integer types.
+pub trait SignedInt: itoa::Integer + sealed::Sealed {}
+
+/// A primitive unsigned integer type.
+///
+/// This trait is sealed and implemented for all the primitive unsigned integer types.
+pub trait UnsignedInt: itoa::Integer + sealed::Sealed {}
+
+macro_rules! impl_int {
+    ($trait:ident: $($ty:ty),*) => {
+        $(
+            impl sealed::Sealed for $ty {}
+            impl $trait for $ty {}
+        )*
+    };
+}
+
+impl_int!(SignedInt: i8, i16, i32, i64, i128, isize);
+impl_int!(UnsignedInt: u8, u16, u32, u64, u128, usize);
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The maximum supported number of digits after the decimal point.
+pub(crate) const MAX_PRECISION: usize = 19;
+
+const ZEROS: &str = "0000000000000000000";
+
+/// Writes `value` with `precision` digits after the decimal point, through `write`.
+///
+/// The last digit is rounded half away from zero. `precision` is capped at [`MAX_PRECISION`].
+pub(crate) fn write_float<W>(value: f64, precision: usize, mut write: W) -> crate::Result<()>
+where
+    W: FnMut(&str) -> crate::Result<()>,
+{
+    if value.is_nan() {
+        return write("NaN");
+    }
+    let value = if value.is_sign_negative() {
+        write("-")?;
+
+        -value
+    } else {
+        value
+    };
+    if value.is_infinite() {
+        return write("inf");
+    }
+    let precision = precision.min(MAX_PRECISION);
+    if value >= U64_LIMIT {
+        // The integral part doesn't fit in a `u64` so let `core::fmt` handle it.
+        let mut adapter = FmtAdapter { write, error: None };
+        return core::fmt::write(&mut adapter, format_args!("{value:.precision$}"))
+            .map_err(|_| adapter.error.unwrap_or(crate::Error::FormatError));
+    }
+
+    let scale = 10u64.pow(precision as u32);
+    let mut int = value as u64;
+    let mut frac = ((value - int as f64) * scale as f64 + 0.5) as u64;
+    if frac >= scale {
+        int = int.saturating_add(1);
+        frac -= scale;
+    }
+
+    let mut buf = itoa::Buffer::new();
+    write(buf.format(int))?;
+    if precision > 0 {
+        write(".")?;
+        let digits = buf.format(frac);
+        write(&ZEROS[..precision - digits.len()])?;
+        write(digits)?;
+    }
+
+    Ok(())
+}
+
+// 2^64 as `f64`.
+const U64_LIMIT: f64 = 18_446_744_073_709_551_616.0;
+
+struct FmtAdapter<W> {
+    write: W,
+    error: Option<crate::Error>,
+}
+
+impl<W> core::fmt::Write for FmtAdapter<W>
+where
+    W: FnMut(&str) -> crate::Result<()>,
+{
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        (self.write)(s).map_err(|e| {
+            self.error = Some(e);
+
+            core::fmt::Error
+        })
+    }
+}
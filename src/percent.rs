@@ -0,0 +1,58 @@
+//! Sets of ASCII characters to percent-encode, for [`crate::String::percent_encode_into`].
+//!
+//! Besides the sets provided by the `percent-encoding` crate, this provides the ones defined by the
+//! [WHATWG URL standard](https://url.spec.whatwg.org/#percent-encoded-bytes). Custom sets can be
+//! built from any of these through the `const` methods of [`AsciiSet`]. Non-ASCII bytes are always
+//! percent-encoded.
+//!
+//! # Example
+//!
+//! ```
+//! use mayheap::{percent, String};
+//!
+//! let mut url = String::<64>::try_from("https://example.com/search?q=").unwrap();
+//! url.percent_encode_into("mayheap & co", percent::COMPONENT).unwrap();
+//! assert_eq!(url, "https://example.com/search?q=mayheap%20%26%20co");
+//!
+//! let mut decoded = String::<16>::new();
+//! decoded.percent_decode_into("mayheap%20%26%20co").unwrap();
+//! assert_eq!(decoded, "mayheap & co");
+//!
+//! const DASHED: &percent::AsciiSet = &percent::NON_ALPHANUMERIC.remove(b'-');
+//! let mut s = String::<16>::new();
+//! s.percent_encode_into("a-b c", DASHED).unwrap();
+//! assert_eq!(s, "a-b%20c");
+//! ```
+
+pub use percent_encoding::{AsciiSet, CONTROLS, NON_ALPHANUMERIC};
+
+/// The fragment percent-encode set.
+pub const FRAGMENT: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'<').add(b'>').add(b'`');
+
+/// The query percent-encode set.
+pub const QUERY: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'#').add(b'<').add(b'>');
+
+/// The path percent-encode set.
+pub const PATH: &AsciiSet = &QUERY.add(b'?').add(b'^').add(b'`').add(b'{').add(b'}');
+
+/// The userinfo percent-encode set.
+pub const USERINFO: &AsciiSet = &PATH
+    .add(b'/')
+    .add(b':')
+    .add(b';')
+    .add(b'=')
+    .add(b'@')
+    .add(b'[')
+    .add(b'\\')
+    .add(b']')
+    .add(b'|');
+
+/// The component percent-encode set, for query parameters and path segments.
+pub const COMPONENT: &AsciiSet = &USERINFO.add(b'$').add(b'%').add(b'&').add(b'+').add(b',');
+
+/// The `application/x-www-form-urlencoded` percent-encode set, for form bodies.
+///
+/// Note that spaces are encoded as `%20`, rather than `+`. Use
+/// [`String::percent_decode_form_into`](crate::String::percent_decode_form_into) to decode form
+/// bodies, which accepts both.
+pub const FORM: &AsciiSet = &COMPONENT.add(b'!').add(b'\'').add(b'(').add(b')').add(b'~');
@@ -0,0 +1,38 @@
+//! The most commonly used items, for glob importing.
+//!
+//! Note that this shadows the `Vec` and `String` types of the standard prelude. [`crate::Result`]
+//! is not included since it'd shadow the standard `Result`.
+//!
+//! # Example
+//!
+//! ```
+//! use mayheap::prelude::*;
+//!
+//! let mut v = Vec::<u8, 4>::new();
+//! v.push(1).unwrap();
+//!
+//! let s: String<8> = string!("hello");
+//! let s2: String<16> = try_format!("{s} {}", v[0]).unwrap();
+//! assert_eq!(s2, "hello 1");
+//!
+//! let err: Error = String::<4>::from_utf8(Vec::from([0xff; 4])).unwrap_err().into();
+//! assert_eq!(err.kind(), ErrorKind::Utf8Error);
+//! ```
+
+pub use crate::{
+    fallible::{FallibleCollection, FallibleString, FallibleVec},
+    string, try_format, Error, ErrorKind, String, Vec,
+};
+
+#[cfg(any(
+    all(
+        not(alloc_backend),
+        any(feature = "portable-atomic", feature = "critical-section")
+    ),
+    alloc_backend
+))]
+pub use crate::{
+    box_pool,
+    boxed::{Box, BoxPool},
+    bytes_pool, str_pool,
+};
@@ -0,0 +1,395 @@
+//! Abstraction over `heapless::sorted_linked_list` and an `alloc::collections::LinkedList`-based
+//! equivalent.
+//!
+//! The API is modeled after `heapless::sorted_linked_list` but simpler, making this a good fit for
+//! timer queues and the like. With `heapless` backend, values are never moved while the list is
+//! (re)sorted, so large values don't cause a performance hit. With `alloc` backend, a value
+//! accessed through [`SortedLinkedList::find_mut`] is moved out of the list and back in.
+//!
+//! See [`crate::linked_list`] for a list that keeps the insertion order and doesn't require the
+//! values to be [`Ord`] (e.g wakers).
+//!
+//! # Usage
+//!
+//! ```
+//! use mayheap::sorted_linked_list::{Max, SortedLinkedList};
+//!
+//! let mut list = SortedLinkedList::<u32, Max, 3>::new();
+//!
+//! // The largest value will always be first.
+//! list.push(1).unwrap();
+//! list.push(3).unwrap();
+//! list.push(2).unwrap();
+//! assert_eq!(list.peek(), Some(&3));
+//! assert!(list.iter().eq(&[3, 2, 1]));
+//!
+//! // Values can be modified in place and are then sorted back into their position.
+//! let mut two = list.find_mut(|v| *v == 2).unwrap();
+//! *two += 1000;
+//! two.finish();
+//! assert_eq!(list.pop(), Some(1002));
+//!
+//! // Or removed from the middle of the list.
+//! list.find_mut(|v| *v == 1).unwrap().pop();
+//! assert_eq!(list.pop(), Some(3));
+//! assert!(list.is_empty());
+//!
+//! for v in [4, 5, 6] {
+//!     list.push(v).unwrap();
+//! }
+//! // Doesn't fit so should fail with `heapless` but not with `alloc`.
+//! let res = list.push(7);
+//! #[cfg(feature = "heapless")]
+//! assert_eq!(res, Err(7));
+//! #[cfg(feature = "alloc")]
+//! assert_eq!(res, Ok(()));
+//! ```
+
+use core::{cmp::Ordering, fmt, ops};
+
+/// Marker for a [`SortedLinkedList`] with the smallest value first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Min;
+
+/// Marker for a [`SortedLinkedList`] with the largest value first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Max;
+
+/// The kind of a [`SortedLinkedList`]: [`Min`] or [`Max`].
+///
+/// This trait is sealed and can not be implemented outside of this crate.
+pub trait Kind: sealed::Sealed {
+    #[cfg(not(alloc_backend))]
+    #[doc(hidden)]
+    type Heapless: heapless::sorted_linked_list::Kind;
+
+    // The ordering of a value with respect to the values sorted after it.
+    #[doc(hidden)]
+    fn ordering() -> Ordering;
+}
+
+impl Kind for Min {
+    #[cfg(not(alloc_backend))]
+    type Heapless = heapless::sorted_linked_list::Min;
+
+    #[inline]
+    fn ordering() -> Ordering {
+        Ordering::Less
+    }
+}
+
+impl Kind for Max {
+    #[cfg(not(alloc_backend))]
+    type Heapless = heapless::sorted_linked_list::Max;
+
+    #[inline]
+    fn ordering() -> Ordering {
+        Ordering::Greater
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for super::Min {}
+    impl Sealed for super::Max {}
+}
+
+/// A linked list, kept sorted on insertion, with a capacity of `N` values.
+///
+/// With `alloc` backend, the capacity is irrelevant and the list is never full.
+pub struct SortedLinkedList<T, K: Kind, const N: usize> {
+    #[cfg(alloc_backend)]
+    list: crate::reexports::alloc::collections::LinkedList<T>,
+    #[cfg(alloc_backend)]
+    kind: core::marker::PhantomData<K>,
+    #[cfg(not(alloc_backend))]
+    list: heapless::sorted_linked_list::SortedLinkedList<T, K::Heapless, N, usize>,
+}
+
+impl<T, K: Kind, const N: usize> SortedLinkedList<T, K, N> {
+    /// Creates a new, empty list.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            #[cfg(alloc_backend)]
+            list: crate::reexports::alloc::collections::LinkedList::new(),
+            #[cfg(alloc_backend)]
+            kind: core::marker::PhantomData,
+            #[cfg(not(alloc_backend))]
+            list: heapless::sorted_linked_list::SortedLinkedList::new_usize(),
+        }
+    }
+}
+
+impl<T: Ord, K: Kind, const N: usize> SortedLinkedList<T, K, N> {
+    /// Inserts a value into its sorted position.
+    ///
+    /// Complexity is worst-case *O*(n).
+    #[inline]
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        #[cfg(alloc_backend)]
+        {
+            insert_sorted::<T, K>(&mut self.list, value);
+
+            Ok(())
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            self.list.push(value)
+        }
+    }
+
+    /// Returns the first value, if any.
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        #[cfg(alloc_backend)]
+        {
+            self.list.front()
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            self.list.peek()
+        }
+    }
+
+    /// Removes the first value and returns it, or `None` if the list is empty.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        #[cfg(alloc_backend)]
+        {
+            self.list.pop_front()
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            self.list.pop()
+        }
+    }
+
+    /// Returns `true` if the list is at full capacity.
+    ///
+    /// Always returns `false` with `alloc` backend.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        #[cfg(alloc_backend)]
+        {
+            false
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            self.list.is_full()
+        }
+    }
+
+    /// Returns `true` if the list is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns an iterator over the values, in sorted order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T, K> {
+        Iter {
+            iter: self.list.iter(),
+            #[cfg(alloc_backend)]
+            kind: core::marker::PhantomData,
+        }
+    }
+
+    /// Finds the first value matching the predicate `f`, giving mutable access to it.
+    ///
+    /// The value is sorted back into its position when the returned guard is dropped, if it was
+    /// accessed mutably. It can also be removed from the list through [`FindMut::pop`].
+    ///
+    /// Complexity is worst-case *O*(n).
+    #[inline]
+    pub fn find_mut<F>(&mut self, f: F) -> Option<FindMut<'_, T, K>>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        #[cfg(alloc_backend)]
+        {
+            let index = self.list.iter().position(f)?;
+            let mut rest = self.list.split_off(index);
+            let value = rest.pop_front();
+            self.list.append(&mut rest);
+
+            Some(FindMut {
+                list: &mut self.list,
+                index,
+                value,
+                changed: false,
+                kind: core::marker::PhantomData,
+            })
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            self.list.find_mut(f).map(|view| FindMut { view })
+        }
+    }
+}
+
+impl<T, K: Kind, const N: usize> Default for SortedLinkedList<T, K, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, K, const N: usize> fmt::Debug for SortedLinkedList<T, K, N>
+where
+    T: Ord + fmt::Debug,
+    K: Kind,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// An iterator over the values of a [`SortedLinkedList`].
+///
+/// This struct is created by [`SortedLinkedList::iter`].
+pub struct Iter<'a, T: Ord, K: Kind> {
+    #[cfg(alloc_backend)]
+    iter: crate::reexports::alloc::collections::linked_list::Iter<'a, T>,
+    #[cfg(alloc_backend)]
+    kind: core::marker::PhantomData<K>,
+    #[cfg(not(alloc_backend))]
+    iter: heapless::sorted_linked_list::IterView<'a, T, usize, K::Heapless>,
+}
+
+impl<'a, T: Ord, K: Kind> Iterator for Iter<'a, T, K> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        self.iter.next()
+    }
+}
+
+impl<T: Ord, K: Kind> fmt::Debug for Iter<'_, T, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Iter").finish_non_exhaustive()
+    }
+}
+
+/// A guard giving mutable access to a value of a [`SortedLinkedList`].
+///
+/// This struct is created by [`SortedLinkedList::find_mut`]. See its documentation for more.
+pub struct FindMut<'a, T: Ord, K: Kind> {
+    #[cfg(alloc_backend)]
+    list: &'a mut crate::reexports::alloc::collections::LinkedList<T>,
+    // The position the value was taken out of.
+    #[cfg(alloc_backend)]
+    index: usize,
+    // Only `None` after the value has been popped or put back.
+    #[cfg(alloc_backend)]
+    value: Option<T>,
+    #[cfg(alloc_backend)]
+    changed: bool,
+    #[cfg(alloc_backend)]
+    kind: core::marker::PhantomData<K>,
+    #[cfg(not(alloc_backend))]
+    view: heapless::sorted_linked_list::FindMutView<'a, T, usize, K::Heapless>,
+}
+
+impl<T: Ord, K: Kind> FindMut<'_, T, K> {
+    /// Removes the value from the list and returns it.
+    #[inline]
+    pub fn pop(self) -> T {
+        #[cfg(alloc_backend)]
+        {
+            let mut this = self;
+            match this.value.take() {
+                Some(value) => value,
+                None => unreachable!(),
+            }
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            self.view.pop()
+        }
+    }
+
+    /// Sorts the value back into its position, if it was accessed mutably.
+    ///
+    /// Same as dropping the guard.
+    #[inline]
+    pub fn finish(self) {}
+}
+
+impl<T: Ord, K: Kind> ops::Deref for FindMut<'_, T, K> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        #[cfg(alloc_backend)]
+        {
+            match &self.value {
+                Some(value) => value,
+                None => unreachable!(),
+            }
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            &self.view
+        }
+    }
+}
+
+impl<T: Ord, K: Kind> ops::DerefMut for FindMut<'_, T, K> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        #[cfg(alloc_backend)]
+        {
+            self.changed = true;
+            match &mut self.value {
+                Some(value) => value,
+                None => unreachable!(),
+            }
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            &mut self.view
+        }
+    }
+}
+
+#[cfg(alloc_backend)]
+impl<T: Ord, K: Kind> Drop for FindMut<'_, T, K> {
+    #[inline]
+    fn drop(&mut self) {
+        let Some(value) = self.value.take() else {
+            return;
+        };
+        if self.changed {
+            insert_sorted::<T, K>(self.list, value);
+        } else {
+            let mut rest = self.list.split_off(self.index);
+            self.list.push_back(value);
+            self.list.append(&mut rest);
+        }
+    }
+}
+
+impl<T: Ord + fmt::Debug, K: Kind> fmt::Debug for FindMut<'_, T, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FindMut").field(&**self).finish()
+    }
+}
+
+// Inserts `value` before the first value that isn't sorted before it, the same way as `heapless`.
+#[cfg(alloc_backend)]
+fn insert_sorted<T: Ord, K: Kind>(
+    list: &mut crate::reexports::alloc::collections::LinkedList<T>,
+    value: T,
+) {
+    let index = list
+        .iter()
+        .position(|v| v.cmp(&value) != K::ordering())
+        .unwrap_or(list.len());
+    let mut rest = list.split_off(index);
+    list.push_back(value);
+    list.append(&mut rest);
+}
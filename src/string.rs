@@ -1,12 +1,12 @@
 //! A UTF-8–encoded, growable string.
 
-use core::{cmp::Ordering, fmt, hash, iter, ops, str};
+use core::{cmp::Ordering, fmt, hash, ops, str};
 
 use crate::Vec;
 
-#[cfg(feature = "alloc")]
+#[cfg(alloc_backend)]
 type Inner<const N: usize> = crate::reexports::alloc::string::String;
-#[cfg(not(feature = "alloc"))]
+#[cfg(not(alloc_backend))]
 type Inner<const N: usize> = heapless::String<N>;
 
 /// A UTF-8–encoded, growable string.
@@ -16,7 +16,11 @@ type Inner<const N: usize> = heapless::String<N>;
 /// When `heapless` feature is enabled, this is wrapper around `heapless::String`. Otherwise, this
 /// is a wrapper around `alloc::string::String`, setting the initial capacity to `N`. All fallible
 /// operations are in reality infallible and all unsafe methods are safe in the latter case.
+///
+/// This is guaranteed to be `#[repr(transparent)]` over the type of the backend. See the
+/// [`crate::convert`] module for the conversions this allows.
 #[derive(Clone, Debug)]
+#[repr(transparent)]
 pub struct String<const N: usize>(Inner<N>);
 
 impl<const N: usize> String<N> {
@@ -25,28 +29,205 @@ impl<const N: usize> String<N> {
     /// Note: Unlike, `heapless::string::String::new`, this method is currently not `const`.
     #[inline]
     pub fn new() -> Self {
-        #[cfg(feature = "alloc")]
+        #[cfg(alloc_backend)]
         {
             Self(Inner::with_capacity(N))
         }
-        #[cfg(not(feature = "alloc"))]
+        #[cfg(not(alloc_backend))]
         {
             Self(Inner::new())
         }
     }
 
+    /// Returns `true` if `len` bytes fit in a capacity of `N`.
+    ///
+    /// This only depends on `N`, regardless of the backend, so it can be used to check sizes that
+    /// must fit with `heapless` backend.
+    #[inline]
+    pub const fn fits(len: usize) -> bool {
+        len <= N
+    }
+
+    /// Asserts at compile-time that the capacity `N` is at least `M`.
+    ///
+    /// This only depends on `N`, regardless of the backend. A violation fails the build wherever
+    /// this function is instantiated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mayheap::String;
+    ///
+    /// const MAX_MESSAGE_LEN: usize = 48;
+    /// type Buffer = String::<64>;
+    ///
+    /// const _: () = Buffer::assert_capacity_at_least::<MAX_MESSAGE_LEN>();
+    /// const _: () = assert!(Buffer::fits(MAX_MESSAGE_LEN));
+    /// ```
+    ///
+    /// ```compile_fail
+    /// const _: () = mayheap::String::<64>::assert_capacity_at_least::<128>();
+    /// ```
+    #[inline]
+    pub const fn assert_capacity_at_least<const M: usize>() {
+        const { assert!(N >= M, "the capacity `N` must be at least `M`") };
+    }
+
     /// Convert UTF-8 bytes into a `String`.
+    ///
+    /// On failure, the bytes can be recovered through [`FromUtf8Error::into_bytes`].
     #[inline]
-    pub fn from_utf8(vec: Vec<u8, N>) -> crate::Result<Self> {
-        let res = Inner::from_utf8(vec.into_inner()).map(Self);
-        #[cfg(feature = "alloc")]
-        {
-            res.map_err(|e| e.utf8_error().into())
+    pub fn from_utf8(vec: Vec<u8, N>) -> Result<Self, FromUtf8Error<N>> {
+        if let Err(error) = validate_utf8(&vec) {
+            return Err(FromUtf8Error { bytes: vec, error });
         }
-        #[cfg(not(feature = "alloc"))]
-        {
-            res.map_err(|e| e.into())
+
+        // SAFETY: We just validated the bytes.
+        Ok(unsafe { Self::from_utf8_unchecked(vec) })
+    }
+
+    /// Creates a `String` from a string whose length `L` is known at compile-time.
+    ///
+    /// Use [`crate::string!`] instead of calling this directly.
+    #[doc(hidden)]
+    #[inline]
+    pub fn __from_str_with_len<const L: usize>(s: &str) -> Self {
+        const {
+            assert!(
+                L <= N,
+                "string literal doesn't fit in the `String` capacity"
+            )
+        };
+
+        let mut string = Self::new();
+        match string.push_str(s) {
+            Ok(()) => string,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Creates a `String` from an iterator of [`char`]s or string slices.
+    ///
+    /// Unlike the [`FromIterator`] implementation, this doesn't panic if the items don't fit in the
+    /// capacity but returns [`crate::Error::BufferOverflow`].
+    #[inline]
+    pub fn try_from_iter<I>(iter: I) -> crate::Result<Self>
+    where
+        I: IntoIterator,
+        I::Item: Appendable,
+    {
+        let mut string = Self::new();
+        for item in iter {
+            item.append_to(&mut string)?;
+        }
+
+        Ok(string)
+    }
+
+    /// Creates a `String` from as much of `s` as fits in the capacity.
+    ///
+    /// The string is cut at a [`char`] boundary. Returns `true` along with the `String` if `s` had
+    /// to be truncated, which can only happen when `heapless` feature is enabled.
+    #[inline]
+    pub fn from_str_truncating(s: &str) -> (Self, bool) {
+        #[cfg(alloc_backend)]
+        let cut = s.len();
+        #[cfg(not(alloc_backend))]
+        let cut = {
+            let mut cut = s.len().min(N);
+            while !s.is_char_boundary(cut) {
+                cut -= 1;
+            }
+
+            cut
+        };
+        let string = match Self::try_from(&s[..cut]) {
+            Ok(string) => string,
+            Err(_) => unreachable!(),
+        };
+
+        (string, cut < s.len())
+    }
+
+    /// Creates a `String` by joining the given string slices, placing `sep` between each of them.
+    ///
+    /// Fails with [`crate::Error::BufferOverflow`] if the result doesn't fit in the capacity.
+    #[inline]
+    pub fn try_join<I>(iter: I, sep: &str) -> crate::Result<Self>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut string = Self::new();
+        let mut iter = iter.into_iter();
+        if let Some(first) = iter.next() {
+            string.push_str(first.as_ref())?;
+        }
+        for part in iter {
+            string.push_str(sep)?;
+            string.push_str(part.as_ref())?;
+        }
+
+        Ok(string)
+    }
+
+    /// Decodes UTF-16 encoded `units` into a `String`.
+    ///
+    /// Returns [`crate::Error::Utf16Error`] if `units` contains an unpaired surrogate.
+    #[inline]
+    pub fn from_utf16(units: &[u16]) -> crate::Result<Self> {
+        let mut string = Self::new();
+        let mut valid_up_to = 0;
+        for c in char::decode_utf16(units.iter().copied()) {
+            let c = c.map_err(|_| crate::Error::Utf16Error { valid_up_to })?;
+            string.push(c)?;
+            valid_up_to += c.len_utf16();
+        }
+
+        Ok(string)
+    }
+
+    /// Decodes UTF-16 encoded `units` into a `String`, replacing unpaired surrogates with
+    /// [`char::REPLACEMENT_CHARACTER`].
+    #[inline]
+    pub fn from_utf16_lossy(units: &[u16]) -> crate::Result<Self> {
+        let mut string = Self::new();
+        for c in char::decode_utf16(units.iter().copied()) {
+            string.push(c.unwrap_or(char::REPLACEMENT_CHARACTER))?;
+        }
+
+        Ok(string)
+    }
+
+    /// Convert ASCII bytes into a `String`.
+    ///
+    /// This is cheaper than [`String::from_utf8`] as it only needs to check that each byte is
+    /// ASCII. Returns [`crate::Error::NonAscii`] if any byte is not ASCII.
+    #[inline]
+    pub fn from_ascii(bytes: &[u8]) -> crate::Result<Self> {
+        if !bytes.is_ascii() {
+            let valid_up_to = bytes.iter().position(|b| !b.is_ascii()).unwrap_or_default();
+
+            return Err(crate::Error::NonAscii { valid_up_to });
         }
+        // SAFETY: ASCII is always valid UTF-8.
+        let s = unsafe { str::from_utf8_unchecked(bytes) };
+        let mut string = Self::new();
+        string.push_str(s)?;
+
+        Ok(string)
+    }
+
+    /// Convert a slice of UTF-8 bytes into a `String`.
+    ///
+    /// Unlike [`String::from_utf8`], this doesn't require an intermediate [`Vec`]. Fails with
+    /// [`crate::Error::Utf8Error`] if the bytes are not valid UTF-8 and with
+    /// [`crate::Error::BufferOverflow`] if they don't fit in the capacity.
+    #[inline]
+    pub fn from_utf8_slice(bytes: &[u8]) -> crate::Result<Self> {
+        let s = validate_utf8(bytes)?;
+
+        Self::try_from(s)
     }
 
     /// Convert UTF-8 bytes into a `String`, without checking that the string
@@ -57,7 +238,7 @@ impl<const N: usize> String<N> {
     /// The bytes passed in must be valid UTF-8.
     #[inline]
     pub unsafe fn from_utf8_unchecked(vec: Vec<u8, N>) -> Self {
-        Self(Inner::from_utf8_unchecked(vec.into_inner()))
+        Self(Inner::from_utf8_unchecked(vec.into()))
     }
 
     /// Converts a `String` into a byte vector.
@@ -88,27 +269,74 @@ impl<const N: usize> String<N> {
     /// to it are valid UTF-8. If this constraint is violated, it may cause
     /// memory unsafety issues with future users of the `String`, as the rest of
     /// the library assumes that `String`s are valid UTF-8.
+    #[deprecated(
+        note = "The returned type depends on the backend. Use `String::bytes_mut`, `convert::AsHeapless` or `convert::AsAlloc` instead."
+    )]
     #[inline]
     pub unsafe fn as_mut_vec(&mut self) -> &mut crate::vec::Inner<u8, N> {
         self.0.as_mut_vec()
     }
 
+    /// Returns a guard giving mutable access to the contents of this `String` as a [`Vec`].
+    ///
+    /// Unlike [`String::as_mut_vec`], this is safe: the contents are validated as UTF-8 when the
+    /// guard is dropped. If they're not valid UTF-8, the `String` is truncated to the longest valid
+    /// prefix. Use [`BytesMut::commit`] to find out if that happened.
+    #[inline]
+    pub fn bytes_mut(&mut self) -> BytesMut<'_, N> {
+        // Unlike `String::new`, this doesn't allocate with `alloc` backend.
+        let bytes = core::mem::replace(self, Self(Inner::new())).into_bytes();
+
+        BytesMut {
+            string: self,
+            bytes: Some(bytes),
+        }
+    }
+
     /// Appends a given string slice onto the end of this `String`.
     #[inline]
     pub fn push_str(&mut self, string: &str) -> crate::Result<()> {
-        #[cfg(feature = "alloc")]
+        #[cfg(alloc_backend)]
         {
+            self.grow(string.len());
             self.0.push_str(string);
+            self.record(None);
             Ok(())
         }
-        #[cfg(not(feature = "alloc"))]
+        #[cfg(not(alloc_backend))]
         {
-            self.0
-                .push_str(string)
-                .map_err(|_| crate::Error::BufferOverflow)
+            let len = self.len();
+            let res = self.0.push_str(string);
+            self.record(res.is_err().then_some(string.len()));
+
+            res.map_err(|_| crate::Error::overflow::<Self>(len, string.len(), N))
         }
     }
 
+    /// Appends as much of `string` as fits in the capacity.
+    ///
+    /// The string is cut at a [`char`] boundary. Returns `true` if `string` had to be truncated,
+    /// which can only happen when `heapless` feature is enabled. This is useful for buffers where
+    /// losing the tail is preferable to failing (e.g log messages).
+    #[inline]
+    pub fn push_str_truncating(&mut self, string: &str) -> bool {
+        #[cfg(alloc_backend)]
+        let cut = string.len();
+        #[cfg(not(alloc_backend))]
+        let cut = {
+            let mut cut = string.len().min(N - self.len());
+            while !string.is_char_boundary(cut) {
+                cut -= 1;
+            }
+
+            cut
+        };
+        // We just made sure it fits.
+        let _ = self.push_str(&string[..cut]);
+
+        cut < string.len()
+    }
+
     /// Returns the maximum number of elements the `String` can hold.
     ///
     /// When `alloc` feature is enabled, this is the current capacity of the `String`.
@@ -117,190 +345,1088 @@ impl<const N: usize> String<N> {
         self.0.capacity()
     }
 
+    /// Tries to reserve capacity for at least `additional` more bytes.
+    ///
+    /// When `alloc` feature is enabled, this fails with [`crate::Error::OutOfMemory`] if the
+    /// allocation fails. Otherwise, this fails with [`crate::Error::BufferOverflow`] if the
+    /// capacity is not enough.
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> crate::Result<()> {
+        #[cfg(alloc_backend)]
+        {
+            self.0.try_reserve(additional).map_err(Into::into)
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            if additional > N - self.len() {
+                return Err(crate::Error::overflow::<Self>(self.len(), additional, N));
+            }
+
+            Ok(())
+        }
+    }
+
     /// Appends the given [`char`] to the end of this `String`.
     #[inline]
     pub fn push(&mut self, c: char) -> crate::Result<()> {
-        #[cfg(feature = "alloc")]
+        #[cfg(alloc_backend)]
         {
+            self.grow(c.len_utf8());
             self.0.push(c);
+            self.record(None);
             Ok(())
         }
-        #[cfg(not(feature = "alloc"))]
+        #[cfg(not(alloc_backend))]
         {
-            self.0.push(c).map_err(|_| crate::Error::BufferOverflow)
+            let len = self.len();
+            let res = self.0.push(c);
+            self.record(res.is_err().then(|| c.len_utf8()));
+
+            res.map_err(|_| crate::Error::overflow::<Self>(len, c.len_utf8(), N))
+        }
+    }
+
+    /// Appends all [`char`]s or string slices from an iterator.
+    ///
+    /// Fails with [`crate::Error::BufferOverflow`] if the items don't fit in the capacity. Nothing
+    /// is appended on failure.
+    #[inline]
+    pub fn try_extend<I>(&mut self, iter: I) -> crate::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Appendable,
+    {
+        let len = self.len();
+        for item in iter {
+            if let Err(e) = item.append_to(self) {
+                self.0.truncate(len);
+
+                return Err(e);
+            }
         }
+
+        Ok(())
     }
 
     /// Shortens this `String` to the specified length.
     ///
     /// If `new_len` is greater than the string's current length, this has no
     /// effect.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new_len` does not lie on a [`char`] boundary.
+    #[cfg(not(feature = "panic-free"))]
     #[inline]
     pub fn truncate(&mut self, new_len: usize) {
         self.0.truncate(new_len)
     }
 
-    /// Removes the last character from the string buffer and returns it.
+    /// Shortens this `String` to the specified length.
     ///
-    /// Returns [`None`] if this `String` is empty.
+    /// If `new_len` is greater than the string's current length, this has no effect. Fails with
+    /// [`crate::Error::NotCharBoundary`] if `new_len` does not lie on a [`char`] boundary, in which
+    /// case the string is left unchanged.
     #[inline]
-    pub fn pop(&mut self) -> Option<char> {
-        self.0.pop()
+    pub fn try_truncate(&mut self, new_len: usize) -> crate::Result<()> {
+        if new_len < self.len() && !self.is_char_boundary(new_len) {
+            return Err(crate::Error::NotCharBoundary { index: new_len });
+        }
+        self.0.truncate(new_len);
+
+        Ok(())
     }
 
-    /// Removes a [`char`] from this `String` at a byte position and returns it.
-    ///
-    /// Note: Because this shifts over the remaining elements, it has a
-    /// worst-case performance of *O*(*n*).
+    /// Shortens this `String` to at most `max_bytes` bytes, ending it with `marker` if truncation
+    /// was needed.
     ///
-    /// # Panics
+    /// The string is cut at a [`char`] boundary so that the string, including `marker`, is at most
+    /// `max_bytes` long. If `marker` itself is longer than `max_bytes`, it's not appended.
     ///
-    /// Panics if `idx` is larger than or equal to the `String`'s length,
-    /// or if it does not lie on a [`char`] boundary.
+    /// Returns `true` if the string was truncated.
     #[inline]
-    pub fn remove(&mut self, index: usize) -> char {
-        self.0.remove(index)
+    pub fn truncate_with_ellipsis(&mut self, max_bytes: usize, marker: &str) -> bool {
+        if self.len() <= max_bytes {
+            return false;
+        }
+        let (mut cut, marker) = match max_bytes.checked_sub(marker.len()) {
+            Some(cut) => (cut, marker),
+            None => (max_bytes, ""),
+        };
+        while !self.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        self.0.truncate(cut);
+        // We just made enough room for the marker.
+        let _ = self.push_str(marker);
+
+        true
     }
 
-    /// Truncates this `String`, removing all contents.
+    /// Returns the displayed width of the string in columns.
+    ///
+    /// See [`unicode_width::UnicodeWidthStr::width`] for details.
+    #[cfg(feature = "unicode-width")]
     #[inline]
-    pub fn clear(&mut self) {
-        self.0.clear()
+    pub fn width(&self) -> usize {
+        unicode_width::UnicodeWidthStr::width(self.as_str())
     }
-}
 
-impl<const N: usize> Default for String<N> {
+    /// Shortens this `String` so that its displayed width is at most `columns`.
+    ///
+    /// The string is cut at a [`char`] boundary, based on the width of each individual `char` (see
+    /// [`unicode_width::UnicodeWidthChar::width`]). Control characters are considered to have a
+    /// width of 0.
+    #[cfg(feature = "unicode-width")]
     #[inline]
-    fn default() -> Self {
-        Self::new()
+    pub fn truncate_to_width(&mut self, columns: usize) {
+        let mut width = 0;
+        for (i, c) in self.char_indices() {
+            width += unicode_width::UnicodeWidthChar::width(c).unwrap_or(0);
+            if width > columns {
+                self.0.truncate(i);
+
+                return;
+            }
+        }
     }
-}
 
-impl<'a, const N: usize> TryFrom<&'a str> for String<N> {
-    type Error = crate::Error;
+    /// Returns an iterator over the (extended) grapheme clusters of the string.
+    ///
+    /// A grapheme cluster is what users perceive as a single character, such as an emoji with a
+    /// skin tone modifier or a letter followed by combining accents, and can be made of several
+    /// [`char`]s.
+    #[cfg(feature = "unicode-segmentation")]
     #[inline]
-    fn try_from(s: &'a str) -> Result<Self, Self::Error> {
-        <Self as core::str::FromStr>::from_str(s).map_err(|_| crate::Error::BufferOverflow)
+    pub fn graphemes(&self) -> unicode_segmentation::Graphemes<'_> {
+        unicode_segmentation::UnicodeSegmentation::graphemes(self.as_str(), true)
     }
-}
 
-impl<const N: usize> str::FromStr for String<N> {
-    type Err = crate::Error;
+    /// Returns the number of (extended) grapheme clusters in the string.
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn grapheme_len(&self) -> usize {
+        self.graphemes().count()
+    }
 
+    /// Shortens this `String` to its first `count` (extended) grapheme clusters.
+    ///
+    /// If the string has `count` grapheme clusters or less, this has no effect.
+    #[cfg(feature = "unicode-segmentation")]
     #[inline]
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Inner::from_str(s)
-            .map(Self)
-            .map_err(|_| crate::Error::BufferOverflow)
+    pub fn truncate_graphemes(&mut self, count: usize) {
+        let Some((i, _)) =
+            unicode_segmentation::UnicodeSegmentation::grapheme_indices(self.as_str(), true)
+                .nth(count)
+        else {
+            return;
+        };
+        self.0.truncate(i);
     }
-}
 
-impl<const N: usize> From<String<N>> for Vec<u8, N> {
+    /// Shortens this `String` to at most `max_bytes` bytes, without splitting a (extended)
+    /// grapheme cluster.
+    ///
+    /// Unlike [`String::try_truncate`], which only keeps [`char`] boundaries, this never leaves a
+    /// partial emoji or a letter without its combining accents at the end.
+    #[cfg(feature = "unicode-segmentation")]
     #[inline]
-    fn from(s: String<N>) -> Self {
-        s.into_bytes()
+    pub fn truncate_to_grapheme_boundary(&mut self, max_bytes: usize) {
+        if self.len() <= max_bytes {
+            return;
+        }
+        let mut end = 0;
+        for (i, grapheme) in
+            unicode_segmentation::UnicodeSegmentation::grapheme_indices(self.as_str(), true)
+        {
+            if i + grapheme.len() > max_bytes {
+                break;
+            }
+            end = i + grapheme.len();
+        }
+        self.0.truncate(end);
     }
-}
 
-impl<const N: usize> From<String<N>> for Inner<N> {
+    /// Appends the Unicode Normalization Form C (canonical composition) of the string to `target`.
+    ///
+    /// Comparing the NFC (or NFD) forms of two strings tells if they're canonically equivalent,
+    /// e.g. if one has an "é" where the other has an "e" followed by a combining acute accent.
+    /// Nothing is appended on failure.
+    #[cfg(feature = "unicode-normalization")]
     #[inline]
-    fn from(s: String<N>) -> Self {
-        s.0
+    pub fn nfc_into<const M: usize>(&self, target: &mut String<M>) -> crate::Result<()> {
+        push_chars(
+            target,
+            unicode_normalization::UnicodeNormalization::nfc(self.as_str()),
+        )
     }
-}
 
-impl<const N: usize> From<Inner<N>> for String<N> {
+    /// Appends the Unicode Normalization Form D (canonical decomposition) of the string to
+    /// `target`.
+    ///
+    /// Nothing is appended on failure.
+    #[cfg(feature = "unicode-normalization")]
     #[inline]
-    fn from(s: Inner<N>) -> Self {
-        Self(s)
+    pub fn nfd_into<const M: usize>(&self, target: &mut String<M>) -> crate::Result<()> {
+        push_chars(
+            target,
+            unicode_normalization::UnicodeNormalization::nfd(self.as_str()),
+        )
     }
-}
 
-impl<const N: usize> iter::FromIterator<char> for String<N> {
+    /// Returns `true` if the string is in Unicode Normalization Form C.
+    #[cfg(feature = "unicode-normalization")]
     #[inline]
-    fn from_iter<T: IntoIterator<Item = char>>(iter: T) -> Self {
-        Self(Inner::from_iter(iter))
+    pub fn is_nfc(&self) -> bool {
+        unicode_normalization::is_nfc(self.as_str())
     }
-}
 
-impl<'a, const N: usize> iter::FromIterator<&'a char> for String<N> {
+    /// Returns `true` if the string is in Unicode Normalization Form D.
+    #[cfg(feature = "unicode-normalization")]
     #[inline]
-    fn from_iter<T: IntoIterator<Item = &'a char>>(iter: T) -> Self {
-        Self(Inner::from_iter(iter))
+    pub fn is_nfd(&self) -> bool {
+        unicode_normalization::is_nfd(self.as_str())
     }
-}
 
-impl<'a, const N: usize> iter::FromIterator<&'a str> for String<N> {
+    /// Appends `string` escaped as the contents of a JSON string (without the quotes).
+    ///
+    /// Quotes, backslashes and control characters are escaped. Nothing is appended on failure.
     #[inline]
-    fn from_iter<T: IntoIterator<Item = &'a str>>(iter: T) -> Self {
-        Self(Inner::from_iter(iter))
+    pub fn push_str_json_escaped(&mut self, string: &str) -> crate::Result<()> {
+        let len = self.len();
+        let res = crate::escape::write_json(string, |s| self.push_str(s));
+        if res.is_err() {
+            self.0.truncate(len);
+        }
+
+        res
     }
-}
 
-impl<const N: usize> fmt::Display for String<N> {
+    /// Appends `string` escaped the same way as [`str::escape_debug`] (without the quotes).
+    ///
+    /// Nothing is appended on failure.
     #[inline]
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.0.fmt(f)
+    pub fn push_str_debug_escaped(&mut self, string: &str) -> crate::Result<()> {
+        let len = self.len();
+        let res = crate::escape::write_debug(string, |s| self.push_str(s));
+        if res.is_err() {
+            self.0.truncate(len);
+        }
+
+        res
     }
-}
 
-impl<const N: usize> hash::Hash for String<N> {
+    /// Appends `input`, percent-encoding the bytes in `set` as well as all non-ASCII bytes.
+    ///
+    /// See the [`crate::percent`] module for the available sets. Nothing is appended on failure.
+    #[cfg(feature = "percent-encoding")]
     #[inline]
-    fn hash<H: hash::Hasher>(&self, hasher: &mut H) {
-        self.0.hash(hasher)
+    pub fn percent_encode_into(
+        &mut self,
+        input: impl AsRef<[u8]>,
+        set: &'static crate::percent::AsciiSet,
+    ) -> crate::Result<()> {
+        let len = self.len();
+        let res = percent_encoding::percent_encode(input.as_ref(), set)
+            .try_for_each(|part| self.push_str(part));
+        if res.is_err() {
+            self.0.truncate(len);
+        }
+
+        res
     }
-}
 
-impl<const N: usize> fmt::Write for String<N> {
+    /// Appends `input`, decoding all percent-encoded bytes.
+    ///
+    /// Fails with [`crate::Error::Utf8Error`] if the decoded bytes are not valid UTF-8. Nothing is
+    /// appended on failure.
+    #[cfg(feature = "percent-encoding")]
     #[inline]
-    fn write_str(&mut self, s: &str) -> Result<(), fmt::Error> {
-        self.0.write_str(s)
+    pub fn percent_decode_into(&mut self, input: &str) -> crate::Result<()> {
+        self.push_decoded(percent_encoding::percent_decode_str(input))
     }
 
+    /// Appends `input`, decoding it as `application/x-www-form-urlencoded` content.
+    ///
+    /// This is the same as [`String::percent_decode_into`], except that `+` is decoded as a space.
+    /// Hence, this decodes the output of [`String::percent_encode_into`] with any of the sets in
+    /// [`crate::percent`], as well as form bodies encoding spaces as `+`.
+    #[cfg(feature = "percent-encoding")]
     #[inline]
-    fn write_char(&mut self, c: char) -> Result<(), fmt::Error> {
-        self.0.write_char(c)
+    pub fn percent_decode_form_into(&mut self, input: &str) -> crate::Result<()> {
+        let decoded = input.split('+').enumerate().flat_map(|(i, part)| {
+            let space = (i > 0).then_some(b' ');
+
+            space
+                .into_iter()
+                .chain(percent_encoding::percent_decode_str(part))
+        });
+
+        self.push_decoded(decoded)
     }
-}
 
-impl<const N: usize> ops::Deref for String<N> {
-    type Target = str;
+    // Appends the `decoded` bytes, failing if they're not valid UTF-8. Nothing is appended on
+    // failure.
+    #[cfg(feature = "percent-encoding")]
+    fn push_decoded(&mut self, decoded: impl Iterator<Item = u8>) -> crate::Result<()> {
+        let len = self.len();
+        let mut bytes = self.bytes_mut();
+        let res = bytes.try_extend(decoded).and_then(move |()| bytes.commit());
+        if res.is_err() {
+            self.0.truncate(len);
+        }
 
-    #[inline]
-    fn deref(&self) -> &str {
-        self.as_str()
+        res
     }
-}
 
-impl<const N: usize> ops::DerefMut for String<N> {
+    /// Removes the last character from the string buffer and returns it.
+    ///
+    /// Returns [`None`] if this `String` is empty.
     #[inline]
-    fn deref_mut(&mut self) -> &mut str {
-        self.as_mut_str()
+    pub fn pop(&mut self) -> Option<char> {
+        self.0.pop()
     }
-}
 
-impl<const N: usize> AsRef<str> for String<N> {
+    /// Removes a [`char`] from this `String` at a byte position and returns it.
+    ///
+    /// Note: Because this shifts over the remaining elements, it has a
+    /// worst-case performance of *O*(*n*).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is larger than or equal to the `String`'s length,
+    /// or if it does not lie on a [`char`] boundary.
+    #[cfg(not(feature = "panic-free"))]
     #[inline]
-    fn as_ref(&self) -> &str {
-        self
+    pub fn remove(&mut self, index: usize) -> char {
+        self.0.remove(index)
     }
-}
 
-impl<const N: usize> AsRef<[u8]> for String<N> {
+    /// Removes the specified range from the string in bulk, returning all removed characters as an
+    /// iterator.
+    ///
+    /// The range is removed even if the iterator is not consumed until the end.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point or end point do not lie on a [`char`] boundary, or if they're
+    /// out of bounds.
+    #[cfg(not(feature = "panic-free"))]
     #[inline]
-    fn as_ref(&self) -> &[u8] {
-        self.as_bytes()
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_>
+    where
+        R: ops::RangeBounds<usize>,
+    {
+        Drain {
+            iter: self.0.drain(range),
+        }
     }
-}
 
-impl<const N1: usize, const N2: usize> PartialEq<String<N2>> for String<N1> {
+    /// Truncates this `String`, removing all contents.
     #[inline]
-    fn eq(&self, rhs: &String<N2>) -> bool {
-        self.0.eq(&rhs.0)
+    pub fn clear(&mut self) {
+        self.0.clear()
     }
-}
 
-// String<N> == str
+    /// Appends the decimal representation of the signed integer `value`.
+    ///
+    /// Unlike going through [`core::fmt`], this uses a small and fast formatting routine.
+    #[cfg(feature = "itoa")]
+    #[inline]
+    pub fn push_int(&mut self, value: impl crate::num::SignedInt) -> crate::Result<()> {
+        self.push_str(itoa::Buffer::new().format(value))
+    }
+
+    /// Appends the decimal representation of the unsigned integer `value`.
+    ///
+    /// Unlike going through [`core::fmt`], this uses a small and fast formatting routine.
+    #[cfg(feature = "itoa")]
+    #[inline]
+    pub fn push_uint(&mut self, value: impl crate::num::UnsignedInt) -> crate::Result<()> {
+        self.push_str(itoa::Buffer::new().format(value))
+    }
+
+    /// Appends the decimal representation of `value`, with `precision` digits after the decimal
+    /// point.
+    ///
+    /// The last digit is rounded half away from zero so the output may differ slightly from
+    /// [`core::fmt`]. `precision` is capped at 19. Nothing is appended on failure.
+    #[cfg(feature = "itoa")]
+    #[inline]
+    pub fn push_float(&mut self, value: impl Into<f64>, precision: usize) -> crate::Result<()> {
+        let len = self.len();
+        let res = crate::num::write_float(value.into(), precision, |s| self.push_str(s));
+        if res.is_err() {
+            self.0.truncate(len);
+        }
+
+        res
+    }
+
+    /// Appends formatted text to this `String`.
+    ///
+    /// Unlike going through [`fmt::Write::write_fmt`], this tells apart running out of capacity
+    /// ([`crate::Error::BufferOverflow`]) from a formatting trait implementation failing
+    /// ([`crate::Error::FormatError`]). Nothing is appended on failure.
+    ///
+    /// See also [`crate::try_format!`].
+    #[inline]
+    pub fn try_write_fmt(&mut self, args: fmt::Arguments<'_>) -> crate::Result<()> {
+        struct Adapter<'a, const N: usize> {
+            string: &'a mut String<N>,
+            error: Option<crate::Error>,
+        }
+
+        impl<const N: usize> fmt::Write for Adapter<'_, N> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.string.push_str(s).map_err(|e| {
+                    self.error = Some(e);
+
+                    fmt::Error
+                })
+            }
+        }
+
+        let len = self.len();
+        let mut adapter = Adapter {
+            string: self,
+            error: None,
+        };
+        let res = fmt::write(&mut adapter, args)
+            .map_err(|_| adapter.error.unwrap_or(crate::Error::FormatError));
+        if res.is_err() {
+            self.0.truncate(len);
+        }
+
+        res
+    }
+
+    /// Removes leading and trailing whitespace in place.
+    ///
+    /// Whitespace is defined according to the terms of the Unicode Derived Core Property
+    /// `White_Space`, as in [`str::trim`].
+    #[inline]
+    pub fn trim_in_place(&mut self) {
+        self.trim_end_in_place();
+        self.trim_start_in_place();
+    }
+
+    /// Removes leading whitespace in place.
+    ///
+    /// See [`String::trim_in_place`] for the definition of whitespace.
+    #[inline]
+    pub fn trim_start_in_place(&mut self) {
+        let trimmed = self.len() - self.trim_start().len();
+        self.0.drain(..trimmed);
+    }
+
+    /// Removes trailing whitespace in place.
+    ///
+    /// See [`String::trim_in_place`] for the definition of whitespace.
+    #[inline]
+    pub fn trim_end_in_place(&mut self) {
+        let len = self.trim_end().len();
+        self.0.truncate(len);
+    }
+
+    /// Pads the string on the left with `fill` so that it's at least `width` [`char`]s long,
+    /// aligning the contents to the right.
+    ///
+    /// Nothing is changed on failure.
+    #[inline]
+    pub fn pad_left(&mut self, width: usize, fill: char) -> crate::Result<()> {
+        let padding = width.saturating_sub(self.chars().count());
+
+        self.pad(padding, 0, fill)
+    }
+
+    /// Pads the string on the right with `fill` so that it's at least `width` [`char`]s long,
+    /// aligning the contents to the left.
+    ///
+    /// Nothing is changed on failure.
+    #[inline]
+    pub fn pad_right(&mut self, width: usize, fill: char) -> crate::Result<()> {
+        let padding = width.saturating_sub(self.chars().count());
+
+        self.pad(0, padding, fill)
+    }
+
+    /// Pads the string on both sides with `fill` so that it's at least `width` [`char`]s long,
+    /// centering the contents.
+    ///
+    /// If the padding can't be split evenly, the extra `fill` goes on the right. Nothing is
+    /// changed on failure.
+    #[inline]
+    pub fn center(&mut self, width: usize, fill: char) -> crate::Result<()> {
+        let padding = width.saturating_sub(self.chars().count());
+
+        self.pad(padding / 2, padding - padding / 2, fill)
+    }
+
+    // Adds `left` and `right` copies of `fill` around the contents.
+    fn pad(&mut self, left: usize, right: usize, fill: char) -> crate::Result<()> {
+        let mut buf = [0; 4];
+        let fill = fill.encode_utf8(&mut buf).as_bytes();
+        self.try_reserve((left + right).saturating_mul(fill.len()))?;
+
+        let mut bytes = self.bytes_mut();
+        for _ in 0..left + right {
+            // We just made sure it fits.
+            let _ = bytes.extend_from_slice(fill);
+        }
+        // Move the left padding from the end to the start.
+        bytes.rotate_right(left * fill.len());
+
+        // The contents are still valid UTF-8.
+        bytes.commit()
+    }
+
+    /// Removes `prefix` from the start of the string in place, if present.
+    ///
+    /// Returns `true` if the prefix was removed.
+    #[inline]
+    pub fn strip_prefix_in_place(&mut self, prefix: &str) -> bool {
+        if !self.starts_with(prefix) {
+            return false;
+        }
+        self.0.drain(..prefix.len());
+
+        true
+    }
+
+    /// Removes `suffix` from the end of the string in place, if present.
+    ///
+    /// Returns `true` if the suffix was removed.
+    #[inline]
+    pub fn strip_suffix_in_place(&mut self, suffix: &str) -> bool {
+        if !self.ends_with(suffix) {
+            return false;
+        }
+        let len = self.len() - suffix.len();
+        self.0.truncate(len);
+
+        true
+    }
+
+    /// Splits the string by `sep` and collects the parts into owned `String`s.
+    ///
+    /// Fails with [`crate::Error::BufferOverflow`] if there are more than `K` parts or any part is
+    /// longer than `M` bytes.
+    #[inline]
+    pub fn split_collect<const M: usize, const K: usize>(
+        &self,
+        sep: &str,
+    ) -> crate::Result<Vec<String<M>, K>> {
+        collect_parts(self.split(sep))
+    }
+
+    /// Splits the string by whitespace and collects the parts into owned `String`s.
+    ///
+    /// Fails with [`crate::Error::BufferOverflow`] if there are more than `K` parts or any part is
+    /// longer than `M` bytes.
+    #[inline]
+    pub fn split_whitespace_collect<const M: usize, const K: usize>(
+        &self,
+    ) -> crate::Result<Vec<String<M>, K>> {
+        collect_parts(self.split_whitespace())
+    }
+
+    /// Returns an iterator over the lines of the string wrapped at whitespace to at most `width`
+    /// [`char`]s.
+    ///
+    /// Existing line breaks are kept and the whitespace around the breaks is dropped. Words longer
+    /// than `width` are broken at `width`. A `width` of `0` is treated as `1`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mayheap::String;
+    ///
+    /// let s = String::<64>::try_from("The quick brown fox\n\njumps over the lazy dog").unwrap();
+    /// let mut lines = s.wrap_lines(10);
+    /// assert_eq!(lines.next(), Some("The quick"));
+    /// assert_eq!(lines.next(), Some("brown fox"));
+    /// assert_eq!(lines.next(), Some(""));
+    /// assert_eq!(lines.next(), Some("jumps over"));
+    /// assert_eq!(lines.next(), Some("the lazy"));
+    /// assert_eq!(lines.next(), Some("dog"));
+    /// assert_eq!(lines.next(), None);
+    /// ```
+    #[inline]
+    pub fn wrap_lines(&self, width: usize) -> WrapLines<'_> {
+        WrapLines {
+            lines: self.lines(),
+            rest: None,
+            width: width.max(1),
+        }
+    }
+
+    /// Wraps the string at whitespace to at most `width` [`char`]s and appends the lines to
+    /// `lines`, as owned `String`s.
+    ///
+    /// See [`String::wrap_lines`] for the details of the wrapping. Fails with
+    /// [`crate::Error::BufferOverflow`] if the lines don't fit in `lines` or any line is longer
+    /// than `M` bytes. Nothing is appended on failure.
+    #[inline]
+    pub fn wrap_into<const M: usize, const K: usize>(
+        &self,
+        width: usize,
+        lines: &mut Vec<String<M>, K>,
+    ) -> crate::Result<()> {
+        let len = lines.len();
+        for line in self.wrap_lines(width) {
+            let res = String::try_from(line).and_then(|line| {
+                lines
+                    .push(line)
+                    .map_err(|_| crate::Error::overflow::<Vec<String<M>, K>>(lines.len(), 1, K))
+            });
+            if let Err(e) = res {
+                lines.truncate(len);
+
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Encodes the string as UTF-16 and appends the result to `vec`.
+    ///
+    /// Fails with [`crate::Error::BufferOverflow`] if the result doesn't fit in `vec`. Nothing is
+    /// appended on failure.
+    #[inline]
+    pub fn encode_utf16_into<const M: usize>(&self, vec: &mut Vec<u16, M>) -> crate::Result<()> {
+        let len = vec.len();
+        for unit in self.encode_utf16() {
+            if vec.push(unit).is_err() {
+                vec.truncate(len);
+
+                return Err(crate::Error::overflow::<Vec<u16, M>>(
+                    len,
+                    self.encode_utf16().count(),
+                    M,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Encodes the string as UTF-16 into a new [`Vec`].
+    ///
+    /// Fails with [`crate::Error::BufferOverflow`] if the result doesn't fit in `M` code units.
+    #[inline]
+    pub fn to_utf16<const M: usize>(&self) -> crate::Result<Vec<u16, M>> {
+        let mut vec = Vec::new();
+        self.encode_utf16_into(&mut vec)?;
+
+        Ok(vec)
+    }
+
+    /// Clones the `String`, without aborting on allocation failure.
+    ///
+    /// When `alloc` feature is enabled, [`crate::Error::OutOfMemory`] is returned if allocation
+    /// fails. Otherwise, this can not fail.
+    #[inline]
+    pub fn try_clone(&self) -> crate::Result<Self> {
+        #[cfg(alloc_backend)]
+        {
+            let mut inner = Inner::new();
+            inner.try_reserve_exact(self.len().max(N))?;
+            inner.push_str(self);
+
+            Ok(Self(inner))
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            Ok(self.clone())
+        }
+    }
+
+    /// Converts the `String` into one with a capacity of `M` bytes.
+    ///
+    /// Fails with [`crate::Error::BufferOverflow`] if the current length exceeds `M`. If `M >= N`,
+    /// use [`String::grow_capacity`] instead, which can not fail.
+    #[inline]
+    pub fn recapacity<const M: usize>(self) -> crate::Result<String<M>> {
+        let bytes = self.into_bytes().recapacity()?;
+
+        // SAFETY: The bytes come from a `String` so they're valid UTF-8.
+        Ok(unsafe { String::from_utf8_unchecked(bytes) })
+    }
+
+    /// Converts the `String` into one with a capacity of `M` bytes, where `M` must be at least `N`.
+    ///
+    /// This is checked at compile-time.
+    #[inline]
+    pub fn grow_capacity<const M: usize>(self) -> String<M> {
+        let bytes = self.into_bytes().grow_capacity();
+
+        // SAFETY: The bytes come from a `String` so they're valid UTF-8.
+        unsafe { String::from_utf8_unchecked(bytes) }
+    }
+
+    // Reserves the capacity for `additional` more bytes in multiples of `N`, if the `linear-growth`
+    // feature is enabled. Otherwise, the growth is left to the inner type.
+    #[cfg(alloc_backend)]
+    #[inline]
+    fn grow(&mut self, additional: usize) {
+        #[cfg(feature = "linear-growth")]
+        if let Some(additional) =
+            crate::vec::linear_growth::<N>(self.len(), self.0.capacity(), additional)
+        {
+            self.0.reserve_exact(additional);
+        }
+        #[cfg(not(feature = "linear-growth"))]
+        let _ = additional;
+    }
+
+    // Records the length (including the `requested` elements that didn't fit, on overflow) for the
+    // instrumentation, and logs the overflows.
+    #[inline]
+    fn record(&self, overflow: Option<usize>) {
+        #[cfg(feature = "instrument")]
+        crate::instrument::record::<Self>(crate::instrument::Kind::String, self.len(), overflow);
+        #[cfg(any(feature = "log", feature = "defmt"))]
+        if let Some(requested) = overflow {
+            crate::error::log_overflow::<Self>(self.len(), requested, N);
+        }
+        #[cfg(not(any(feature = "instrument", feature = "log", feature = "defmt")))]
+        let _ = overflow;
+    }
+}
+
+/// Validates `bytes` as UTF-8, using SIMD instructions if the `simdutf8` feature is enabled.
+#[inline]
+fn validate_utf8(bytes: &[u8]) -> Result<&str, str::Utf8Error> {
+    #[cfg(feature = "simdutf8")]
+    if let Ok(s) = simdutf8::basic::from_utf8(bytes) {
+        return Ok(s);
+    }
+
+    // Either the `simdutf8` feature is disabled or the validation failed, in which case we let
+    // `core` figure out the details of the error.
+    str::from_utf8(bytes)
+}
+
+/// Appends `chars` to `target`, appending nothing on failure.
+#[cfg(feature = "unicode-normalization")]
+fn push_chars<const M: usize>(
+    target: &mut String<M>,
+    mut chars: impl Iterator<Item = char>,
+) -> crate::Result<()> {
+    let len = target.len();
+    let res = chars.try_for_each(|c| target.push(c));
+    if res.is_err() {
+        target.0.truncate(len);
+    }
+
+    res
+}
+
+fn collect_parts<'a, const M: usize, const K: usize>(
+    parts: impl Iterator<Item = &'a str>,
+) -> crate::Result<Vec<String<M>, K>> {
+    let mut v = Vec::new();
+    for part in parts {
+        v.push(String::try_from(part)?)
+            .map_err(|_| crate::Error::overflow::<Vec<String<M>, K>>(K, 1, K))?;
+    }
+
+    Ok(v)
+}
+
+impl<const N: usize> Default for String<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, const N: usize> TryFrom<&'a str> for String<N> {
+    type Error = crate::Error;
+    #[inline]
+    fn try_from(s: &'a str) -> Result<Self, Self::Error> {
+        <Self as core::str::FromStr>::from_str(s)
+    }
+}
+
+impl<const N: usize> str::FromStr for String<N> {
+    type Err = crate::Error;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Inner::from_str(s)
+            .map(Self)
+            .map_err(|_| crate::Error::overflow::<Self>(0, s.len(), N))
+    }
+}
+
+impl<const N: usize> From<String<N>> for Vec<u8, N> {
+    #[inline]
+    fn from(s: String<N>) -> Self {
+        s.into_bytes()
+    }
+}
+
+impl<const N: usize> From<String<N>> for Inner<N> {
+    #[inline]
+    fn from(s: String<N>) -> Self {
+        s.0
+    }
+}
+
+impl<const N: usize> From<Inner<N>> for String<N> {
+    #[inline]
+    fn from(s: Inner<N>) -> Self {
+        Self(s)
+    }
+}
+
+#[cfg(not(alloc_backend))]
+impl<const N: usize> crate::convert::AsHeapless for String<N> {
+    type Heapless = heapless::String<N>;
+
+    #[inline]
+    fn as_heapless(&self) -> &Self::Heapless {
+        &self.0
+    }
+
+    #[inline]
+    fn as_heapless_mut(&mut self) -> &mut Self::Heapless {
+        &mut self.0
+    }
+
+    #[inline]
+    fn into_heapless(self) -> Self::Heapless {
+        self.0
+    }
+
+    #[inline]
+    fn from_heapless(heapless: Self::Heapless) -> Self {
+        Self(heapless)
+    }
+
+    #[inline]
+    fn from_heapless_ref(heapless: &Self::Heapless) -> &Self {
+        // SAFETY: `String` is `#[repr(transparent)]` over `Self::Heapless`.
+        unsafe { &*(heapless as *const Self::Heapless as *const Self) }
+    }
+
+    #[inline]
+    fn from_heapless_mut(heapless: &mut Self::Heapless) -> &mut Self {
+        // SAFETY: `String` is `#[repr(transparent)]` over `Self::Heapless`.
+        unsafe { &mut *(heapless as *mut Self::Heapless as *mut Self) }
+    }
+}
+
+#[cfg(alloc_backend)]
+impl<const N: usize> crate::convert::AsAlloc for String<N> {
+    type Alloc = crate::reexports::alloc::string::String;
+
+    #[inline]
+    fn as_alloc(&self) -> &Self::Alloc {
+        &self.0
+    }
+
+    #[inline]
+    fn as_alloc_mut(&mut self) -> &mut Self::Alloc {
+        &mut self.0
+    }
+
+    #[inline]
+    fn into_alloc(self) -> Self::Alloc {
+        self.0
+    }
+
+    #[inline]
+    fn from_alloc(alloc: Self::Alloc) -> Self {
+        Self(alloc)
+    }
+
+    #[inline]
+    fn from_alloc_ref(alloc: &Self::Alloc) -> &Self {
+        // SAFETY: `String` is `#[repr(transparent)]` over `Self::Alloc`.
+        unsafe { &*(alloc as *const Self::Alloc as *const Self) }
+    }
+
+    #[inline]
+    fn from_alloc_mut(alloc: &mut Self::Alloc) -> &mut Self {
+        // SAFETY: `String` is `#[repr(transparent)]` over `Self::Alloc`.
+        unsafe { &mut *(alloc as *mut Self::Alloc as *mut Self) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> crate::convert::TryIntoStd for String<N> {
+    type Std = crate::reexports::alloc::string::String;
+
+    #[inline]
+    fn try_into_std(self) -> crate::Result<Self::Std> {
+        #[cfg(alloc_backend)]
+        {
+            Ok(self.0)
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            let mut s = Self::Std::new();
+            s.try_reserve_exact(self.len())?;
+            s.push_str(&self);
+
+            Ok(s)
+        }
+    }
+}
+
+// With both features enabled, `alloc` is the backend so we need explicit conversions from/to
+// `heapless::String`.
+#[cfg(all(alloc_backend, feature = "heapless"))]
+impl<const N: usize, const M: usize> From<heapless::String<M>> for String<N> {
+    #[inline]
+    fn from(s: heapless::String<M>) -> Self {
+        let mut string = Self::new();
+        string.0.push_str(&s);
+
+        string
+    }
+}
+
+#[cfg(all(alloc_backend, feature = "heapless"))]
+impl<const N: usize, const M: usize> TryFrom<String<N>> for heapless::String<M> {
+    type Error = crate::Error;
+
+    #[inline]
+    fn try_from(s: String<N>) -> Result<Self, Self::Error> {
+        heapless::String::try_from(s.as_str())
+            .map_err(|_| crate::Error::overflow::<Self>(0, s.len(), M))
+    }
+}
+
+// With `prefer-heapless`, `heapless` is the backend even with `alloc` enabled, so we need an
+// explicit conversion from `alloc::string::String`. See `TryIntoStd` for the other way around.
+#[cfg(all(not(alloc_backend), feature = "alloc"))]
+impl<const N: usize> TryFrom<crate::reexports::alloc::string::String> for String<N> {
+    type Error = crate::Error;
+
+    #[inline]
+    fn try_from(s: crate::reexports::alloc::string::String) -> Result<Self, Self::Error> {
+        Self::try_from(s.as_str())
+    }
+}
+
+#[cfg(not(feature = "panic-free"))]
+impl<const N: usize> FromIterator<char> for String<N> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = char>>(iter: T) -> Self {
+        match Self::try_from_iter(iter) {
+            Ok(string) => string,
+            Err(e) => panic!("{e}"),
+        }
+    }
+}
+
+#[cfg(not(feature = "panic-free"))]
+impl<'a, const N: usize> FromIterator<&'a char> for String<N> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = &'a char>>(iter: T) -> Self {
+        match Self::try_from_iter(iter) {
+            Ok(string) => string,
+            Err(e) => panic!("{e}"),
+        }
+    }
+}
+
+#[cfg(not(feature = "panic-free"))]
+impl<'a, const N: usize> FromIterator<&'a str> for String<N> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = &'a str>>(iter: T) -> Self {
+        match Self::try_from_iter(iter) {
+            Ok(string) => string,
+            Err(e) => panic!("{e}"),
+        }
+    }
+}
+
+impl<const N: usize> IntoIterator for String<N> {
+    type Item = char;
+    type IntoIter = IntoChars<N>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        let back = self.len();
+
+        IntoChars {
+            string: self,
+            front: 0,
+            back,
+        }
+    }
+}
+
+impl<const N: usize> fmt::Display for String<N> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<const N: usize> hash::Hash for String<N> {
+    #[inline]
+    fn hash<H: hash::Hasher>(&self, hasher: &mut H) {
+        self.0.hash(hasher)
+    }
+}
+
+impl<const N: usize> fmt::Write for String<N> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> Result<(), fmt::Error> {
+        self.push_str(s).map_err(|_| fmt::Error)
+    }
+
+    #[inline]
+    fn write_char(&mut self, c: char) -> Result<(), fmt::Error> {
+        self.push(c).map_err(|_| fmt::Error)
+    }
+}
+
+impl<const N: usize> ops::Deref for String<N> {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> ops::DerefMut for String<N> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut str {
+        self.as_mut_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for String<N> {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for String<N> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<const N1: usize, const N2: usize> PartialEq<String<N2>> for String<N1> {
+    #[inline]
+    fn eq(&self, rhs: &String<N2>) -> bool {
+        self.0.eq(&rhs.0)
+    }
+}
+
+// String<N> == str
 impl<const N: usize> PartialEq<str> for String<N> {
     #[inline]
     fn eq(&self, other: &str) -> bool {
@@ -332,6 +1458,42 @@ impl<const N: usize> PartialEq<String<N>> for &str {
     }
 }
 
+// String<N> == alloc::string::String
+#[cfg(feature = "alloc")]
+impl<const N: usize> PartialEq<crate::reexports::alloc::string::String> for String<N> {
+    #[inline]
+    fn eq(&self, other: &crate::reexports::alloc::string::String) -> bool {
+        self.as_str().eq(other.as_str())
+    }
+}
+
+// alloc::string::String == String<N>
+#[cfg(feature = "alloc")]
+impl<const N: usize> PartialEq<String<N>> for crate::reexports::alloc::string::String {
+    #[inline]
+    fn eq(&self, other: &String<N>) -> bool {
+        self.as_str().eq(other.as_str())
+    }
+}
+
+// String<N> == Cow<'_, str>
+#[cfg(feature = "alloc")]
+impl<const N: usize> PartialEq<crate::reexports::alloc::borrow::Cow<'_, str>> for String<N> {
+    #[inline]
+    fn eq(&self, other: &crate::reexports::alloc::borrow::Cow<'_, str>) -> bool {
+        self.as_str().eq(&**other)
+    }
+}
+
+// Cow<'_, str> == String<N>
+#[cfg(feature = "alloc")]
+impl<const N: usize> PartialEq<String<N>> for crate::reexports::alloc::borrow::Cow<'_, str> {
+    #[inline]
+    fn eq(&self, other: &String<N>) -> bool {
+        (**self).eq(other.as_str())
+    }
+}
+
 impl<const N: usize> Eq for String<N> {}
 
 impl<const N1: usize, const N2: usize> PartialOrd<String<N2>> for String<N1> {
@@ -354,17 +1516,20 @@ macro_rules! impl_try_from_num {
             type Error = crate::Error;
             #[inline]
             fn try_from(s: $num) -> Result<Self, Self::Error> {
-                #[cfg(feature = "alloc")]
+                #[cfg(alloc_backend)]
                 {
                     Ok(Self(crate::reexports::alloc::string::ToString::to_string(
                         &s,
                     )))
                 }
-                #[cfg(not(feature = "alloc"))]
+                #[cfg(not(alloc_backend))]
                 {
-                    Inner::try_from(s)
-                        .map(Self)
-                        .map_err(|_| crate::Error::BufferOverflow)
+                    Inner::try_from(s).map(Self).map_err(|_| {
+                        let requested = heapless::String::<$size>::try_from(s)
+                            .map_or($size, |digits| digits.len());
+
+                        crate::Error::overflow::<Self>(0, requested, N)
+                    })
                 }
             }
         }
@@ -381,6 +1546,364 @@ impl_try_from_num!(u16, 5);
 impl_try_from_num!(u32, 10);
 impl_try_from_num!(u64, 20);
 
+/// An item that can be appended to a [`String`].
+///
+/// This is implemented for [`char`], `&char` and `&str`. It can not be implemented outside of this
+/// crate.
+pub trait Appendable: sealed::Sealed {
+    /// Appends `self` to the end of `string`.
+    fn append_to<const N: usize>(self, string: &mut String<N>) -> crate::Result<()>;
+}
+
+impl Appendable for char {
+    #[inline]
+    fn append_to<const N: usize>(self, string: &mut String<N>) -> crate::Result<()> {
+        string.push(self)
+    }
+}
+
+impl Appendable for &char {
+    #[inline]
+    fn append_to<const N: usize>(self, string: &mut String<N>) -> crate::Result<()> {
+        string.push(*self)
+    }
+}
+
+impl Appendable for &str {
+    #[inline]
+    fn append_to<const N: usize>(self, string: &mut String<N>) -> crate::Result<()> {
+        string.push_str(self)
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for char {}
+    impl Sealed for &char {}
+    impl Sealed for &str {}
+}
+
+/// Creates a [`String`] from a string literal.
+///
+/// The capacity of the `String` is inferred from the context and if the literal doesn't fit in it,
+/// the compilation fails.
+///
+/// ```
+/// use mayheap::{string, String};
+///
+/// let s: String<8> = string!("hello");
+/// assert_eq!(s, "hello");
+/// ```
+///
+/// ```compile_fail
+/// use mayheap::{string, String};
+///
+/// let s: String<4> = string!("hello");
+/// ```
+#[macro_export]
+macro_rules! string {
+    ($s:expr) => {{
+        const S: &str = $s;
+
+        $crate::String::__from_str_with_len::<{ S.len() }>(S)
+    }};
+}
+
+/// Creates a [`String`] using interpolation of runtime expressions.
+///
+/// This is a fallible version of `alloc::format!`, built on [`String::try_write_fmt`]. The capacity
+/// of the `String` can be specified as the first argument, followed by a `;`, or it can be inferred
+/// from the context.
+///
+/// ```
+/// use mayheap::{try_format, String};
+///
+/// let s: String<16> = try_format!("{}-{}", "led", 3).unwrap();
+/// assert_eq!(s, "led-3");
+///
+/// let s = try_format!(16; "{:04}", 42).unwrap();
+/// assert_eq!(s, "0042");
+/// ```
+#[macro_export]
+macro_rules! try_format {
+    ($n:expr; $($arg:tt)*) => {{
+        let mut s = $crate::String::<$n>::new();
+        s.try_write_fmt(::core::format_args!($($arg)*)).map(|()| s)
+    }};
+    ($($arg:tt)*) => {{
+        let mut s = $crate::String::new();
+        s.try_write_fmt(::core::format_args!($($arg)*)).map(|()| s)
+    }};
+}
+
+/// The error returned by [`String::from_utf8`].
+///
+/// Besides the UTF-8 error, this gives back the bytes that were being converted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FromUtf8Error<const N: usize> {
+    bytes: Vec<u8, N>,
+    error: str::Utf8Error,
+}
+
+impl<const N: usize> FromUtf8Error<N> {
+    /// The bytes that were being converted.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Converts into the bytes that were being converted.
+    #[inline]
+    pub fn into_bytes(self) -> Vec<u8, N> {
+        self.bytes
+    }
+
+    /// The details of the conversion failure.
+    #[inline]
+    pub fn utf8_error(&self) -> str::Utf8Error {
+        self.error
+    }
+}
+
+impl<const N: usize> fmt::Display for FromUtf8Error<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<const N: usize> core::error::Error for FromUtf8Error<N> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl<const N: usize> From<FromUtf8Error<N>> for crate::Error {
+    fn from(err: FromUtf8Error<N>) -> Self {
+        crate::Error::Utf8Error(err.error)
+    }
+}
+
+/// A guard giving mutable access to the bytes of a [`String`].
+///
+/// This struct is created by [`String::bytes_mut`]. See its documentation for more.
+#[derive(Debug)]
+pub struct BytesMut<'a, const N: usize> {
+    string: &'a mut String<N>,
+    // Only `None` after the bytes have been written back to the `String`.
+    bytes: Option<Vec<u8, N>>,
+}
+
+impl<const N: usize> BytesMut<'_, N> {
+    /// Writes the bytes back to the `String`, validating them as UTF-8.
+    ///
+    /// If the bytes are not valid UTF-8, the `String` is truncated to the longest valid prefix and
+    /// [`crate::Error::Utf8Error`] is returned.
+    #[inline]
+    pub fn commit(mut self) -> crate::Result<()> {
+        self.write_back()
+    }
+
+    fn write_back(&mut self) -> crate::Result<()> {
+        let Some(mut bytes) = self.bytes.take() else {
+            return Ok(());
+        };
+        let res = validate_utf8(&bytes).map(|_| ()).map_err(|e| {
+            bytes.truncate(e.valid_up_to());
+
+            e.into()
+        });
+        // SAFETY: Either the bytes are valid UTF-8 or we truncated them to the valid part.
+        *self.string = unsafe { String::from_utf8_unchecked(bytes) };
+
+        res
+    }
+}
+
+impl<const N: usize> ops::Deref for BytesMut<'_, N> {
+    type Target = Vec<u8, N>;
+
+    #[inline]
+    fn deref(&self) -> &Vec<u8, N> {
+        match &self.bytes {
+            Some(bytes) => bytes,
+            None => unreachable!(),
+        }
+    }
+}
+
+impl<const N: usize> ops::DerefMut for BytesMut<'_, N> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Vec<u8, N> {
+        match &mut self.bytes {
+            Some(bytes) => bytes,
+            None => unreachable!(),
+        }
+    }
+}
+
+impl<const N: usize> Drop for BytesMut<'_, N> {
+    #[inline]
+    fn drop(&mut self) {
+        let _ = self.write_back();
+    }
+}
+
+/// A draining iterator for [`String`].
+///
+/// This struct is created by [`String::drain`].
+#[cfg(not(feature = "panic-free"))]
+#[derive(Debug)]
+pub struct Drain<'a> {
+    #[cfg(alloc_backend)]
+    iter: crate::reexports::alloc::string::Drain<'a>,
+    #[cfg(not(alloc_backend))]
+    iter: heapless::string::Drain<'a, usize>,
+}
+
+#[cfg(not(feature = "panic-free"))]
+impl Drain<'_> {
+    /// Returns the remaining (sub)string of this iterator as a slice.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        self.iter.as_str()
+    }
+}
+
+#[cfg(not(feature = "panic-free"))]
+impl Iterator for Drain<'_> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+#[cfg(not(feature = "panic-free"))]
+impl DoubleEndedIterator for Drain<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<char> {
+        self.iter.next_back()
+    }
+}
+
+#[cfg(not(feature = "panic-free"))]
+impl core::iter::FusedIterator for Drain<'_> {}
+
+/// An iterator that moves the [`char`]s out of a [`String`].
+///
+/// This struct is created by the `into_iter` method on [`String`].
+///
+/// # Example
+///
+/// ```
+/// use mayheap::String;
+///
+/// let mut chars = String::<8>::try_from("añb").unwrap().into_iter();
+/// assert_eq!(chars.next(), Some('a'));
+/// assert_eq!(chars.next_back(), Some('b'));
+/// assert_eq!(chars.as_str(), "ñ");
+/// ```
+#[derive(Debug, Clone)]
+pub struct IntoChars<const N: usize> {
+    string: String<N>,
+    // The byte range of the remaining characters.
+    front: usize,
+    back: usize,
+}
+
+impl<const N: usize> IntoChars<N> {
+    /// Returns the remaining (sub)string of this iterator as a slice.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.string[self.front..self.back]
+    }
+}
+
+impl<const N: usize> Iterator for IntoChars<N> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        let c = self.as_str().chars().next()?;
+        self.front += c.len_utf8();
+
+        Some(c)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+
+        (len.div_ceil(4), Some(len))
+    }
+}
+
+impl<const N: usize> DoubleEndedIterator for IntoChars<N> {
+    #[inline]
+    fn next_back(&mut self) -> Option<char> {
+        let c = self.as_str().chars().next_back()?;
+        self.back -= c.len_utf8();
+
+        Some(c)
+    }
+}
+
+impl<const N: usize> core::iter::FusedIterator for IntoChars<N> {}
+
+/// An iterator over the lines of a [`String`] wrapped to a given width.
+///
+/// This struct is created by [`String::wrap_lines`]. See its documentation for more.
+#[derive(Debug, Clone)]
+pub struct WrapLines<'a> {
+    lines: str::Lines<'a>,
+    // The rest of the current line, if any is left.
+    rest: Option<&'a str>,
+    width: usize,
+}
+
+impl<'a> Iterator for WrapLines<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let s = match self.rest.take() {
+            Some(rest) => rest,
+            None => {
+                let line = self.lines.next()?.trim_start();
+                if line.is_empty() {
+                    return Some(line);
+                }
+
+                line
+            }
+        };
+
+        let mut split = None;
+        for (count, (i, c)) in s.char_indices().enumerate() {
+            if c.is_whitespace() {
+                split = Some(i);
+            } else if count >= self.width {
+                // Break at the last whitespace or, if there's none, in the middle of the word.
+                let i = split.unwrap_or(i);
+                let rest = s[i..].trim_start();
+                self.rest = (!rest.is_empty()).then_some(rest);
+
+                return Some(s[..i].trim_end());
+            }
+        }
+
+        Some(s.trim_end())
+    }
+}
+
+impl core::iter::FusedIterator for WrapLines<'_> {}
+
 #[cfg(feature = "serde")]
 impl<'de, const N: usize> serde_core::Deserialize<'de> for String<N> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -391,6 +1914,87 @@ impl<'de, const N: usize> serde_core::Deserialize<'de> for String<N> {
     }
 }
 
+/// Deserializes a string into an existing [`String`], reusing its storage.
+///
+/// The string is cleared first, and is left empty on failure. This is useful for parsing many
+/// messages in a row without constructing a new string for each.
+///
+/// # Example
+///
+/// ```
+/// use mayheap::{string::StringSeed, String};
+/// use serde_core::de::DeserializeSeed;
+///
+/// let mut name = String::<8>::new();
+/// for json in [r#""alice""#, r#""bob""#] {
+///     let mut de = serde_json::Deserializer::from_str(json);
+///     StringSeed::new(&mut name).deserialize(&mut de).unwrap();
+/// }
+/// assert_eq!(name, "bob");
+/// ```
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub struct StringSeed<'a, const N: usize>(&'a mut String<N>);
+
+#[cfg(feature = "serde")]
+impl<'a, const N: usize> StringSeed<'a, N> {
+    /// Creates a new seed, deserializing into `string`.
+    #[inline]
+    pub fn new(string: &'a mut String<N>) -> Self {
+        Self(string)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde_core::de::DeserializeSeed<'de> for StringSeed<'_, N> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: serde_core::Deserializer<'de>,
+    {
+        // Clear even if the deserializer fails before visiting.
+        self.0.clear();
+        deserializer.deserialize_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde_core::de::Visitor<'de> for StringSeed<'_, N> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(alloc_backend)]
+        {
+            formatter.write_str("a string")
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            write!(formatter, "a string of at most {N} bytes")
+        }
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<(), E>
+    where
+        E: serde_core::de::Error,
+    {
+        self.0.clear();
+        self.0
+            .push_str(v)
+            .map_err(|_| E::invalid_length(v.len(), &self))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<(), E>
+    where
+        E: serde_core::de::Error,
+    {
+        let s = str::from_utf8(v)
+            .map_err(|_| E::invalid_value(serde_core::de::Unexpected::Bytes(v), &self))?;
+
+        self.visit_str(s)
+    }
+}
+
 #[cfg(feature = "serde")]
 impl<const N: usize> serde_core::Serialize for String<N> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -400,3 +2004,81 @@ impl<const N: usize> serde_core::Serialize for String<N> {
         self.0.serialize(serializer)
     }
 }
+
+#[cfg(feature = "musli")]
+impl<M, const N: usize> musli::Encode<M> for String<N> {
+    type Encode = str;
+
+    #[inline]
+    fn encode<E>(&self, encoder: E) -> Result<(), E::Error>
+    where
+        E: musli::Encoder<Mode = M>,
+    {
+        encoder.encode_string(self.as_str())
+    }
+
+    #[inline]
+    fn as_encode(&self) -> &Self::Encode {
+        self.as_str()
+    }
+}
+
+/// Fails with [`crate::Error::BufferOverflow`] if the string doesn't fit in the capacity.
+#[cfg(feature = "musli")]
+impl<'de, M, A, const N: usize> musli::Decode<'de, M, A> for String<N>
+where
+    A: musli::Allocator,
+{
+    #[inline]
+    fn decode<D>(decoder: D) -> Result<Self, D::Error>
+    where
+        D: musli::Decoder<'de, Mode = M, Allocator = A>,
+    {
+        use musli::Context;
+
+        let cx = decoder.cx();
+        decoder.decode_unsized(|s: &str| Self::try_from(s).map_err(|e| cx.custom(e)))
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl<const N: usize> ufmt::uWrite for String<N> {
+    type Error = crate::Error;
+
+    #[inline]
+    fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+        self.push_str(s)
+    }
+
+    #[inline]
+    fn write_char(&mut self, c: char) -> Result<(), Self::Error> {
+        self.push(c)
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl<const N: usize> ufmt::uDisplay for String<N> {
+    #[inline]
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        <str as ufmt::uDisplay>::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl<const N: usize> ufmt::uDebug for String<N> {
+    #[inline]
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        // `ufmt` doesn't implement `uDebug` for `str` so we've to do the escaping ourselves.
+        f.write_char('"')?;
+        for c in self.chars().flat_map(char::escape_debug) {
+            f.write_char(c)?;
+        }
+        f.write_char('"')
+    }
+}
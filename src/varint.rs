@@ -0,0 +1,80 @@
+//! Variable-length integer (unsigned LEB128) decoding, as used by protobuf.
+//!
+//! Each byte holds 7 bits of the value, least significant group first, with the most significant
+//! bit set on all bytes but the last. Encoding is done through [`crate::Vec::put_varint_u32`] and
+//! [`crate::Vec::put_varint_u64`].
+//!
+//! # Example
+//!
+//! ```
+//! use mayheap::{varint, Vec};
+//!
+//! let mut buf = Vec::<u8, 8>::new();
+//! buf.put_varint_u32(300).unwrap();
+//! buf.put_varint_u64(1).unwrap();
+//! assert_eq!(buf, [0xac, 0x02, 0x01]);
+//!
+//! let (value, len) = varint::decode_u32(&buf).unwrap();
+//! assert_eq!((value, len), (300, 2));
+//! assert_eq!(varint::decode_u64(&buf[len..]), Some((1, 1)));
+//! // Truncated.
+//! assert_eq!(varint::decode_u32(&buf[..1]), None);
+//! ```
+
+/// The maximum length of an encoded `u64`.
+pub(crate) const MAX_LEN: usize = 10;
+
+/// Decodes a `u32` from the start of `bytes`, returning it along with the number of bytes read.
+///
+/// Returns `None` if `bytes` ends before the last byte of the value, or if the value doesn't fit
+/// in a `u32`.
+#[inline]
+pub fn decode_u32(bytes: &[u8]) -> Option<(u32, usize)> {
+    decode(bytes, u32::BITS).map(|(value, len)| (value as u32, len))
+}
+
+/// Decodes a `u64` from the start of `bytes`, returning it along with the number of bytes read.
+///
+/// Returns `None` if `bytes` ends before the last byte of the value, or if the value doesn't fit
+/// in a `u64`.
+#[inline]
+pub fn decode_u64(bytes: &[u8]) -> Option<(u64, usize)> {
+    decode(bytes, u64::BITS)
+}
+
+/// Encodes `value` into `buf`, returning the encoded bytes.
+pub(crate) fn encode(mut value: u64, buf: &mut [u8; MAX_LEN]) -> &[u8] {
+    let mut len = 0;
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf[len] = byte;
+
+            return &buf[..=len];
+        }
+        buf[len] = byte | 0x80;
+        len += 1;
+    }
+}
+
+fn decode(bytes: &[u8], bits: u32) -> Option<(u64, usize)> {
+    let mut value = 0;
+    for (i, &byte) in bytes.iter().enumerate() {
+        let shift = 7 * i as u32;
+        if shift >= bits {
+            return None;
+        }
+        let group = u64::from(byte & 0x7f);
+        // The last group may only use the remaining bits.
+        if bits - shift < 7 && group >> (bits - shift) != 0 {
+            return None;
+        }
+        value |= group << shift;
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+
+    None
+}
@@ -2,11 +2,11 @@
 
 //! Defines [`Vec`] and associated types.
 
-use core::{cmp::Ordering, fmt, hash, iter::FromIterator, ops, slice};
+use core::{cmp::Ordering, fmt, hash, mem::MaybeUninit, ops, slice};
 
-#[cfg(feature = "alloc")]
+#[cfg(alloc_backend)]
 pub(crate) type Inner<T, const N: usize> = crate::reexports::alloc::vec::Vec<T>;
-#[cfg(not(feature = "alloc"))]
+#[cfg(not(alloc_backend))]
 pub(crate) type Inner<T, const N: usize> = heapless::Vec<T, N>;
 
 /// A contiguous growable array type.
@@ -16,7 +16,11 @@ pub(crate) type Inner<T, const N: usize> = heapless::Vec<T, N>;
 /// When `heapless` feature is enabled, this is wrapper around `heapless::Vec`. Otherwise, this is
 /// a wrapper around `alloc::vec::Vec`, setting the initial capacity to `N`. All fallible
 /// operations are in reality infallible and all unsafe methods are safe in the latter case.
-#[derive(Clone, Debug)]
+///
+/// This is guaranteed to be `#[repr(transparent)]` over the type of the backend. See the
+/// [`crate::convert`] module for the conversions this allows.
+#[derive(Debug)]
+#[repr(transparent)]
 pub struct Vec<T, const N: usize>(Inner<T, N>);
 
 impl<T, const N: usize> Vec<T, N> {
@@ -27,11 +31,45 @@ impl<T, const N: usize> Vec<T, N> {
     #[inline]
     pub fn new() -> Self {
         let mut v = Inner::new();
-        #[cfg(feature = "alloc")]
+        #[cfg(alloc_backend)]
         v.reserve(N);
         Self(v)
     }
 
+    /// Returns `true` if `len` elements fit in a capacity of `N`.
+    ///
+    /// This only depends on `N`, regardless of the backend, so it can be used to check sizes that
+    /// must fit with `heapless` backend.
+    #[inline]
+    pub const fn fits(len: usize) -> bool {
+        len <= N
+    }
+
+    /// Asserts at compile-time that the capacity `N` is at least `M`.
+    ///
+    /// This only depends on `N`, regardless of the backend. A violation fails the build wherever
+    /// this function is instantiated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mayheap::Vec;
+    ///
+    /// const MAX_MESSAGE_LEN: usize = 48;
+    /// type Buffer = Vec::<u8, 64>;
+    ///
+    /// const _: () = Buffer::assert_capacity_at_least::<MAX_MESSAGE_LEN>();
+    /// const _: () = assert!(Buffer::fits(MAX_MESSAGE_LEN));
+    /// ```
+    ///
+    /// ```compile_fail
+    /// const _: () = mayheap::Vec::<u8, 64>::assert_capacity_at_least::<128>();
+    /// ```
+    #[inline]
+    pub const fn assert_capacity_at_least<const M: usize>() {
+        const { assert!(N >= M, "the capacity `N` must be at least `M`") };
+    }
+
     /// Constructs a new vector with a capacity of `N` and fills it with the provided slice.
     #[inline]
     pub fn from_slice(other: &[T]) -> crate::Result<Self>
@@ -44,6 +82,93 @@ impl<T, const N: usize> Vec<T, N> {
         Ok(v)
     }
 
+    /// Constructs a new vector with a capacity of `N`, filled with `n` clones of `value`.
+    ///
+    /// This is the equivalent of `vec![value; n]`. The buffer is filled in a single pass, which the
+    /// compiler turns into a `memset` for bytes. With `alloc` backend, a zero-filled buffer is
+    /// allocated directly as zeroed memory. Fails with [`crate::Error::BufferOverflow`] if `n`
+    /// exceeds `N`, with `heapless` backend.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mayheap::Vec;
+    ///
+    /// let buf = Vec::<u8, 16>::from_elem(0xff, 4).unwrap();
+    /// assert_eq!(buf, [0xff; 4]);
+    /// ```
+    #[inline]
+    pub fn from_elem(value: T, n: usize) -> crate::Result<Self>
+    where
+        T: Clone,
+    {
+        #[cfg(alloc_backend)]
+        {
+            let mut v = crate::reexports::alloc::vec![value; n];
+            v.reserve(N.saturating_sub(n));
+            let v = Self(v);
+            v.record(None);
+
+            Ok(v)
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            let mut v = Self::new();
+            if n > N {
+                v.record(Some(n));
+
+                return Err(crate::Error::overflow::<Self>(0, n, N));
+            }
+            if let Some((last, rest)) = v.spare_capacity_mut()[..n].split_last_mut() {
+                for slot in rest {
+                    slot.write(value.clone());
+                }
+                last.write(value);
+            }
+            // SAFETY: We just initialized the first `n` elements and `n` is within the capacity.
+            unsafe { v.set_len(n) };
+            v.record(None);
+
+            Ok(v)
+        }
+    }
+
+    /// Constructs a new vector with a capacity of `N`, initializing it with the provided array.
+    ///
+    /// Unlike the [`From`] implementation, the length of the array, `M`, can be less than `N`. If
+    /// it's greater, the compilation fails.
+    #[inline]
+    pub fn from_array<const M: usize>(array: [T; M]) -> Self {
+        const { assert!(M <= N, "`M` must be less than or equal to `N`") };
+
+        #[cfg(alloc_backend)]
+        {
+            let mut v = Inner::from(array);
+            v.reserve(N - M);
+
+            Self(v)
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            Self(Inner::from_array(array))
+        }
+    }
+
+    /// Creates a `Vec` from an iterator.
+    ///
+    /// Unlike the [`FromIterator`] implementation, this doesn't panic if the items don't fit in the
+    /// capacity but returns [`crate::Error::BufferOverflow`].
+    #[inline]
+    pub fn try_from_iter<I>(iter: I) -> crate::Result<Self>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut v = Self::new();
+        v.try_extend(iter)?;
+
+        Ok(v)
+    }
+
     /// Returns a raw pointer to the vector’s buffer.
     #[inline]
     pub fn as_ptr(&self) -> *const T {
@@ -66,11 +191,11 @@ impl<T, const N: usize> Vec<T, N> {
     /// of the vector is exactly `M`, otherwise returns `Err(self)`.
     #[inline]
     pub fn into_array<const M: usize>(self) -> Result<[T; M], Self> {
-        #[cfg(feature = "alloc")]
+        #[cfg(alloc_backend)]
         {
             self.0.try_into().map_err(Self)
         }
-        #[cfg(not(feature = "alloc"))]
+        #[cfg(not(alloc_backend))]
         {
             self.0.into_array().map_err(Self)
         }
@@ -86,12 +211,37 @@ impl<T, const N: usize> Vec<T, N> {
 
     /// the current capacity of the vector.
     ///
+    /// With `heapless` backend, this is always `N`. With `alloc` backend, this is at least `N`,
+    /// unless the vector was converted from an `alloc` vector with less capacity, and `usize::MAX`
+    /// for zero-sized types, which never need to allocate.
+    ///
     /// Note: Unlike, `heapless::vec::Vec::capacity`, this method is currently not `const`.
     #[inline]
     pub fn capacity(&self) -> usize {
         self.0.capacity()
     }
 
+    /// Tries to reserve capacity for at least `additional` more elements.
+    ///
+    /// When `alloc` feature is enabled, this fails with [`crate::Error::OutOfMemory`] if the
+    /// allocation fails. Otherwise, this fails with [`crate::Error::BufferOverflow`] if the
+    /// capacity is not enough.
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> crate::Result<()> {
+        #[cfg(alloc_backend)]
+        {
+            self.0.try_reserve(additional).map_err(Into::into)
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            if additional > N - self.len() {
+                return Err(crate::Error::overflow::<Self>(self.len(), additional, N));
+            }
+
+            Ok(())
+        }
+    }
+
     /// Clears the vector, removing all values.
     #[inline]
     pub fn clear(&mut self) {
@@ -99,12 +249,47 @@ impl<T, const N: usize> Vec<T, N> {
     }
 
     /// Extends the vec from an iterator.
+    #[cfg(not(feature = "panic-free"))]
     #[inline]
     pub fn extend<I>(&mut self, iter: I)
     where
         I: IntoIterator<Item = T>,
     {
-        self.0.extend(iter)
+        #[cfg(all(alloc_backend, not(feature = "linear-growth")))]
+        {
+            self.0.extend(iter);
+            self.record(None);
+        }
+        #[cfg(all(alloc_backend, feature = "linear-growth"))]
+        for item in iter {
+            let _ = self.push(item);
+        }
+        #[cfg(not(alloc_backend))]
+        if let Err(e) = self.try_extend(iter) {
+            panic!("{e}");
+        }
+    }
+
+    /// Appends all items from an iterator.
+    ///
+    /// Unlike the [`Extend`] implementation, this doesn't panic if the items don't fit in the
+    /// capacity but returns [`crate::Error::BufferOverflow`]. Nothing is appended on failure.
+    #[inline]
+    pub fn try_extend<I>(&mut self, iter: I) -> crate::Result<()>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let len = self.len();
+        for item in iter {
+            if self.push(item).is_err() {
+                let err = crate::Error::overflow::<Self>(self.len(), 1, N);
+                self.truncate(len);
+
+                return Err(err);
+            }
+        }
+
+        Ok(())
     }
 
     /// Clones and appends all elements in a slice to the `Vec`.
@@ -116,20 +301,43 @@ impl<T, const N: usize> Vec<T, N> {
     where
         T: Clone,
     {
-        #[cfg(feature = "alloc")]
+        #[cfg(alloc_backend)]
         {
+            self.grow(other.len());
             self.0.extend_from_slice(other);
+            self.record(None);
 
             Ok(())
         }
-        #[cfg(not(feature = "alloc"))]
+        #[cfg(not(alloc_backend))]
         {
-            self.0
-                .extend_from_slice(other)
-                .map_err(|_| crate::Error::BufferOverflow)
+            let len = self.len();
+            let res = self.0.extend_from_slice(other);
+            self.record(res.is_err().then_some(other.len()));
+
+            res.map_err(|_| crate::Error::overflow::<Self>(len, other.len(), N))
         }
     }
 
+    /// Clones and appends as many elements of a slice as fit in the capacity.
+    ///
+    /// Returns `true` if `other` had to be truncated, which can only happen when `heapless` feature
+    /// is enabled.
+    #[inline]
+    pub fn extend_from_slice_truncating(&mut self, other: &[T]) -> bool
+    where
+        T: Clone,
+    {
+        #[cfg(alloc_backend)]
+        let cut = other.len();
+        #[cfg(not(alloc_backend))]
+        let cut = other.len().min(N - self.len());
+        // We just made sure it fits.
+        let _ = self.extend_from_slice(&other[..cut]);
+
+        cut < other.len()
+    }
+
     /// Removes the last element from a vector and returns it, or `None` if it's empty
     #[inline]
     pub fn pop(&mut self) -> Option<T> {
@@ -139,15 +347,20 @@ impl<T, const N: usize> Vec<T, N> {
     /// Appends an `item` to the back of the collection
     #[inline]
     pub fn push(&mut self, item: T) -> Result<(), T> {
-        #[cfg(feature = "alloc")]
+        #[cfg(alloc_backend)]
         {
+            self.grow(1);
             self.0.push(item);
+            self.record(None);
 
             Ok(())
         }
-        #[cfg(not(feature = "alloc"))]
+        #[cfg(not(alloc_backend))]
         {
-            self.0.push(item)
+            let res = self.0.push(item);
+            self.record(res.is_err().then_some(1));
+
+            res
         }
     }
 
@@ -164,9 +377,12 @@ impl<T, const N: usize> Vec<T, N> {
     /// Appends an `item` to the back of the collection.
     #[inline]
     pub unsafe fn push_unchecked(&mut self, item: T) {
-        #[cfg(feature = "alloc")]
-        self.0.push(item);
-        #[cfg(not(feature = "alloc"))]
+        #[cfg(alloc_backend)]
+        {
+            self.grow(1);
+            self.0.push(item);
+        }
+        #[cfg(not(alloc_backend))]
         self.0.push_unchecked(item);
     }
 
@@ -188,17 +404,21 @@ impl<T, const N: usize> Vec<T, N> {
     where
         T: Clone,
     {
-        #[cfg(feature = "alloc")]
+        #[cfg(alloc_backend)]
         {
+            self.grow(new_len.saturating_sub(self.len()));
             self.0.resize(new_len, value);
+            self.record(None);
 
             Ok(())
         }
-        #[cfg(not(feature = "alloc"))]
+        #[cfg(not(alloc_backend))]
         {
-            self.0
-                .resize(new_len, value)
-                .map_err(|_| crate::Error::BufferOverflow)
+            let len = self.len();
+            let res = self.0.resize(new_len, value);
+            self.record(res.is_err().then(|| new_len - len));
+
+            res.map_err(|_| crate::Error::overflow::<Self>(len, new_len - len, N))
         }
     }
 
@@ -218,6 +438,7 @@ impl<T, const N: usize> Vec<T, N> {
     }
 
     /// Removes an element from the vector and returns it.
+    #[cfg(not(feature = "panic-free"))]
     #[inline]
     pub fn swap_remove(&mut self, index: usize) -> T {
         self.0.swap_remove(index)
@@ -226,7 +447,7 @@ impl<T, const N: usize> Vec<T, N> {
     /// Removes an element from the vector and returns it.
     #[inline]
     pub unsafe fn swap_remove_unchecked(&mut self, index: usize) -> T {
-        self.swap_remove(index)
+        self.0.swap_remove(index)
     }
 
     /// Returns true if the vec is at full capacity.
@@ -241,6 +462,30 @@ impl<T, const N: usize> Vec<T, N> {
         self.0.is_empty()
     }
 
+    /// Compares the elements with `other` lexicographically.
+    ///
+    /// Unlike [`Ord::cmp`], which requires both sides to be of the same type, this allows a total
+    /// ordering between vectors of different capacities, slices and arrays.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use core::cmp::Ordering;
+    /// use mayheap::Vec;
+    ///
+    /// let a = Vec::<u8, 4>::from_slice(&[1, 2]).unwrap();
+    /// let b = Vec::<u8, 8>::from_slice(&[1, 3]).unwrap();
+    /// assert_eq!(a.cmp_slice(&b), Ordering::Less);
+    /// assert_eq!(a.cmp_slice(&[1]), Ordering::Greater);
+    /// ```
+    #[inline]
+    pub fn cmp_slice(&self, other: &[T]) -> Ordering
+    where
+        T: Ord,
+    {
+        self.as_slice().cmp(other)
+    }
+
     /// Returns `true` if `needle` is a prefix of the Vec.
     #[inline]
     pub fn starts_with(&self, needle: &[T]) -> bool
@@ -263,22 +508,29 @@ impl<T, const N: usize> Vec<T, N> {
 
     /// Inserts an element at position `index` within the vector, shifting all
     /// elements after it to the right.
+    #[cfg(not(feature = "panic-free"))]
     #[inline]
     pub fn insert(&mut self, index: usize, element: T) -> Result<(), T> {
-        #[cfg(feature = "alloc")]
+        #[cfg(alloc_backend)]
         {
+            self.grow(1);
             self.0.insert(index, element);
+            self.record(None);
 
             Ok(())
         }
-        #[cfg(not(feature = "alloc"))]
+        #[cfg(not(alloc_backend))]
         {
-            self.0.insert(index, element)
+            let res = self.0.insert(index, element);
+            self.record(res.is_err().then_some(1));
+
+            res
         }
     }
 
     /// Removes and returns the element at position `index` within the vector,
     /// shifting all elements after it to the left.
+    #[cfg(not(feature = "panic-free"))]
     #[inline]
     pub fn remove(&mut self, index: usize) -> T {
         self.0.remove(index)
@@ -302,165 +554,1039 @@ impl<T, const N: usize> Vec<T, N> {
         self.0.retain_mut(f)
     }
 
-    /// Returns a reference to the underlying inner type.
-    #[inline]
-    pub fn inner(&self) -> &Inner<T, N> {
-        &self.0
-    }
-
-    /// Returns a mutable reference to the underlying inner type.
+    /// Retains only the elements specified by the predicate, without preserving the order.
+    ///
+    /// Unlike [`Vec::retain`], the removed elements are replaced by the last element instead of
+    /// shifting all the following ones, so this is *O*(n) with no moves for the retained elements
+    /// in between.
     #[inline]
-    pub fn inner_mut(&mut self) -> &mut Inner<T, N> {
-        &mut self.0
+    pub fn retain_unordered<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut i = 0;
+        while i < self.len() {
+            if f(&self[i]) {
+                i += 1;
+            } else {
+                // Checks the element swapped in on the next iteration.
+                self.0.swap_remove(i);
+            }
+        }
     }
 
-    /// Consumes the `Vec` and returns the inner type.
+    /// Returns mutable references to many elements at once.
+    ///
+    /// Returns `None` if any of the indices is out of bounds or if the same index is given more
+    /// than once.
     #[inline]
-    pub fn into_inner(self) -> Inner<T, N> {
-        self.0
+    pub fn get_many_mut<const K: usize>(&mut self, indices: [usize; K]) -> Option<[&mut T; K]> {
+        self.as_mut_slice().get_disjoint_mut(indices).ok()
     }
-}
 
-impl<T, const N: usize> Default for Vec<T, N> {
+    /// Returns the elements in this vector, `other` or both, which must be sorted in ascending
+    /// order.
+    ///
+    /// The result is sorted, with the elements present in both included once. Fails with
+    /// [`crate::Error::BufferOverflow`] if it doesn't fit in the capacity `M`.
     #[inline]
-    fn default() -> Self {
-        Self::new()
+    pub fn union_sorted<const M: usize>(&self, other: &[T]) -> crate::Result<Vec<T, M>>
+    where
+        T: Ord + Clone,
+    {
+        merge_sorted(self, other, [true, true, true])
     }
-}
 
-impl<const N: usize> fmt::Write for Vec<u8, N> {
+    /// Returns the elements present in both this vector and `other`, which must be sorted in
+    /// ascending order.
+    ///
+    /// The result is sorted. Fails with [`crate::Error::BufferOverflow`] if it doesn't fit in the
+    /// capacity `M`.
     #[inline]
-    fn write_str(&mut self, s: &str) -> fmt::Result {
-        #[cfg(feature = "alloc")]
-        {
-            self.0.extend_from_slice(s.as_bytes());
-
-            Ok(())
-        }
-        #[cfg(not(feature = "alloc"))]
-        {
-            self.0.write_str(s)
-        }
+    pub fn intersect_sorted<const M: usize>(&self, other: &[T]) -> crate::Result<Vec<T, M>>
+    where
+        T: Ord + Clone,
+    {
+        merge_sorted(self, other, [false, true, false])
     }
-}
-
-impl<'a, T: Clone, const N: usize> TryFrom<&'a [T]> for Vec<T, N> {
-    type Error = crate::Error;
 
+    /// Returns the elements in this vector that are not in `other`, both of which must be sorted
+    /// in ascending order.
+    ///
+    /// The result is sorted. Fails with [`crate::Error::BufferOverflow`] if it doesn't fit in the
+    /// capacity `M`.
     #[inline]
-    fn try_from(slice: &'a [T]) -> Result<Self, Self::Error> {
-        Vec::from_slice(slice)
+    pub fn difference_sorted<const M: usize>(&self, other: &[T]) -> crate::Result<Vec<T, M>>
+    where
+        T: Ord + Clone,
+    {
+        merge_sorted(self, other, [true, false, false])
     }
-}
 
-impl<T, const N: usize> From<Vec<T, N>> for Inner<T, N> {
+    /// Sorts the vector, preserving the order of equal elements.
+    ///
+    /// Unlike `[T]::sort`, this is also available with `heapless` backend, where it doesn't
+    /// allocate, sorting in place in *O*(n \* log(n)²) time instead. With `alloc` backend, this is
+    /// the same as `[T]::sort`.
     #[inline]
-    fn from(vec: Vec<T, N>) -> Self {
-        vec.0
+    pub fn sort_stable(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_stable_by(T::cmp);
     }
-}
 
-impl<T, const N: usize> From<[T; N]> for Vec<T, N> {
+    /// Sorts the vector with the comparison function `compare`, preserving the order of equal
+    /// elements.
+    ///
+    /// See [`Vec::sort_stable`] for details.
     #[inline]
-    fn from(array: [T; N]) -> Self {
-        Self(Inner::from(array))
+    pub fn sort_stable_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        #[cfg(alloc_backend)]
+        self.0.sort_by(compare);
+        #[cfg(not(alloc_backend))]
+        stable_sort(self, &mut |a, b| compare(a, b) == Ordering::Less);
     }
-}
 
-impl<T, const N: usize> From<Inner<T, N>> for Vec<T, N> {
+    /// Sorts the vector by the key extracted by `f`, preserving the order of elements with equal
+    /// keys.
+    ///
+    /// See [`Vec::sort_stable`] for details.
     #[inline]
-    fn from(inner: Inner<T, N>) -> Self {
-        Self(inner)
+    pub fn sort_stable_by_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.sort_stable_by(|a, b| f(a).cmp(&f(b)));
     }
-}
 
-impl<T, const N: usize> Extend<T> for Vec<T, N> {
+    /// Removes consecutive repeated elements, which for a sorted vector means all the duplicates.
     #[inline]
-    fn extend<I>(&mut self, iter: I)
+    pub fn dedup_sorted(&mut self)
     where
-        I: IntoIterator<Item = T>,
+        T: PartialEq,
     {
-        self.extend(iter)
+        let mut kept = 1;
+        for i in 1..self.len() {
+            if self[i] != self[kept - 1] {
+                self.swap(i, kept);
+                kept += 1;
+            }
+        }
+        self.truncate(kept);
     }
-}
 
-impl<'a, T, const N: usize> Extend<&'a T> for Vec<T, N>
-where
-    T: 'a + Copy,
-{
+    /// Removes the specified range from the vector in bulk, returning all removed elements as an
+    /// iterator.
+    ///
+    /// If the iterator is dropped before being fully consumed, it drops the remaining removed
+    /// elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the starting point is greater than the end point or if the end point is greater
+    /// than the length of the vector.
+    #[cfg(not(feature = "panic-free"))]
     #[inline]
-    fn extend<I>(&mut self, iter: I)
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, T>
     where
-        I: IntoIterator<Item = &'a T>,
+        R: ops::RangeBounds<usize>,
     {
-        self.extend(iter.into_iter().cloned())
+        Drain {
+            iter: self.0.drain(range),
+        }
     }
-}
 
-impl<T, const N: usize> hash::Hash for Vec<T, N>
-where
-    T: core::hash::Hash,
-{
-    #[inline]
-    fn hash<H: hash::Hasher>(&self, state: &mut H) {
-        hash::Hash::hash(&self.0, state);
-    }
-}
+    /// Removes the first `at` elements from the vector and returns them, leaving the rest.
+    ///
+    /// This is the mirror image of `split_off`, handy for consuming frames from the front of a
+    /// receive buffer. The remaining elements are moved to the front in one go.
+    ///
+    /// Fails with [`crate::Error::OutOfBounds`] if `at` is greater than the length of the vector.
+    /// When `alloc` feature is enabled, [`crate::Error::OutOfMemory`] is returned if allocation
+    /// fails. Nothing is removed on failure.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mayheap::Vec;
+    ///
+    /// let mut buf = Vec::<u8, 8>::from_slice(b"abcdef").unwrap();
+    /// let frame = buf.split_to(4).unwrap();
+    /// assert_eq!(frame, *b"abcd");
+    /// assert_eq!(buf, *b"ef");
+    /// ```
+    #[inline]
+    pub fn split_to(&mut self, at: usize) -> crate::Result<Self> {
+        if at > self.len() {
+            return Err(crate::Error::OutOfBounds {
+                index: at,
+                len: self.len(),
+            });
+        }
 
-impl<'a, T, const N: usize> IntoIterator for &'a Vec<T, N> {
-    type Item = &'a T;
-    type IntoIter = slice::Iter<'a, T>;
+        let mut head = Self::new();
+        head.try_reserve(at)?;
+        // We just made sure it fits.
+        head.0.extend(self.0.drain(..at));
 
-    #[inline]
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter()
+        Ok(head)
     }
-}
-
-impl<'a, T, const N: usize> IntoIterator for &'a mut Vec<T, N> {
-    type Item = &'a mut T;
-    type IntoIter = slice::IterMut<'a, T>;
 
+    /// Returns the remaining spare capacity of the vector as a slice of `MaybeUninit<T>`.
+    ///
+    /// The returned slice can be used to fill the vector with data before marking the data as
+    /// initialized using [`Vec::set_len`].
+    ///
+    /// With `alloc` backend, the capacity is first grown to `N` if it's less, so the returned slice
+    /// always covers at least `N - len` elements, regardless of the backend.
     #[inline]
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter_mut()
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        #[cfg(alloc_backend)]
+        self.0.reserve_exact(N.saturating_sub(self.len()));
+
+        self.0.spare_capacity_mut()
     }
-}
 
-impl<T, const N: usize> FromIterator<T> for Vec<T, N> {
+    /// Forces the length of the vector to `new_len`.
+    ///
+    /// # Safety
+    ///
+    /// - `new_len` must be less than or equal to [`Vec::capacity`].
+    /// - The elements at `old_len..new_len` must be initialized.
     #[inline]
-    fn from_iter<I>(iter: I) -> Self
-    where
-        I: IntoIterator<Item = T>,
-    {
-        Self(FromIterator::<T>::from_iter(iter))
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        self.0.set_len(new_len)
     }
-}
-
-/// An iterator that moves out of an [`Vec`][`Vec`].
-///
-/// This struct is created by calling the `into_iter` method on [`Vec`][`Vec`].
-#[derive(Clone, Debug)]
-pub struct IntoIter<T, const N: usize> {
-    #[cfg(feature = "alloc")]
-    iter: crate::reexports::alloc::vec::IntoIter<T>,
-    // FIXME: Once the fix for https://github.com/rust-embedded/heapless/issues/530 is released. We
-    // can turn this into a wrapper around `heapless::vec::IntoIter`.
-    #[cfg(not(feature = "alloc"))]
-    iter: heapless::vec::IntoIter<T, N, usize>,
-}
 
-impl<T, const N: usize> Iterator for IntoIter<T, N> {
-    type Item = T;
+    /// Returns a reference to the underlying inner type.
+    #[deprecated(
+        note = "The inner type depends on the backend. Use `convert::AsHeapless` or `convert::AsAlloc` instead."
+    )]
     #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next()
+    pub fn inner(&self) -> &Inner<T, N> {
+        &self.0
     }
-}
 
-impl<T, const N: usize> IntoIterator for Vec<T, N> {
-    type Item = T;
-    type IntoIter = IntoIter<T, N>;
+    /// Returns a mutable reference to the underlying inner type.
+    #[deprecated(
+        note = "The inner type depends on the backend. Use `convert::AsHeapless` or `convert::AsAlloc` instead."
+    )]
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut Inner<T, N> {
+        &mut self.0
+    }
+
+    /// Consumes the `Vec` and returns the inner type.
+    #[deprecated(
+        note = "The inner type depends on the backend. Use `convert::AsHeapless`, `convert::AsAlloc` or `convert::TryIntoStd` instead."
+    )]
+    #[inline]
+    pub fn into_inner(self) -> Inner<T, N> {
+        self.0
+    }
+
+    /// Converts the `Vec` into one with a capacity of `M`.
+    ///
+    /// Fails with [`crate::Error::BufferOverflow`] if the current length exceeds `M`. If `M >= N`,
+    /// use [`Vec::grow_capacity`] instead, which can not fail.
+    #[inline]
+    pub fn recapacity<const M: usize>(self) -> crate::Result<Vec<T, M>> {
+        #[cfg(alloc_backend)]
+        {
+            let mut v = self.0;
+            v.reserve(M.saturating_sub(v.len()));
+
+            Ok(Vec(v))
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            if self.len() > M {
+                return Err(crate::Error::overflow::<Vec<T, M>>(0, self.len(), M));
+            }
+            let mut v = Vec::new();
+            v.0.extend(self.0);
+
+            Ok(v)
+        }
+    }
+
+    /// Converts the `Vec` into one with a capacity of `M`, where `M` must be at least `N`.
+    ///
+    /// This is checked at compile-time.
+    #[inline]
+    pub fn grow_capacity<const M: usize>(self) -> Vec<T, M> {
+        const { assert!(M >= N, "`M` must be greater than or equal to `N`") };
+
+        match self.recapacity() {
+            Ok(v) => v,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Converts each element with `f`, collecting the results into a `Vec` with a capacity of `M`.
+    ///
+    /// Stops at the first error returned by `f`. Fails with [`crate::Error::BufferOverflow`]
+    /// (converted into `E`) if the current length exceeds `M`, before calling `f` at all.
+    #[inline]
+    pub fn try_map<U, E, const M: usize>(
+        self,
+        mut f: impl FnMut(T) -> Result<U, E>,
+    ) -> Result<Vec<U, M>, E>
+    where
+        E: From<crate::Error>,
+    {
+        #[cfg(not(alloc_backend))]
+        if self.len() > M {
+            return Err(crate::Error::overflow::<Vec<U, M>>(0, self.len(), M).into());
+        }
+        let mut v = Vec::new();
+        #[cfg(alloc_backend)]
+        v.0.reserve(self.len());
+        for item in self.0 {
+            // We just made sure it fits.
+            let _ = v.push(f(item)?);
+        }
+
+        Ok(v)
+    }
+
+    /// Converts each element with `f`, keeping the capacity.
+    #[inline]
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Vec<U, N> {
+        let mut v = Vec::new();
+        #[cfg(alloc_backend)]
+        v.0.reserve(self.len());
+        for item in self.0 {
+            // Can't overflow since the length doesn't change.
+            let _ = v.push(f(item));
+        }
+
+        v
+    }
+
+    /// Returns an object implementing [`fmt::Display`] by joining the elements with `separator`.
+    ///
+    /// This allows logging the elements without building an intermediate `String`. The formatting
+    /// options (such as the width) are applied to each element.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use core::fmt::Write;
+    /// use mayheap::{String, Vec};
+    ///
+    /// let v = Vec::<f32, 4>::from_slice(&[1.0, 2.5, 3.25]).unwrap();
+    /// let mut s = String::<32>::new();
+    /// write!(s, "[{:.1}]", v.display(", ")).unwrap();
+    /// assert_eq!(s, "[1.0, 2.5, 3.2]");
+    /// ```
+    #[inline]
+    pub fn display<'a>(&'a self, separator: &'a str) -> Joined<'a, T>
+    where
+        T: fmt::Display,
+    {
+        Joined {
+            items: self.as_slice(),
+            separator,
+        }
+    }
+
+    // Reserves the capacity for `additional` more elements in multiples of `N`, if the
+    // `linear-growth` feature is enabled. Otherwise, the growth is left to the inner type.
+    #[cfg(alloc_backend)]
+    #[inline]
+    fn grow(&mut self, additional: usize) {
+        #[cfg(feature = "linear-growth")]
+        if let Some(additional) = linear_growth::<N>(self.len(), self.0.capacity(), additional) {
+            self.0.reserve_exact(additional);
+        }
+        #[cfg(not(feature = "linear-growth"))]
+        let _ = additional;
+    }
+
+    // Records the length (including the `requested` elements that didn't fit, on overflow) for the
+    // instrumentation, and logs the overflows.
+    #[inline]
+    fn record(&self, overflow: Option<usize>) {
+        #[cfg(feature = "instrument")]
+        crate::instrument::record::<Self>(crate::instrument::Kind::Vec, self.len(), overflow);
+        #[cfg(any(feature = "log", feature = "defmt"))]
+        if let Some(requested) = overflow {
+            crate::error::log_overflow::<Self>(self.len(), requested, N);
+        }
+        #[cfg(not(any(feature = "instrument", feature = "log", feature = "defmt")))]
+        let _ = overflow;
+    }
+}
+
+impl<const N: usize> Vec<u8, N> {
+    // Appends as many bytes as fit in the capacity, failing only if none do. This is the write
+    // semantics of the I/O traits.
+    #[cfg(any(feature = "embedded-io", feature = "futures-io"))]
+    #[inline]
+    pub(crate) fn write_bytes(&mut self, buf: &[u8]) -> crate::Result<usize> {
+        #[cfg(alloc_backend)]
+        self.try_reserve(buf.len())?;
+        #[cfg(not(alloc_backend))]
+        if !buf.is_empty() && self.is_full() {
+            self.record(Some(buf.len()));
+
+            return Err(crate::Error::overflow::<Self>(self.len(), buf.len(), N));
+        }
+
+        let len = self.len();
+        self.extend_from_slice_truncating(buf);
+
+        Ok(self.len() - len)
+    }
+
+    /// Reads all bytes from `reader` until EOF or until the capacity is reached, appending them.
+    ///
+    /// With `alloc` backend, the capacity is only reached if the allocation fails. On error, the
+    /// bytes read before the error are kept.
+    #[cfg(feature = "embedded-io")]
+    #[inline]
+    pub fn read_to_end<R>(&mut self, reader: &mut R) -> Result<ReadToEnd, R::Error>
+    where
+        R: embedded_io::Read + ?Sized,
+    {
+        self.read_with(|buf| reader.read(buf))
+    }
+
+    /// Reads all bytes from `reader` until EOF or until the capacity is reached, appending them.
+    ///
+    /// This is the same as [`Vec::read_to_end`] but for [`std::io::Read`]. Reads failing with
+    /// [`std::io::ErrorKind::Interrupted`] are retried.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn read_to_end_std<R>(&mut self, reader: &mut R) -> std::io::Result<ReadToEnd>
+    where
+        R: std::io::Read + ?Sized,
+    {
+        self.read_with(|buf| loop {
+            match reader.read(buf) {
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                res => break res,
+            }
+        })
+    }
+
+    // Fills the spare capacity (growing it with `alloc` backend) through `read` until it returns
+    // `0`.
+    #[cfg(any(feature = "embedded-io", feature = "std"))]
+    fn read_with<E>(
+        &mut self,
+        mut read: impl FnMut(&mut [u8]) -> Result<usize, E>,
+    ) -> Result<ReadToEnd, E> {
+        let start = self.len();
+        // The end of the zeroed bytes of the spare capacity.
+        let mut zeroed = start;
+        let res = loop {
+            if self.is_full() {
+                #[cfg(alloc_backend)]
+                if self.try_reserve(N.max(READ_CHUNK)).is_err() {
+                    break Ok(false);
+                }
+                #[cfg(not(alloc_backend))]
+                break Ok(false);
+            }
+            let len = self.len();
+            let spare = self.0.spare_capacity_mut();
+            // Readers get an initialized buffer, so zero the bytes that aren't yet. The length is
+            // only ever set to the bytes actually read, so that the instrumentation sees those.
+            for byte in &mut spare[zeroed.max(len) - len..] {
+                byte.write(0);
+            }
+            zeroed = len + spare.len();
+            // SAFETY: We just made sure all the bytes are initialized.
+            let buf = unsafe { &mut *(spare as *mut [MaybeUninit<u8>] as *mut [u8]) };
+            match read(buf) {
+                Ok(0) => break Ok(true),
+                // SAFETY: The bytes are initialized and within the capacity.
+                Ok(n) => unsafe { self.0.set_len(len + n.min(buf.len())) },
+                Err(e) => break Err(e),
+            }
+        };
+        self.record(None);
+
+        res.map(|eof| ReadToEnd {
+            read: self.len() - start,
+            eof,
+        })
+    }
+
+    /// Appends `string` escaped as the contents of a JSON string (without the quotes).
+    ///
+    /// Quotes, backslashes and control characters are escaped. Nothing is appended on failure.
+    #[inline]
+    pub fn push_str_json_escaped(&mut self, string: &str) -> crate::Result<()> {
+        let len = self.len();
+        let res = crate::escape::write_json(string, |s| self.extend_from_slice(s.as_bytes()));
+        if res.is_err() {
+            self.truncate(len);
+        }
+
+        res
+    }
+
+    /// Appends `string` escaped the same way as [`str::escape_debug`] (without the quotes).
+    ///
+    /// Nothing is appended on failure.
+    #[inline]
+    pub fn push_str_debug_escaped(&mut self, string: &str) -> crate::Result<()> {
+        let len = self.len();
+        let res = crate::escape::write_debug(string, |s| self.extend_from_slice(s.as_bytes()));
+        if res.is_err() {
+            self.truncate(len);
+        }
+
+        res
+    }
+
+    /// Appends the variable-length (unsigned LEB128) encoding of `value`.
+    ///
+    /// Nothing is appended on failure. See the [`crate::varint`] module for decoding.
+    #[inline]
+    pub fn put_varint_u32(&mut self, value: u32) -> crate::Result<()> {
+        self.put_varint_u64(value.into())
+    }
+
+    /// Appends the variable-length (unsigned LEB128) encoding of `value`.
+    ///
+    /// Nothing is appended on failure. See the [`crate::varint`] module for decoding.
+    #[inline]
+    pub fn put_varint_u64(&mut self, value: u64) -> crate::Result<()> {
+        self.extend_from_slice(crate::varint::encode(
+            value,
+            &mut [0; crate::varint::MAX_LEN],
+        ))
+    }
+
+    /// Appends the decimal representation of the signed integer `value`.
+    ///
+    /// Unlike going through [`core::fmt`], this uses a small and fast formatting routine.
+    #[cfg(feature = "itoa")]
+    #[inline]
+    pub fn push_int(&mut self, value: impl crate::num::SignedInt) -> crate::Result<()> {
+        self.extend_from_slice(itoa::Buffer::new().format(value).as_bytes())
+    }
+
+    /// Appends the decimal representation of the unsigned integer `value`.
+    ///
+    /// Unlike going through [`core::fmt`], this uses a small and fast formatting routine.
+    #[cfg(feature = "itoa")]
+    #[inline]
+    pub fn push_uint(&mut self, value: impl crate::num::UnsignedInt) -> crate::Result<()> {
+        self.extend_from_slice(itoa::Buffer::new().format(value).as_bytes())
+    }
+
+    /// Appends the decimal representation of `value`, with `precision` digits after the decimal
+    /// point.
+    ///
+    /// The last digit is rounded half away from zero so the output may differ slightly from
+    /// [`core::fmt`]. `precision` is capped at 19. Nothing is appended on failure.
+    #[cfg(feature = "itoa")]
+    #[inline]
+    pub fn push_float(&mut self, value: impl Into<f64>, precision: usize) -> crate::Result<()> {
+        let len = self.len();
+        let res = crate::num::write_float(value.into(), precision, |s| {
+            self.extend_from_slice(s.as_bytes())
+        });
+        if res.is_err() {
+            self.truncate(len);
+        }
+
+        res
+    }
+}
+
+/// Merges the sorted slices `a` and `b`, keeping the elements only in `a`, in both (once) and only
+/// in `b`, according to `keep`.
+fn merge_sorted<T, const M: usize>(a: &[T], b: &[T], keep: [bool; 3]) -> crate::Result<Vec<T, M>>
+where
+    T: Ord + Clone,
+{
+    let [keep_a, keep_both, keep_b] = keep;
+    let (mut a, mut b) = (a, b);
+    let mut v = Vec::new();
+    loop {
+        let (item, keep) = match (a.split_first(), b.split_first()) {
+            (Some((x, rest)), None) => {
+                a = rest;
+                (x, keep_a)
+            }
+            (None, Some((y, rest))) => {
+                b = rest;
+                (y, keep_b)
+            }
+            (Some((x, rest_a)), Some((y, rest_b))) => match x.cmp(y) {
+                Ordering::Less => {
+                    a = rest_a;
+                    (x, keep_a)
+                }
+                Ordering::Greater => {
+                    b = rest_b;
+                    (y, keep_b)
+                }
+                Ordering::Equal => {
+                    a = rest_a;
+                    b = rest_b;
+                    (x, keep_both)
+                }
+            },
+            (None, None) => return Ok(v),
+        };
+        if keep && v.push(item.clone()).is_err() {
+            return Err(crate::Error::overflow::<Vec<T, M>>(M, 1, M));
+        }
+    }
+}
+
+/// Sorts `v` in place without allocating, preserving the order of equal elements.
+///
+/// This is a merge sort, merging the sorted halves in place through rotations, and falling back to
+/// an insertion sort for short slices.
+#[cfg(not(alloc_backend))]
+fn stable_sort<T, F>(v: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if v.len() <= 16 {
+        for i in 1..v.len() {
+            let mut j = i;
+            while j > 0 && is_less(&v[j], &v[j - 1]) {
+                v.swap(j, j - 1);
+                j -= 1;
+            }
+        }
+
+        return;
+    }
+
+    let mid = v.len() / 2;
+    stable_sort(&mut v[..mid], is_less);
+    stable_sort(&mut v[mid..], is_less);
+    merge_in_place(v, mid, is_less);
+}
+
+/// Merges the sorted `v[..mid]` and `v[mid..]` in place, preserving the order of equal elements.
+#[cfg(not(alloc_backend))]
+fn merge_in_place<T, F>(v: &mut [T], mid: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let (len1, len2) = (mid, v.len() - mid);
+    if len1 == 0 || len2 == 0 || !is_less(&v[mid], &v[mid - 1]) {
+        return;
+    }
+    if len1 + len2 == 2 {
+        v.swap(0, 1);
+
+        return;
+    }
+
+    // Split the longer run in half and the other one around its middle element, so that all the
+    // elements before both cuts belong before all the elements after them.
+    let (left, right) = v.split_at(mid);
+    let (cut1, cut2) = if len1 > len2 {
+        let cut1 = len1 / 2;
+        let pivot = &left[cut1];
+
+        (cut1, mid + right.partition_point(|x| is_less(x, pivot)))
+    } else {
+        let cut2 = len2 / 2;
+        let pivot = &right[cut2];
+
+        (left.partition_point(|x| !is_less(pivot, x)), mid + cut2)
+    };
+    v[cut1..cut2].rotate_left(mid - cut1);
+    let new_mid = cut1 + cut2 - mid;
+    merge_in_place(&mut v[..new_mid], cut1, is_less);
+    merge_in_place(&mut v[new_mid..], cut2 - new_mid, is_less);
+}
+
+/// Returns the number of elements to reserve (exactly) for `additional` more elements to fit in a
+/// collection of length `len` and capacity `capacity`, such that the new capacity is a multiple of
+/// `N`. Returns `None` if they already fit.
+#[cfg(all(alloc_backend, feature = "linear-growth"))]
+#[inline]
+pub(crate) fn linear_growth<const N: usize>(
+    len: usize,
+    capacity: usize,
+    additional: usize,
+) -> Option<usize> {
+    let required = len.saturating_add(additional);
+    if required <= capacity {
+        return None;
+    }
+    let step = N.max(1);
+
+    Some(required.div_ceil(step).saturating_mul(step) - len)
+}
+
+/// With `alloc` backend, the clone gets a capacity of at least `N`, like a new vector.
+impl<T: Clone, const N: usize> Clone for Vec<T, N> {
+    #[inline]
+    fn clone(&self) -> Self {
+        #[cfg(alloc_backend)]
+        {
+            let mut v = Inner::with_capacity(self.len().max(N));
+            v.extend_from_slice(&self.0);
+
+            Self(v)
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            Self(self.0.clone())
+        }
+    }
+
+    #[inline]
+    fn clone_from(&mut self, source: &Self) {
+        self.0.clone_from(&source.0);
+    }
+}
+
+impl<T, const N: usize> Default for Vec<T, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Write for Vec<u8, N> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.extend_from_slice(s.as_bytes()).map_err(|_| fmt::Error)
+    }
+}
+
+impl<'a, T: Clone, const N: usize> TryFrom<&'a [T]> for Vec<T, N> {
+    type Error = crate::Error;
+
+    #[inline]
+    fn try_from(slice: &'a [T]) -> Result<Self, Self::Error> {
+        Vec::from_slice(slice)
+    }
+}
+
+impl<T, const N: usize> From<Vec<T, N>> for Inner<T, N> {
+    #[inline]
+    fn from(vec: Vec<T, N>) -> Self {
+        vec.0
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for Vec<T, N> {
+    #[inline]
+    fn from(array: [T; N]) -> Self {
+        Self(Inner::from(array))
+    }
+}
+
+impl<T, const N: usize> From<Inner<T, N>> for Vec<T, N> {
+    #[inline]
+    fn from(inner: Inner<T, N>) -> Self {
+        Self(inner)
+    }
+}
+
+#[cfg(not(alloc_backend))]
+impl<T, const N: usize> crate::convert::AsHeapless for Vec<T, N> {
+    type Heapless = heapless::Vec<T, N>;
+
+    #[inline]
+    fn as_heapless(&self) -> &Self::Heapless {
+        &self.0
+    }
+
+    #[inline]
+    fn as_heapless_mut(&mut self) -> &mut Self::Heapless {
+        &mut self.0
+    }
+
+    #[inline]
+    fn into_heapless(self) -> Self::Heapless {
+        self.0
+    }
+
+    #[inline]
+    fn from_heapless(heapless: Self::Heapless) -> Self {
+        Self(heapless)
+    }
+
+    #[inline]
+    fn from_heapless_ref(heapless: &Self::Heapless) -> &Self {
+        // SAFETY: `Vec` is `#[repr(transparent)]` over `Self::Heapless`.
+        unsafe { &*(heapless as *const Self::Heapless as *const Self) }
+    }
+
+    #[inline]
+    fn from_heapless_mut(heapless: &mut Self::Heapless) -> &mut Self {
+        // SAFETY: `Vec` is `#[repr(transparent)]` over `Self::Heapless`.
+        unsafe { &mut *(heapless as *mut Self::Heapless as *mut Self) }
+    }
+}
+
+#[cfg(alloc_backend)]
+impl<T, const N: usize> crate::convert::AsAlloc for Vec<T, N> {
+    type Alloc = crate::reexports::alloc::vec::Vec<T>;
+
+    #[inline]
+    fn as_alloc(&self) -> &Self::Alloc {
+        &self.0
+    }
+
+    #[inline]
+    fn as_alloc_mut(&mut self) -> &mut Self::Alloc {
+        &mut self.0
+    }
+
+    #[inline]
+    fn into_alloc(self) -> Self::Alloc {
+        self.0
+    }
+
+    #[inline]
+    fn from_alloc(alloc: Self::Alloc) -> Self {
+        Self(alloc)
+    }
+
+    #[inline]
+    fn from_alloc_ref(alloc: &Self::Alloc) -> &Self {
+        // SAFETY: `Vec` is `#[repr(transparent)]` over `Self::Alloc`.
+        unsafe { &*(alloc as *const Self::Alloc as *const Self) }
+    }
+
+    #[inline]
+    fn from_alloc_mut(alloc: &mut Self::Alloc) -> &mut Self {
+        // SAFETY: `Vec` is `#[repr(transparent)]` over `Self::Alloc`.
+        unsafe { &mut *(alloc as *mut Self::Alloc as *mut Self) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, const N: usize> crate::convert::TryIntoStd for Vec<T, N> {
+    type Std = crate::reexports::alloc::vec::Vec<T>;
+
+    #[inline]
+    fn try_into_std(self) -> crate::Result<Self::Std> {
+        #[cfg(alloc_backend)]
+        {
+            Ok(self.0)
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            let mut v = Self::Std::new();
+            v.try_reserve_exact(self.len())?;
+            v.extend(self.0);
+
+            Ok(v)
+        }
+    }
+}
+
+#[cfg(not(feature = "panic-free"))]
+impl<T, const N: usize> Extend<T> for Vec<T, N> {
+    #[inline]
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.extend(iter)
+    }
+}
+
+#[cfg(not(feature = "panic-free"))]
+impl<'a, T, const N: usize> Extend<&'a T> for Vec<T, N>
+where
+    T: 'a + Copy,
+{
+    #[inline]
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = &'a T>,
+    {
+        self.extend(iter.into_iter().cloned())
+    }
+}
+
+impl<T, const N: usize> hash::Hash for Vec<T, N>
+where
+    T: core::hash::Hash,
+{
+    #[inline]
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        hash::Hash::hash(&self.0, state);
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a Vec<T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut Vec<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(not(feature = "panic-free"))]
+impl<T, const N: usize> FromIterator<T> for Vec<T, N> {
+    #[inline]
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut v = Self::new();
+        v.extend(iter);
+
+        v
+    }
+}
+
+/// An iterator that moves out of an [`Vec`][`Vec`].
+///
+/// This struct is created by calling the `into_iter` method on [`Vec`][`Vec`].
+#[derive(Clone, Debug)]
+pub struct IntoIter<T, const N: usize> {
+    #[cfg(alloc_backend)]
+    iter: crate::reexports::alloc::vec::IntoIter<T>,
+    // FIXME: Once the fix for https://github.com/rust-embedded/heapless/issues/530 is released. We
+    // can turn this into a wrapper around `heapless::vec::IntoIter`.
+    #[cfg(not(alloc_backend))]
+    iter: heapless::vec::IntoIter<T, N, usize>,
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        self.iter.next_back()
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> core::iter::FusedIterator for IntoIter<T, N> {}
+
+/// The outcome of [`Vec::read_to_end`].
+#[cfg(any(feature = "embedded-io", feature = "std"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReadToEnd {
+    /// The number of bytes read.
+    pub read: usize,
+    /// Whether the reader reached EOF. Otherwise, the capacity was reached and the reader might
+    /// have more bytes to give.
+    pub eof: bool,
+}
+
+// The minimum number of bytes to grow by when reading with `alloc` backend.
+#[cfg(all(alloc_backend, any(feature = "embedded-io", feature = "std")))]
+const READ_CHUNK: usize = 32;
+
+/// A draining iterator for [`Vec`].
+///
+/// This struct is created by [`Vec::drain`].
+#[cfg(not(feature = "panic-free"))]
+#[derive(Debug)]
+pub struct Drain<'a, T> {
+    #[cfg(alloc_backend)]
+    iter: crate::reexports::alloc::vec::Drain<'a, T>,
+    #[cfg(not(alloc_backend))]
+    iter: heapless::vec::Drain<'a, T, usize>,
+}
+
+#[cfg(not(feature = "panic-free"))]
+impl<T> Drain<'_, T> {
+    /// Returns the remaining items of this iterator as a slice.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        self.iter.as_slice()
+    }
+}
+
+#[cfg(not(feature = "panic-free"))]
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+#[cfg(not(feature = "panic-free"))]
+impl<T> DoubleEndedIterator for Drain<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        self.iter.next_back()
+    }
+}
+
+#[cfg(not(feature = "panic-free"))]
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+#[cfg(not(feature = "panic-free"))]
+impl<T> core::iter::FusedIterator for Drain<'_, T> {}
+
+/// Displays the elements of a [`Vec`] joined with a separator.
+///
+/// This struct is created by [`Vec::display`]. See its documentation for more.
+#[derive(Debug, Clone, Copy)]
+pub struct Joined<'a, T> {
+    items: &'a [T],
+    separator: &'a str,
+}
+
+impl<T: fmt::Display> fmt::Display for Joined<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                f.write_str(self.separator)?;
+            }
+            item.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T, const N: usize> IntoIterator for Vec<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
 
     #[inline]
     fn into_iter(self) -> Self::IntoIter {
@@ -603,6 +1729,106 @@ where
     }
 }
 
+impl<T, const N: usize> PartialOrd<[T]> for Vec<T, N>
+where
+    T: PartialOrd,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &[T]) -> Option<Ordering> {
+        self.as_slice().partial_cmp(other)
+    }
+}
+
+impl<T, const N: usize> PartialOrd<Vec<T, N>> for [T]
+where
+    T: PartialOrd,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Vec<T, N>) -> Option<Ordering> {
+        self.partial_cmp(other.as_slice())
+    }
+}
+
+impl<T, const N: usize> PartialOrd<&[T]> for Vec<T, N>
+where
+    T: PartialOrd,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &&[T]) -> Option<Ordering> {
+        self.as_slice().partial_cmp(*other)
+    }
+}
+
+impl<T, const N: usize> PartialOrd<Vec<T, N>> for &[T]
+where
+    T: PartialOrd,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Vec<T, N>) -> Option<Ordering> {
+        (*self).partial_cmp(other.as_slice())
+    }
+}
+
+impl<T, const N: usize> PartialOrd<&mut [T]> for Vec<T, N>
+where
+    T: PartialOrd,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &&mut [T]) -> Option<Ordering> {
+        self.as_slice().partial_cmp(*other)
+    }
+}
+
+impl<T, const N: usize> PartialOrd<Vec<T, N>> for &mut [T]
+where
+    T: PartialOrd,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Vec<T, N>) -> Option<Ordering> {
+        (**self).partial_cmp(other.as_slice())
+    }
+}
+
+impl<T, const N: usize, const M: usize> PartialOrd<[T; M]> for Vec<T, N>
+where
+    T: PartialOrd,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &[T; M]) -> Option<Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<T, const N: usize, const M: usize> PartialOrd<Vec<T, N>> for [T; M]
+where
+    T: PartialOrd,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Vec<T, N>) -> Option<Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<T, const N: usize, const M: usize> PartialOrd<&[T; M]> for Vec<T, N>
+where
+    T: PartialOrd,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &&[T; M]) -> Option<Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<T, const N: usize, const M: usize> PartialOrd<Vec<T, N>> for &[T; M]
+where
+    T: PartialOrd,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Vec<T, N>) -> Option<Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
 impl<T, const N: usize> ops::Deref for Vec<T, N> {
     type Target = [T];
 
@@ -669,6 +1895,252 @@ where
     where
         D: serde_core::Deserializer<'de>,
     {
-        Inner::deserialize(deserializer).map(Self)
+        #[cfg(alloc_backend)]
+        {
+            let mut v = Inner::deserialize(deserializer)?;
+            v.reserve(N.saturating_sub(v.len()));
+
+            Ok(Self(v))
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            use serde_core::de::DeserializeSeed;
+
+            let mut values = Vec::new();
+            VecSeed::new(&mut values).deserialize(deserializer)?;
+
+            Ok(values)
+        }
+    }
+}
+
+/// Deserializes a sequence into an existing [`Vec`], reusing its storage.
+///
+/// The vector is cleared first, so on failure it holds the elements deserialized up to that point.
+/// This is useful for parsing many messages in a row without constructing a new vector for each.
+///
+/// Unlike the [`serde_core::Deserialize`] implementation of [`Vec`], this reports the capacity
+/// and the actual length of the sequence if it doesn't fit.
+///
+/// # Example
+///
+/// ```
+/// use mayheap::{vec::VecSeed, Vec};
+/// use serde_core::de::DeserializeSeed;
+///
+/// let mut values = Vec::<u8, 4>::new();
+/// for json in ["[1, 2]", "[3, 4, 5]"] {
+///     let mut de = serde_json::Deserializer::from_str(json);
+///     VecSeed::new(&mut values).deserialize(&mut de).unwrap();
+/// }
+/// assert_eq!(values, [3, 4, 5]);
+/// ```
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub struct VecSeed<'a, T, const N: usize>(&'a mut Vec<T, N>);
+
+#[cfg(feature = "serde")]
+impl<'a, T, const N: usize> VecSeed<'a, T, N> {
+    /// Creates a new seed, deserializing into `vec`.
+    #[inline]
+    pub fn new(vec: &'a mut Vec<T, N>) -> Self {
+        Self(vec)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T, const N: usize> serde_core::de::DeserializeSeed<'de> for VecSeed<'_, T, N>
+where
+    T: serde_core::Deserialize<'de>,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: serde_core::Deserializer<'de>,
+    {
+        // Clear even if the deserializer fails before visiting.
+        self.0.clear();
+        deserializer.deserialize_seq(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T, const N: usize> serde_core::de::Visitor<'de> for VecSeed<'_, T, N>
+where
+    T: serde_core::Deserialize<'de>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(alloc_backend)]
+        {
+            formatter.write_str("a sequence")
+        }
+        #[cfg(not(alloc_backend))]
+        {
+            write!(formatter, "a sequence of at most {N} elements")
+        }
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error>
+    where
+        A: serde_core::de::SeqAccess<'de>,
+    {
+        self.0.clear();
+        while let Some(value) = seq.next_element()? {
+            if self.0.push(value).is_err() {
+                // Skip the rest to get the actual length.
+                let mut len = N + 1;
+                while seq.next_element::<serde_core::de::IgnoredAny>()?.is_some() {
+                    len += 1;
+                }
+
+                return Err(serde_core::de::Error::invalid_length(len, &self));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "musli")]
+impl<M, T, const N: usize> musli::Encode<M> for Vec<T, N>
+where
+    T: musli::Encode<M>,
+{
+    type Encode = [T];
+
+    #[inline]
+    fn encode<E>(&self, encoder: E) -> Result<(), E::Error>
+    where
+        E: musli::Encoder<Mode = M>,
+    {
+        self.as_slice().encode(encoder)
+    }
+
+    #[inline]
+    fn as_encode(&self) -> &Self::Encode {
+        self.as_slice()
+    }
+}
+
+/// Fails without decoding the remaining elements, as soon as one doesn't fit in the capacity.
+#[cfg(feature = "musli")]
+impl<'de, M, A, T, const N: usize> musli::Decode<'de, M, A> for Vec<T, N>
+where
+    A: musli::Allocator,
+    T: musli::Decode<'de, M, A>,
+{
+    #[inline]
+    fn decode<D>(decoder: D) -> Result<Self, D::Error>
+    where
+        D: musli::Decoder<'de, Mode = M, Allocator = A>,
+    {
+        use musli::{de::SequenceDecoder, Context, Decoder};
+
+        let cx = decoder.cx();
+        decoder.decode_sequence(|seq| {
+            let mut v = Self::new();
+            while let Some(item) = seq.try_decode_next()? {
+                let item = item.decode()?;
+                let len = v.len();
+                if v.push(item).is_err() {
+                    return Err(cx.custom(crate::Error::overflow::<Self>(len, 1, N)));
+                }
+            }
+
+            Ok(v)
+        })
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl<const N: usize> ufmt::uWrite for Vec<u8, N> {
+    type Error = crate::Error;
+
+    #[inline]
+    fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+        self.extend_from_slice(s.as_bytes())
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl<T, const N: usize> ufmt::uDebug for Vec<T, N>
+where
+    T: ufmt::uDebug,
+{
+    #[inline]
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        <[T] as ufmt::uDebug>::fmt(self.as_slice(), f)
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<const N: usize> embedded_io::ErrorType for Vec<u8, N> {
+    type Error = crate::Error;
+}
+
+/// Appends as many bytes as fit in the capacity.
+///
+/// Fails with [`crate::Error::BufferOverflow`] if the vector is already full.
+#[cfg(feature = "embedded-io")]
+impl<const N: usize> embedded_io::Write for Vec<u8, N> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.write_bytes(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Same as the [`embedded_io::Write`] implementation, never pending.
+#[cfg(feature = "embedded-io-async")]
+impl<const N: usize> embedded_io_async::Write for Vec<u8, N> {
+    #[inline]
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        embedded_io::Write::write(self, buf)
+    }
+
+    #[inline]
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Appends as many bytes as fit in the capacity, never pending.
+///
+/// Fails with an error of kind [`std::io::ErrorKind::WriteZero`] if the vector is already full.
+#[cfg(feature = "futures-io")]
+impl<const N: usize> futures_io::AsyncWrite for Vec<u8, N> {
+    #[inline]
+    fn poll_write(
+        self: core::pin::Pin<&mut Self>,
+        _cx: &mut core::task::Context<'_>,
+        buf: &[u8],
+    ) -> core::task::Poll<std::io::Result<usize>> {
+        core::task::Poll::Ready(self.get_mut().write_bytes(buf).map_err(Into::into))
+    }
+
+    #[inline]
+    fn poll_flush(
+        self: core::pin::Pin<&mut Self>,
+        _cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<std::io::Result<()>> {
+        core::task::Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    fn poll_close(
+        self: core::pin::Pin<&mut Self>,
+        _cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<std::io::Result<()>> {
+        core::task::Poll::Ready(Ok(()))
     }
 }